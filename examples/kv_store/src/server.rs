@@ -38,7 +38,10 @@ impl OmniPaxosServer {
                 biased;
 
                 _ = election_interval.tick() => { self.omni_paxos.lock().unwrap().election_timeout(); },
-                _ = outgoing_interval.tick() => { self.send_outgoing_msgs().await; },
+                _ = outgoing_interval.tick() => {
+                    self.omni_paxos.lock().unwrap().tick();
+                    self.send_outgoing_msgs().await;
+                },
                 Some(in_msg) = self.incoming.recv() => { self.omni_paxos.lock().unwrap().handle_incoming(in_msg); },
                 else => { }
             }
@@ -8,13 +8,20 @@ use crate::utils::hocon_kv::LOG_FILE_PATH;
 use crate::utils::logger::create_logger;
 use crate::{
     messages::ballot_leader_election::{
-        BLEMessage, HeartbeatMsg, HeartbeatReply, HeartbeatRequest,
+        BLEMessage, HeartbeatMsg, HeartbeatReply, HeartbeatRequest, LeaderAnnouncement, TimeoutNow,
     },
-    omni_paxos::OmniPaxosConfig,
-    util::NodeId,
+    omni_paxos::{ElectionStateErr, HandleErr, OmniPaxosConfig, PartitionStatus, RejoinPolicy},
+    util::{BallotGenerator, ClusterId, NodeId, ELECTION_STATE_VERSION},
 };
 #[cfg(feature = "logging")]
 use slog::{debug, info, trace, warn, Logger};
+#[cfg(feature = "rejected_ballots")]
+use std::collections::VecDeque;
+#[cfg(feature = "hardened")]
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
 
 /// Used to define an epoch
 #[derive(Clone, Copy, Eq, Debug, Default, Ord, PartialOrd, PartialEq)]
@@ -38,18 +45,156 @@ impl Ballot {
     }
 }
 
+/// A ballot that was rejected for being lower than the ballot of the current leader.
+#[cfg(feature = "rejected_ballots")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RejectedBallot {
+    /// The heartbeat round in which the ballot was rejected.
+    pub hb_round: u32,
+    /// The rejected ballot. The sender of the ballot is `ballot.pid`.
+    pub ballot: Ballot,
+    /// The ballot of the leader at the time of the rejection.
+    pub leader: Ballot,
+}
+
+/// The hooks of the leader election that are set by the user. Hooks are not copied when cloning, see `ElectionCheckpoint`.
+#[derive(Default)]
+struct Hooks {
+    ballot_generator: Option<BallotGenerator>,
+}
+
+impl Clone for Hooks {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A copy of the complete state of the leader election of a server, including its outgoing messages. Created with `OmniPaxos::checkpoint_election()`
+/// and restored with `OmniPaxos::restore_election()`, e.g. to branch the execution in deterministic simulation tests. Hooks such as the ballot generator are not copied,
+/// the server keeps its own hooks when restoring a checkpoint.
+#[derive(Clone)]
+pub struct ElectionCheckpoint(pub(crate) BallotLeaderElection);
+
+/// Encodes the state of the leader election for `BallotLeaderElection::serialize_state()`. Integers are encoded in big-endian.
+#[derive(Default)]
+struct StateWriter(Vec<u8>);
+
+impl StateWriter {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn bool(&mut self, v: bool) {
+        self.0.push(v as u8);
+    }
+
+    fn ballot(&mut self, b: Ballot) {
+        self.u32(b.n);
+        self.u64(b.priority);
+        self.u64(b.pid);
+    }
+
+    fn option_ballot(&mut self, b: Option<Ballot>) {
+        self.bool(b.is_some());
+        self.ballot(b.unwrap_or_default());
+    }
+
+    fn ballots(&mut self, ballots: &[(Ballot, bool)]) {
+        self.u64(ballots.len() as u64);
+        for (b, quorum_connected) in ballots {
+            self.ballot(*b);
+            self.bool(*quorum_connected);
+        }
+    }
+}
+
+/// Decodes the state of the leader election encoded by a `StateWriter`.
+struct StateReader<'a>(&'a [u8]);
+
+impl<'a> StateReader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ElectionStateErr> {
+        if self.0.len() < N {
+            return Err(ElectionStateErr::Malformed);
+        }
+        let (head, tail) = self.0.split_at(N);
+        self.0 = tail;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(head);
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ElectionStateErr> {
+        self.take().map(u8::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ElectionStateErr> {
+        self.take().map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ElectionStateErr> {
+        self.take().map(u64::from_be_bytes)
+    }
+
+    fn bool(&mut self) -> Result<bool, ElectionStateErr> {
+        match self.take::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ElectionStateErr::Malformed),
+        }
+    }
+
+    fn ballot(&mut self) -> Result<Ballot, ElectionStateErr> {
+        Ok(Ballot::with(self.u32()?, self.u64()?, self.u64()?))
+    }
+
+    fn option_ballot(&mut self) -> Result<Option<Ballot>, ElectionStateErr> {
+        let is_some = self.bool()?;
+        let b = self.ballot()?;
+        Ok(if is_some { Some(b) } else { None })
+    }
+
+    fn ballots(&mut self) -> Result<Vec<(Ballot, bool)>, ElectionStateErr> {
+        let len = self.u64()?;
+        let mut ballots = vec![];
+        for _ in 0..len {
+            ballots.push((self.ballot()?, self.bool()?));
+        }
+        Ok(ballots)
+    }
+}
+
 /// A Ballot Leader Election component. Used in conjunction with Omni-Paxos handles the election of a leader for a group of omni-paxos replicas,
 /// incoming messages and produces outgoing messages that the user has to fetch periodically and send using a network implementation.
 /// User also has to periodically fetch the decided entries that are guaranteed to be strongly consistent and linearizable, and therefore also safe to be used in the higher level application.
+#[derive(Clone)]
 pub(crate) struct BallotLeaderElection {
     /// Process identifier used to uniquely identify this instance.
     pid: NodeId,
+    /// The cluster of this instance. Messages of other clusters are ignored.
+    cluster_id: ClusterId,
     /// Vector that holds all the other replicas.
     peers: Vec<u64>,
     /// The current round of the heartbeat cycle.
     hb_round: u32,
     /// Vector which holds all the received ballots.
     ballots: Vec<(Ballot, bool)>,
+    /// The number of heartbeat requests sent in the current round.
+    round_requests: usize,
+    /// The pids of the ballots in `ballots` that were received in a reply to the previous round. They are replaced by the reply to the current round.
+    late_replies: Vec<NodeId>,
+    /// The ballots that were considered in the latest leader check.
+    last_round_ballots: Vec<(Ballot, bool)>,
+    /// The nonces of the heartbeat requests of the current and the previous round that have not been replied to yet, with the round of the request.
+    #[cfg(feature = "hardened")]
+    outstanding_nonces: Vec<(NodeId, u32, u64)>,
     /// Holds the current ballot of this instance.
     current_ballot: Ballot, // (round, pid)
     /// States if the instance is a candidate to become a leader.
@@ -58,8 +203,57 @@ pub(crate) struct BallotLeaderElection {
     leader: Option<Ballot>,
     /// The majority of replicas inside a cluster. It is measured in ticks.
     majority: usize,
+    /// If set, the current leader is kept as long as it is a candidate and there is no candidate with a strictly greater ballot.
+    sticky_leader: bool,
+    /// The number of consecutive rounds the leader may be disconnected from a quorum without losing its leadership.
+    quorum_grace_rounds: u32,
+    /// The number of rounds after losing an election in which this instance does not contest a leader that it still hears from.
+    candidate_cooldown_rounds: u32,
+    /// The number of rounds left of the current cooldown.
+    cooldown_remaining: u32,
+    /// The number of consecutive rounds this instance did not receive a majority of heartbeats.
+    missed_quorum_rounds: u32,
+    /// The number of consecutive rounds the current leader was not among the candidates.
+    missed_leader_rounds: u32,
+    /// If set, this leader is always reported and no heartbeats are exchanged. Only intended for testing.
+    fixed_leader: Option<Ballot>,
+    /// If set, this instance takes part in the election but is never a candidate to become the leader.
+    witness: bool,
+    /// The servers that are elected over any other candidate if at least one of them is a candidate.
+    preferred_leaders: Vec<NodeId>,
+    /// How this instance handles a greater ballot when it rejoins the cluster as a former leader.
+    rejoin_policy: RejoinPolicy,
+    /// If set, the leader gives up its candidacy for a round after leading for this many rounds, if another candidate is connected.
+    leader_rotation_rounds: Option<u32>,
+    /// The number of consecutive rounds this instance has been the leader.
+    rounds_as_leader: u32,
+    /// States if this instance is the leader and gives up its candidacy to let another candidate take over.
+    rotating: bool,
+    /// The number of times this instance voluntarily gave up its leadership.
+    voluntary_rotations: u64,
+    /// The leader that was adopted from a `LeaderAnnouncement` and has not been passed to Sequence Paxos yet.
+    announced_leader: Option<Ballot>,
+    /// The number of messages that were ignored because they were sent by another cluster.
+    foreign_messages_dropped: u64,
+    /// The number of messages that were ignored because they were sent by a server that is not a peer.
+    unknown_peer_messages_dropped: u64,
+    /// If set, only this many peers (and the leader) are sent a heartbeat request per round.
+    heartbeat_peers: Option<usize>,
+    /// If set, the leader is considered stable once it has been present for this many consecutive rounds.
+    stable_leader_rounds: Option<u32>,
+    /// The number of consecutive rounds the current leader has been present without any missed rounds.
+    stable_rounds: u32,
+    /// The greatest ballot received in any heartbeat reply, including stale rounds.
+    highest_seen_ballot: Ballot,
+    /// The number of servers (including this instance) that replied in the latest heartbeat round, or `None` if no round has ended yet.
+    last_round_reachable: Option<usize>,
+    /// The hooks set by the user.
+    hooks: Hooks,
     /// Vector which holds all the outgoing messages of the BLE instance.
     outgoing: Vec<BLEMessage>,
+    /// The most recent rejected ballots.
+    #[cfg(feature = "rejected_ballots")]
+    rejected_ballots: VecDeque<RejectedBallot>,
     /// Logger used to output the status of the component.
     #[cfg(feature = "logging")]
     logger: Logger,
@@ -71,20 +265,58 @@ impl BallotLeaderElection {
         let pid = config.pid;
         let peers = config.peers;
         let n = &peers.len() + 1;
-        let initial_ballot = match &config.initial_leader {
-            Some(leader_ballot) if leader_ballot.pid == pid => *leader_ballot,
+        let initial_ballot = match (&config.initial_leader, &config.persisted_leader) {
+            (Some(leader_ballot), _) if leader_ballot.pid == pid => *leader_ballot,
+            // the persisted ballot might already be promised, resume with a greater one to be re-elected
+            (_, Some(leader_ballot)) if leader_ballot.pid == pid => {
+                Ballot::with(leader_ballot.n + 1, config.priority, pid)
+            }
             _ => Ballot::with(0, config.priority, pid),
         };
+        let leader = config
+            .initial_leader
+            .or(config.persisted_leader.filter(|l| l.pid != pid));
         let mut ble = BallotLeaderElection {
             pid,
+            cluster_id: config.cluster_id,
             majority: n / 2 + 1, // +1 because peers is exclusive ourselves
+            sticky_leader: config.sticky_leader,
+            quorum_grace_rounds: config.quorum_grace_rounds,
+            candidate_cooldown_rounds: config.candidate_cooldown_rounds,
+            cooldown_remaining: 0,
+            missed_quorum_rounds: 0,
+            missed_leader_rounds: 0,
+            fixed_leader: config.fixed_leader.map(|pid| Ballot::with(1, 0, pid)),
+            witness: config.witnesses.contains(&pid),
+            preferred_leaders: config.preferred_leaders,
+            rejoin_policy: config.rejoin_policy,
+            leader_rotation_rounds: config.leader_rotation_rounds,
+            rounds_as_leader: 0,
+            rotating: false,
+            voluntary_rotations: 0,
+            announced_leader: None,
+            foreign_messages_dropped: 0,
+            unknown_peer_messages_dropped: 0,
+            heartbeat_peers: config.heartbeat_peers,
+            stable_leader_rounds: config.stable_leader_rounds,
+            stable_rounds: 0,
+            highest_seen_ballot: Ballot::default(),
+            last_round_reachable: None,
+            hooks: Hooks::default(),
             peers,
             hb_round: 0,
             ballots: Vec::with_capacity(n),
+            round_requests: 0,
+            late_replies: vec![],
+            last_round_ballots: vec![],
+            #[cfg(feature = "hardened")]
+            outstanding_nonces: Vec::with_capacity(n),
             current_ballot: initial_ballot,
             quorum_connected: true,
-            leader: config.initial_leader,
+            leader,
             outgoing: Vec::with_capacity(config.buffer_size),
+            #[cfg(feature = "rejected_ballots")]
+            rejected_ballots: VecDeque::with_capacity(REJECTED_BALLOTS_SIZE),
             #[cfg(feature = "logging")]
             logger: {
                 let path = config.logger_file_path;
@@ -101,7 +333,13 @@ impl BallotLeaderElection {
                 "Ballot Leader Election component pid: {} created!", pid
             );
         }
-        ble.new_hb_round();
+        if let Some(l) = ble.fixed_leader {
+            if l.pid == pid {
+                ble.current_ballot = l;
+            }
+        } else {
+            ble.new_hb_round();
+        }
         ble
     }
 
@@ -110,18 +348,273 @@ impl BallotLeaderElection {
         self.current_ballot.priority = p;
     }
 
+    /// Raises the ballot number of this instance to at least the number of `ballot`, keeping our own pid and priority.
+    /// # Arguments
+    /// * `ballot` - A ballot learned from another server, e.g. the current leader of the cluster that this server joins.
+    pub(crate) fn ensure_ballot_at_least(&mut self, ballot: Ballot) {
+        if ballot.n > self.current_ballot.n {
+            self.current_ballot.n = ballot.n;
+        }
+    }
+
+    /// Sets the hook that computes the next ballot number from the ballot number of the previous leader.
+    pub(crate) fn set_ballot_generator(&mut self, f: BallotGenerator) {
+        self.hooks.ballot_generator = Some(f);
+    }
+
+    /// Returns the ballot number to use after the leader with ballot number `n` was lost. A generated number that is not greater than `n`
+    /// is ignored, as the new ballot must be greater than all ballots of this server that were seen by others.
+    fn next_ballot_number(&mut self, n: u32) -> u32 {
+        let next = match self.hooks.ballot_generator.as_mut() {
+            Some(f) => f(n),
+            None => n + 1,
+        };
+        if next > n {
+            next
+        } else {
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "Generated ballot number {} is not greater than {}, incrementing instead", next, n
+            );
+            n + 1
+        }
+    }
+
+    /// Replaces the state of this instance with `checkpoint`, keeping the hooks of this instance.
+    pub(crate) fn restore(&mut self, mut checkpoint: BallotLeaderElection) {
+        checkpoint.hooks = std::mem::take(&mut self.hooks);
+        *self = checkpoint;
+    }
+
+    /// Encodes the state of this instance that changes while running, i.e. the ballots, the heartbeat round, the leader and the round counters.
+    /// The configuration, the outgoing messages and the hooks are not included. The state is prefixed with `ELECTION_STATE_VERSION`.
+    pub(crate) fn serialize_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::default();
+        w.u8(ELECTION_STATE_VERSION);
+        w.u64(self.pid);
+        w.u32(self.hb_round);
+        w.ballots(&self.ballots);
+        w.u64(self.round_requests as u64);
+        w.u64(self.late_replies.len() as u64);
+        for pid in &self.late_replies {
+            w.u64(*pid);
+        }
+        w.ballots(&self.last_round_ballots);
+        w.ballot(self.current_ballot);
+        w.bool(self.quorum_connected);
+        w.option_ballot(self.leader);
+        w.u32(self.cooldown_remaining);
+        w.u32(self.missed_quorum_rounds);
+        w.u32(self.missed_leader_rounds);
+        w.u32(self.rounds_as_leader);
+        w.bool(self.rotating);
+        w.u64(self.voluntary_rotations);
+        w.u64(self.foreign_messages_dropped);
+        w.u64(self.unknown_peer_messages_dropped);
+        w.u32(self.stable_rounds);
+        w.ballot(self.highest_seen_ballot);
+        w.bool(self.last_round_reachable.is_some());
+        w.u64(self.last_round_reachable.unwrap_or(0) as u64);
+        #[cfg(feature = "hardened")]
+        {
+            w.u64(self.outstanding_nonces.len() as u64);
+            for (pid, round, nonce) in &self.outstanding_nonces {
+                w.u64(*pid);
+                w.u32(*round);
+                w.u64(*nonce);
+            }
+        }
+        #[cfg(feature = "rejected_ballots")]
+        {
+            w.u64(self.rejected_ballots.len() as u64);
+            for r in &self.rejected_ballots {
+                w.u32(r.hb_round);
+                w.ballot(r.ballot);
+                w.ballot(r.leader);
+            }
+        }
+        w.0
+    }
+
+    /// Replaces the state of this instance with a state encoded by `serialize_state()`. The state is only replaced if `bytes` could be decoded entirely.
+    /// The outgoing messages are discarded, the configuration and the hooks of this instance are kept. Returns the leader of the restored state.
+    pub(crate) fn deserialize_state(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<Option<Ballot>, ElectionStateErr> {
+        let mut r = StateReader(bytes);
+        let version = r.u8()?;
+        if version != ELECTION_STATE_VERSION {
+            return Err(ElectionStateErr::UnsupportedVersion(version));
+        }
+        let pid = r.u64()?;
+        if pid != self.pid {
+            return Err(ElectionStateErr::OtherServer(pid));
+        }
+        let mut ble = self.clone();
+        ble.hb_round = r.u32()?;
+        ble.ballots = r.ballots()?;
+        ble.round_requests = r.u64()? as usize;
+        let len = r.u64()?;
+        ble.late_replies.clear();
+        for _ in 0..len {
+            ble.late_replies.push(r.u64()?);
+        }
+        ble.last_round_ballots = r.ballots()?;
+        ble.current_ballot = r.ballot()?;
+        ble.quorum_connected = r.bool()?;
+        ble.leader = r.option_ballot()?;
+        ble.cooldown_remaining = r.u32()?;
+        ble.missed_quorum_rounds = r.u32()?;
+        ble.missed_leader_rounds = r.u32()?;
+        ble.rounds_as_leader = r.u32()?;
+        ble.rotating = r.bool()?;
+        ble.voluntary_rotations = r.u64()?;
+        ble.foreign_messages_dropped = r.u64()?;
+        ble.unknown_peer_messages_dropped = r.u64()?;
+        ble.stable_rounds = r.u32()?;
+        ble.highest_seen_ballot = r.ballot()?;
+        let reachable = r.bool()?;
+        let last_round_reachable = r.u64()? as usize;
+        ble.last_round_reachable = if reachable {
+            Some(last_round_reachable)
+        } else {
+            None
+        };
+        #[cfg(feature = "hardened")]
+        {
+            let len = r.u64()?;
+            ble.outstanding_nonces.clear();
+            for _ in 0..len {
+                ble.outstanding_nonces.push((r.u64()?, r.u32()?, r.u64()?));
+            }
+        }
+        #[cfg(feature = "rejected_ballots")]
+        {
+            let len = r.u64()?;
+            ble.rejected_ballots.clear();
+            for _ in 0..len {
+                ble.rejected_ballots.push_back(RejectedBallot {
+                    hb_round: r.u32()?,
+                    ballot: r.ballot()?,
+                    leader: r.ballot()?,
+                });
+            }
+        }
+        if !r.0.is_empty() {
+            return Err(ElectionStateErr::Malformed);
+        }
+        ble.outgoing.clear();
+        self.restore(ble);
+        Ok(self.leader)
+    }
+
+    /// Returns the pid of this instance.
+    pub(crate) fn pid(&self) -> NodeId {
+        self.pid
+    }
+
+    /// Returns the pids of all the servers in the configuration, including this server, sorted in ascending order.
+    pub(crate) fn members(&self) -> Vec<NodeId> {
+        let mut members = self.peers.clone();
+        members.push(self.pid);
+        members.sort_unstable();
+        members
+    }
+
+    /// Returns whether the leader election currently knows of a leader.
+    pub(crate) fn has_leader(&self) -> bool {
+        self.leader.is_some()
+    }
+
+    /// Returns whether this server received heartbeats from a majority in the latest heartbeat round.
+    pub(crate) fn is_quorum_connected(&self) -> bool {
+        self.quorum_connected
+    }
+
+    /// Returns the `quorum_connected` flag of the ballot of the leader in the latest round in which a majority replied, or `None` if there is no leader.
+    /// For this server as the leader, returns whether it received heartbeats from a majority.
+    pub(crate) fn leader_quorum_connected(&self) -> Option<bool> {
+        let leader = self.leader?;
+        if leader.pid == self.pid {
+            return Some(self.quorum_connected);
+        }
+        Some(
+            self.last_round_ballots
+                .iter()
+                .any(|(b, quorum_connected)| *b == leader && *quorum_connected),
+        )
+    }
+
+    /// Returns whether there are no outgoing messages and no heartbeat round is waiting for replies.
+    pub(crate) fn is_quiescent(&self) -> bool {
+        self.outgoing.is_empty() && !self.round_in_progress()
+    }
+
+    /// Returns the most recent ballots that were rejected for being lower than the ballot of the current leader, in the order they were rejected.
+    #[cfg(feature = "rejected_ballots")]
+    pub(crate) fn rejected_ballots(&self) -> Vec<RejectedBallot> {
+        self.rejected_ballots.iter().copied().collect()
+    }
+
+    #[cfg(feature = "rejected_ballots")]
+    fn add_rejected_ballot(&mut self, rejected: RejectedBallot) {
+        if self.rejected_ballots.len() >= REJECTED_BALLOTS_SIZE {
+            self.rejected_ballots.pop_front();
+        }
+        self.rejected_ballots.push_back(rejected);
+    }
+
+    /// Returns the ballots (and whether they were quorum-connected) that were considered in the latest leader check, including our own.
+    pub(crate) fn last_round_ballots(&self) -> &[(Ballot, bool)] {
+        &self.last_round_ballots
+    }
+
+    /// Returns the ballots of the latest leader check ordered by their chance to be elected: the candidates by descending ballot, followed by the other servers.
+    pub(crate) fn candidates(&self) -> Vec<(Ballot, bool)> {
+        let mut candidates = self.last_round_ballots.clone();
+        candidates.sort_by(|(b1, c1), (b2, c2)| c2.cmp(c1).then(b2.cmp(b1)));
+        candidates
+    }
+
+    /// Returns the number of outgoing messages, without taking them.
+    pub(crate) fn outgoing_len(&self) -> usize {
+        self.outgoing.len()
+    }
+
     /// Returns outgoing messages
     pub(crate) fn get_outgoing_msgs(&mut self) -> Vec<BLEMessage> {
         std::mem::take(&mut self.outgoing)
     }
 
-    /// Handle an incoming message.
+    /// Handle an incoming message. Returns an error if the message was ignored, e.g. a reply from a previous round or a message from an unknown sender.
     /// # Arguments
     /// * `m` - the message to be handled.
-    pub(crate) fn handle(&mut self, m: BLEMessage) {
+    pub(crate) fn try_handle(&mut self, m: BLEMessage) -> Result<(), HandleErr> {
+        if self.fixed_leader.is_some() {
+            return Err(HandleErr::FixedLeader);
+        }
+        if m.cluster_id != self.cluster_id {
+            self.foreign_messages_dropped += 1;
+            return Err(HandleErr::ForeignCluster(m.cluster_id));
+        }
+        if m.from == self.pid {
+            // e.g. our own request echoed by a broadcast transport. Our ballot is already counted in every round.
+            return Err(HandleErr::Loopback);
+        }
+        if !self.peers.contains(&m.from) {
+            self.unknown_peer_messages_dropped += 1;
+            return Err(HandleErr::UnknownPeer(m.from));
+        }
         match m.msg {
-            HeartbeatMsg::Request(req) => self.handle_request(m.from, req),
-            HeartbeatMsg::Reply(rep) => self.handle_reply(rep),
+            HeartbeatMsg::Request(req) => {
+                self.handle_request(m.from, req);
+                Ok(())
+            }
+            HeartbeatMsg::Reply(rep) => self.handle_reply(m.from, rep),
+            HeartbeatMsg::TimeoutNow(t) => self.handle_timeout_now(m.from, t),
+            HeartbeatMsg::LeaderAnnouncement(a) => self.handle_leader_announcement(m.from, a),
         }
     }
 
@@ -137,30 +630,152 @@ impl BallotLeaderElection {
         self.leader = Some(leader_ballot);
     }*/
 
+    /// Adopts a leader that was learned outside of the heartbeats, e.g. from a hint, an announcement or a `Prepare` handled by Sequence Paxos, if its ballot is greater than the ballot of the current leader.
+    /// Unlike the initial leader, this can be used at any time. An adopted leader must also be passed to `SequencePaxos::handle_leader()`, so that both keep the same view of the leader.
+    /// Returns the ballot if it was adopted as the new leader.
+    /// # Arguments
+    /// * `leader_ballot` - The external leader.
+    pub(crate) fn accept_external_leader(&mut self, leader_ballot: Ballot) -> Option<Ballot> {
+        if leader_ballot > self.leader.unwrap_or_default() {
+            if leader_ballot.pid == self.pid && leader_ballot > self.current_ballot {
+                self.current_ballot = leader_ballot;
+            }
+            self.leader = Some(leader_ballot);
+            #[cfg(feature = "logging")]
+            debug!(
+                self.logger,
+                "BLE {}, Adopted hinted leader: {:?}", self.pid, leader_ballot
+            );
+            Some(leader_ballot)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the ballot that `ballots` elect with the current leader: the greatest candidate among the preferred leaders if any of them is a candidate,
+    /// otherwise among all candidates, unless a sticky leader is kept. Returns the default ballot if there is no candidate.
+    fn top_ballot(&self, ballots: &[(Ballot, bool)]) -> Ballot {
+        let candidates = ballots
+            .iter()
+            .filter(|(_, candidate)| *candidate)
+            .map(|(ballot, _)| *ballot);
+        // only the preferred leaders are eligible if any of them is a candidate, otherwise any candidate is
+        let preferred: Vec<Ballot> = candidates
+            .clone()
+            .filter(|b| self.preferred_leaders.contains(&b.pid))
+            .collect();
+        let eligible: Vec<Ballot> = if preferred.is_empty() {
+            candidates.collect()
+        } else {
+            preferred
+        };
+        let max_candidate = eligible.iter().max().copied().unwrap_or_default();
+        match self.leader {
+            // keep the current leader if it is still a candidate and no candidate has a strictly greater ballot number or priority
+            Some(l)
+                if self.sticky_leader
+                    && eligible.contains(&l)
+                    && max_candidate.n == l.n
+                    && max_candidate.priority == l.priority =>
+            {
+                l
+            }
+            _ => max_candidate,
+        }
+    }
+
+    /// Returns the leader that a heartbeat round with `ballots` would elect, or `None` if the round would not elect one, like `check_leader()`, see `OmniPaxos::would_elect()`.
+    pub(crate) fn would_elect(&self, ballots: &[(Ballot, bool)]) -> Option<Ballot> {
+        let top_ballot = self.top_ballot(ballots);
+        if top_ballot == Ballot::default() || top_ballot < self.leader.unwrap_or_default() {
+            None
+        } else {
+            Some(top_ballot)
+        }
+    }
+
     fn check_leader(&mut self) -> Option<Ballot> {
+        // a former leader that regains a quorum, e.g. after a partition healed
+        let rejoining = !self.quorum_connected && self.leader.map(|l| l.pid) == Some(self.pid);
         self.quorum_connected = true;
+        let cooling_down = self.cooldown_remaining > 0;
+        self.cooldown_remaining = self.cooldown_remaining.saturating_sub(1);
         let ballots = std::mem::take(&mut self.ballots);
-        let top_ballot = ballots
-            .into_iter()
-            .filter_map(
-                |(ballot, candidate)| {
-                    if candidate {
-                        Some(ballot)
-                    } else {
-                        None
-                    }
-                },
-            )
-            .max()
-            .unwrap_or_default();
+        let top_ballot = self.top_ballot(&ballots);
+        self.last_round_ballots = ballots;
 
+        if rejoining
+            && self.rejoin_policy == RejoinPolicy::Contest
+            && top_ballot > self.leader.unwrap_or_default()
+        {
+            // contest the leader that was elected in the meantime instead of adopting it
+            self.current_ballot.n = self.next_ballot_number(top_ballot.n);
+            self.leader = None;
+            #[cfg(feature = "logging")]
+            debug!(
+                self.logger,
+                "BLE {}, Rejoined and contesting leader: {:?}", self.pid, top_ballot
+            );
+            return None;
+        }
         if top_ballot < self.leader.unwrap_or_default() {
+            if cooling_down
+                && self
+                    .last_round_ballots
+                    .iter()
+                    .any(|(b, _)| Some(*b) == self.leader)
+            {
+                // the leader that beat us still replies, give it a chance to regain its quorum
+                #[cfg(feature = "logging")]
+                debug!(
+                    self.logger,
+                    "Leader is not a candidate, not contesting it during cooldown"
+                );
+                return None;
+            }
             // did not get HB from leader
-            self.current_ballot.n = self.leader.unwrap_or_default().n + 1;
+            self.missed_leader_rounds += 1;
+            if self.missed_leader_rounds < self.quorum_grace_rounds {
+                #[cfg(feature = "logging")]
+                debug!(
+                    self.logger,
+                    "Missed leader for {} rounds, keeping it during grace period",
+                    self.missed_leader_rounds
+                );
+                return None;
+            }
+            self.missed_leader_rounds = 0;
+            #[cfg(feature = "rejected_ballots")]
+            {
+                let leader = self.leader.unwrap_or_default();
+                let candidates: Vec<Ballot> = self
+                    .last_round_ballots
+                    .iter()
+                    .filter(|(_, candidate)| *candidate)
+                    .map(|(ballot, _)| *ballot)
+                    .collect();
+                for ballot in candidates {
+                    self.add_rejected_ballot(RejectedBallot {
+                        hb_round: self.hb_round,
+                        ballot,
+                        leader,
+                    });
+                }
+            }
+            if self.rotating {
+                // keep our ballot number so that the candidates that increase theirs take over
+                self.rotating = false;
+            } else {
+                self.current_ballot.n = self.next_ballot_number(self.leader.unwrap_or_default().n);
+            }
             self.leader = None;
             None
         } else if self.leader != Some(top_ballot) {
             // got a new leader with greater ballot
+            self.missed_leader_rounds = 0;
+            if top_ballot.pid != self.pid {
+                self.cooldown_remaining = self.candidate_cooldown_rounds;
+            }
             self.leader = Some(top_ballot);
             #[cfg(feature = "logging")]
             debug!(
@@ -169,6 +784,7 @@ impl BallotLeaderElection {
             );
             Some(top_ballot)
         } else {
+            self.missed_leader_rounds = 0;
             None
         }
     }
@@ -183,29 +799,130 @@ impl BallotLeaderElection {
             self.hb_round
         );
 
-        for peer in &self.peers {
+        self.late_replies.clear();
+        #[cfg(feature = "hardened")]
+        {
+            // the requests of the previous round can still be replied to
+            let previous_round = self.hb_round - 1;
+            self.outstanding_nonces
+                .retain(|(_, round, _)| *round == previous_round);
+        }
+        let targets = self.hb_targets();
+        self.round_requests = targets.len();
+        for peer in &targets {
+            #[cfg(feature = "hardened")]
+            let nonce = {
+                let nonce = Self::random_nonce();
+                self.outstanding_nonces.push((*peer, self.hb_round, nonce));
+                nonce
+            };
             let hb_request = HeartbeatRequest {
                 round: self.hb_round,
+                #[cfg(feature = "hardened")]
+                nonce,
             };
 
             self.outgoing.push(BLEMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: *peer,
                 msg: HeartbeatMsg::Request(hb_request),
             });
         }
     }
 
+    /// Starts a heartbeat round right away if this server is the leader, and announces the leadership to all peers. The replies that were collected in the interrupted round count for the new round like late replies.
+    pub(crate) fn announce_leadership(&mut self) {
+        let leader = match self.leader {
+            Some(l) if l.pid == self.pid => l,
+            _ => return,
+        };
+        let replied: Vec<NodeId> = self.ballots.iter().map(|(b, _)| b.pid).collect();
+        self.new_hb_round();
+        self.late_replies = replied;
+        for peer in &self.peers {
+            self.outgoing.push(BLEMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: *peer,
+                msg: HeartbeatMsg::LeaderAnnouncement(LeaderAnnouncement { ballot: leader }),
+            });
+        }
+    }
+
+    /// Adopts the announced leader if its ballot is greater than the one of the current leader, like a hint.
+    fn handle_leader_announcement(
+        &mut self,
+        from: NodeId,
+        a: LeaderAnnouncement,
+    ) -> Result<(), HandleErr> {
+        if a.ballot.pid != from {
+            return Err(HandleErr::NotLeader(from));
+        }
+        if let Some(b) = self.accept_external_leader(a.ballot) {
+            self.announced_leader = Some(b);
+        }
+        Ok(())
+    }
+
+    /// Returns the leader that was adopted from an announcement since the last call.
+    pub(crate) fn take_announced_leader(&mut self) -> Option<Ballot> {
+        self.announced_leader.take()
+    }
+
+    /// Returns the peers to send heartbeat requests to in the current round. With `heartbeat_peers`, this is a window of the peers that
+    /// moves by `heartbeat_peers` every round, such that every peer is contacted at least once every `peers.len() / heartbeat_peers` rounds (rounded up).
+    /// The leader is always included, as a follower that does not hear from the leader in a round would consider it lost.
+    fn hb_targets(&self) -> Vec<NodeId> {
+        match self.heartbeat_peers {
+            Some(k) if k < self.peers.len() => {
+                // offset by pid to spread the requests of different servers over the peers
+                let start = (self.pid as usize + self.hb_round as usize * k) % self.peers.len();
+                let mut targets: Vec<NodeId> = self
+                    .peers
+                    .iter()
+                    .cycle()
+                    .skip(start)
+                    .take(k)
+                    .copied()
+                    .collect();
+                if let Some(l) = self.leader {
+                    if l.pid != self.pid && !targets.contains(&l.pid) && self.peers.contains(&l.pid)
+                    {
+                        targets.push(l.pid);
+                    }
+                }
+                targets
+            }
+            _ => self.peers.clone(),
+        }
+    }
+
+    /// Ends the current heartbeat round and returns the newly elected leader, if the leader changed.
+    /// A leader is only elected among the candidates, i.e., the returned leader was connected to a majority in this round.
     pub(crate) fn hb_timeout(&mut self) -> Option<Ballot> {
+        if let Some(l) = self.fixed_leader {
+            // report the fixed leader once
+            return if self.leader != Some(l) {
+                self.leader = Some(l);
+                Some(l)
+            } else {
+                None
+            };
+        }
+        self.last_round_reachable = Some(self.ballots.len() + 1);
         let result: Option<Ballot> = if self.ballots.len() + 1 >= self.majority {
+            self.missed_quorum_rounds = 0;
             #[cfg(feature = "logging")]
             debug!(
                 self.logger,
                 "Received a majority of heartbeats, round: {}, {:?}", self.hb_round, self.ballots
             );
             self.ballots
-                .push((self.current_ballot, self.quorum_connected));
-            self.check_leader()
+                .push((self.current_ballot, self.is_candidate()));
+            let leader = self.check_leader();
+            self.check_rotation();
+            leader
         } else {
             #[cfg(feature = "logging")]
             warn!(
@@ -215,58 +932,328 @@ impl BallotLeaderElection {
                 self.ballots
             );
             self.ballots.clear();
-            self.quorum_connected = false;
+            self.missed_quorum_rounds += 1;
+            if self.missed_quorum_rounds >= self.quorum_grace_rounds {
+                self.quorum_connected = false;
+            }
             None
         };
+        let stable = result.is_none()
+            && self.leader.is_some()
+            && self.missed_quorum_rounds == 0
+            && self.missed_leader_rounds == 0;
+        self.stable_rounds = if stable {
+            self.stable_rounds.saturating_add(1)
+        } else {
+            0
+        };
         self.new_hb_round();
         result
     }
 
+    /// Returns `true` if the same leader has been present for `stable_leader_rounds` consecutive rounds and no round has been missed since.
+    pub(crate) fn is_leader_stable(&self) -> bool {
+        match self.stable_leader_rounds {
+            Some(r) => self.stable_rounds >= r,
+            None => false,
+        }
+    }
+
+    /// Returns whether this instance reached a majority in the latest heartbeat round.
+    pub(crate) fn partition_status(&self) -> PartitionStatus {
+        match self.last_round_reachable {
+            Some(reachable) if reachable >= self.majority => PartitionStatus::Majority,
+            Some(reachable) => PartitionStatus::Minority {
+                reachable,
+                needed: self.majority,
+            },
+            None => PartitionStatus::Unknown,
+        }
+    }
+
+    /// Returns whether the heartbeat requests of the current round were sent and not all of them have been replied to yet.
+    pub(crate) fn round_in_progress(&self) -> bool {
+        self.fixed_leader.is_none() && self.ballots.len() < self.round_requests
+    }
+
+    /// Returns the number of heartbeat replies received in the current round.
+    pub(crate) fn replies_this_round(&self) -> usize {
+        self.ballots.len()
+    }
+
+    /// Returns the greatest ballot received in any heartbeat reply.
+    pub(crate) fn highest_seen_ballot(&self) -> Ballot {
+        self.highest_seen_ballot
+    }
+
     fn handle_request(&mut self, from: u64, req: HeartbeatRequest) {
         let hb_reply = HeartbeatReply {
             round: req.round,
+            #[cfg(feature = "hardened")]
+            nonce: req.nonce,
             ballot: self.current_ballot,
-            quorum_connected: self.quorum_connected,
+            quorum_connected: self.is_candidate(),
         };
 
         self.outgoing.push(BLEMessage {
             from: self.pid,
+            cluster_id: self.cluster_id,
             to: from,
             msg: HeartbeatMsg::Reply(hb_reply),
         });
     }
 
-    fn handle_reply(&mut self, rep: HeartbeatReply) {
-        if rep.round == self.hb_round {
-            self.ballots.push((rep.ballot, rep.quorum_connected));
-        } else {
+    fn handle_reply(&mut self, _from: NodeId, rep: HeartbeatReply) -> Result<(), HandleErr> {
+        #[cfg(feature = "hardened")]
+        {
+            // every request can only be replied to once
+            match self
+                .outstanding_nonces
+                .iter()
+                .position(|(pid, round, nonce)| {
+                    *pid == _from && *round == rep.round && *nonce == rep.nonce
+                }) {
+                Some(i) => {
+                    self.outstanding_nonces.swap_remove(i);
+                }
+                None => {
+                    #[cfg(feature = "logging")]
+                    warn!(
+                        self.logger,
+                        "Got reply from {} with unknown nonce, round {}", _from, rep.round
+                    );
+                    return Err(HandleErr::UnknownNonce(_from));
+                }
+            }
+        }
+        if rep.ballot > self.highest_seen_ballot {
+            self.highest_seen_ballot = rep.ballot;
+        }
+        // a reply to the previous round is usually only delayed and still counts for the current round
+        let late = self.hb_round.checked_sub(1) == Some(rep.round);
+        if rep.round != self.hb_round && !late {
             #[cfg(feature = "logging")]
             warn!(
                 self.logger,
                 "Got late response, round {}, ballot {:?}", self.hb_round, rep.ballot
             );
+            return Err(HandleErr::StaleRound(rep.round));
+        }
+        if late {
+            if self.ballots.iter().any(|(b, _)| b.pid == rep.ballot.pid) {
+                // the sender already replied to the current round
+                return Ok(());
+            }
+        } else if let Some(i) = self
+            .late_replies
+            .iter()
+            .position(|pid| *pid == rep.ballot.pid)
+        {
+            // the reply to the current round replaces the late reply of the sender
+            self.late_replies.swap_remove(i);
+            self.ballots.retain(|(b, _)| b.pid != rep.ballot.pid);
+        }
+        let same_ballot = |b: &Ballot| b.n == rep.ballot.n && b.pid == rep.ballot.pid;
+        if same_ballot(&self.current_ballot) || self.ballots.iter().any(|(b, _)| same_ballot(b)) {
+            // a duplicated reply, or two servers with the same pid. Counting it would inflate the quorum.
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "Got duplicate ballot {:?} from {}, round {}", rep.ballot, _from, rep.round
+            );
+            return Err(HandleErr::DuplicateBallot(rep.ballot));
+        }
+        if late {
+            self.late_replies.push(rep.ballot.pid);
+        }
+        self.ballots.push((rep.ballot, rep.quorum_connected));
+        Ok(())
+    }
+
+    /// Sends a `TimeoutNow` to `to`, the target of a leadership transfer that has caught up with the log of this leader.
+    pub(crate) fn send_timeout_now(&mut self, to: NodeId) {
+        self.outgoing.push(BLEMessage {
+            from: self.pid,
+            cluster_id: self.cluster_id,
+            to,
+            msg: HeartbeatMsg::TimeoutNow(TimeoutNow {
+                ballot: self.current_ballot,
+            }),
+        });
+    }
+
+    /// Takes over from the current leader that transfers its leadership to us, by increasing our ballot number above the leader's.
+    /// The servers elect us in their next heartbeat round, as we are a candidate with the greatest ballot.
+    fn handle_timeout_now(&mut self, from: NodeId, t: TimeoutNow) -> Result<(), HandleErr> {
+        if self.leader != Some(t.ballot) || t.ballot.pid != from || self.witness {
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "Got TimeoutNow from {} that is not the leader: {:?}", from, t.ballot
+            );
+            return Err(HandleErr::NotLeader(from));
+        }
+        if self.current_ballot <= t.ballot {
+            self.current_ballot.n = self.next_ballot_number(t.ballot.n);
+        }
+        #[cfg(feature = "logging")]
+        info!(
+            self.logger,
+            "BLE {}, Taking over the leadership from {:?}", self.pid, t.ballot
+        );
+        Ok(())
+    }
+
+    /// Returns whether this instance is a candidate to become the leader. A witness never is, and neither is a leader that is rotating.
+    fn is_candidate(&self) -> bool {
+        self.quorum_connected && !self.witness && !self.rotating
+    }
+
+    /// Starts rotating the leadership if we have been the leader for `leader_rotation_rounds` rounds and another candidate was connected in the latest round.
+    fn check_rotation(&mut self) {
+        let max_rounds = match self.leader_rotation_rounds {
+            Some(r) => r,
+            None => return,
+        };
+        if self.leader != Some(self.current_ballot) {
+            self.rounds_as_leader = 0;
+            return;
+        }
+        if self.rotating {
+            return;
+        }
+        self.rounds_as_leader += 1;
+        let other_candidate = self
+            .last_round_ballots
+            .iter()
+            .any(|(ballot, candidate)| *candidate && ballot.pid != self.pid);
+        if self.rounds_as_leader >= max_rounds && other_candidate {
+            #[cfg(feature = "logging")]
+            info!(
+                self.logger,
+                "BLE {}, Rotating leadership after {} rounds", self.pid, self.rounds_as_leader
+            );
+            self.rounds_as_leader = 0;
+            self.rotating = true;
+            self.voluntary_rotations += 1;
+        }
+    }
+
+    /// Returns the number of times this instance voluntarily gave up its leadership.
+    pub(crate) fn voluntary_rotations(&self) -> u64 {
+        self.voluntary_rotations
+    }
+
+    /// Returns the number of messages that were ignored because they were sent by another cluster.
+    pub(crate) fn foreign_messages_dropped(&self) -> u64 {
+        self.foreign_messages_dropped
+    }
+
+    /// Returns the number of messages that were ignored because they were sent by a server that is not a peer.
+    pub(crate) fn unknown_peer_messages_dropped(&self) -> u64 {
+        self.unknown_peer_messages_dropped
+    }
+
+    /// Returns the nonce of the outstanding heartbeat request of `round` to `pid`, if it has not been replied to yet.
+    #[cfg(all(feature = "hardened", feature = "testing"))]
+    pub(crate) fn outstanding_nonce(&self, pid: NodeId, round: u32) -> Option<u64> {
+        self.outstanding_nonces
+            .iter()
+            .find(|(p, r, _)| *p == pid && *r == round)
+            .map(|(_, _, nonce)| *nonce)
+    }
+
+    /// Resets the counters that are only used for observability.
+    pub(crate) fn reset_metrics(&mut self) {
+        self.voluntary_rotations = 0;
+        self.foreign_messages_dropped = 0;
+        self.unknown_peer_messages_dropped = 0;
+    }
+
+    /// Panics with a description of the violated invariant if the internal state is inconsistent.
+    #[cfg(debug_assertions)]
+    pub(crate) fn verify_invariants(&self) {
+        let num_members = self.peers.len() + 1;
+        assert_eq!(
+            self.majority,
+            num_members / 2 + 1,
+            "BLE {}: majority {} does not match {} members",
+            self.pid,
+            self.majority,
+            num_members
+        );
+        assert_eq!(
+            self.current_ballot.pid, self.pid,
+            "BLE {}: current ballot {:?} belongs to another server",
+            self.pid, self.current_ballot
+        );
+        if let Some(l) = self.leader {
+            assert!(
+                l.pid == self.pid || self.peers.contains(&l.pid),
+                "BLE {}: leader {:?} is not a member of the configuration",
+                self.pid,
+                l
+            );
         }
+        assert!(
+            self.ballots.len() <= num_members,
+            "BLE {}: received {} ballots in round {} with only {} members",
+            self.pid,
+            self.ballots.len(),
+            self.hb_round,
+            num_members
+        );
+    }
+
+    /// Returns a random nonce for a heartbeat request.
+    #[cfg(feature = "hardened")]
+    fn random_nonce() -> u64 {
+        RandomState::new().build_hasher().finish()
     }
 }
 
 /// Configuration for `BallotLeaderElection`.
 /// # Fields
 /// * `pid`: The unique identifier of this node. Must not be 0.
+/// * `cluster_id`: The identifier of the cluster that this node is part of.
 /// * `peers`: The peers of this node i.e. the `pid`s of the other replicas in the configuration.
 /// * `priority`: Set custom priority for this node to be elected as the leader.
 /// * `hb_delay`: Timeout for waiting on heartbeat messages. It is measured in number of ticks.
 /// * `initial_leader`: The initial leader of the cluster.
+/// * `persisted_leader`: The leader before a restart, adopted as the leader if it is another node, or resumed with a greater ballot if it is this node.
 /// * `initial_timeout`: Optional initial timeout that can be used to elect a leader faster initially.
 /// * `logger`: Custom logger for logging events of Ballot Leader Election.
 /// * `logger_file_path`: The path where the default logger logs events.
 /// * `buffer_size`: The buffer size for outgoing messages.
+/// * `sticky_leader`: Keep the current leader as long as it is a candidate and there is no candidate with a strictly greater ballot.
+/// * `quorum_grace_rounds`: The number of consecutive rounds the leader may be disconnected from a quorum without losing its leadership.
+/// * `candidate_cooldown_rounds`: The number of rounds after losing an election in which a leader that is still heard from is not contested.
+/// * `fixed_leader`: Always report this node as the leader without exchanging heartbeats. Only intended for testing.
+/// * `witnesses`: The nodes that take part in the election but are never candidates to become the leader.
+/// * `preferred_leaders`: The nodes that are elected over any other candidate if at least one of them is a candidate.
+/// * `rejoin_policy`: Whether a former leader that rejoins the cluster adopts a current leader with a greater ballot or contests it.
+/// * `leader_rotation_rounds`: The number of rounds after which the leader gives up its candidacy for a round if another candidate is connected.
+/// * `heartbeat_peers`: The number of peers, besides the leader, that are sent a heartbeat request per round. All peers if `None`.
+/// * `stable_leader_rounds`: The number of consecutive rounds with the same leader after which the leader is considered stable.
 #[derive(Clone, Debug)]
 pub(crate) struct BLEConfig {
     pid: NodeId,
+    cluster_id: ClusterId,
     peers: Vec<u64>,
     priority: u64,
     initial_leader: Option<Ballot>,
+    persisted_leader: Option<Ballot>,
     buffer_size: usize,
+    sticky_leader: bool,
+    quorum_grace_rounds: u32,
+    candidate_cooldown_rounds: u32,
+    fixed_leader: Option<NodeId>,
+    witnesses: Vec<NodeId>,
+    preferred_leaders: Vec<NodeId>,
+    rejoin_policy: RejoinPolicy,
+    leader_rotation_rounds: Option<u32>,
+    heartbeat_peers: Option<usize>,
+    stable_leader_rounds: Option<u32>,
     #[cfg(feature = "logging")]
     logger: Option<Logger>,
     #[cfg(feature = "logging")]
@@ -277,10 +1264,22 @@ impl From<OmniPaxosConfig> for BLEConfig {
     fn from(config: OmniPaxosConfig) -> Self {
         Self {
             pid: config.pid,
+            cluster_id: config.cluster_id,
             peers: config.peers,
             priority: config.leader_priority,
             initial_leader: config.initial_leader,
+            persisted_leader: config.persisted_leader,
             buffer_size: BLE_BUFFER_SIZE,
+            sticky_leader: config.sticky_leader,
+            quorum_grace_rounds: config.quorum_grace_rounds,
+            candidate_cooldown_rounds: config.candidate_cooldown_rounds,
+            fixed_leader: config.fixed_leader,
+            witnesses: config.witnesses,
+            preferred_leaders: config.preferred_leaders,
+            rejoin_policy: config.rejoin_policy,
+            leader_rotation_rounds: config.leader_rotation_rounds,
+            heartbeat_peers: config.heartbeat_peers,
+            stable_leader_rounds: config.stable_leader_rounds,
             #[cfg(feature = "logging")]
             logger: None,
             #[cfg(feature = "logging")]
@@ -9,6 +9,12 @@
 //! * `latest_accepted` - Only send latest accepted log index as all preceding entries are implicitly accepted. Reduces message overhead.
 //! * `latest_decide` - Only send latest decided log index as all preceding entries are implicitly decided. Reduces message overhead.
 //! * `continued_leader_reconfiguration` - Let the cluster pick the current leader as the initial leader in the new configuration (if possible) to shorten down-time during reconfiguration.
+//! * `rejected_ballots` - Keep a bounded record of the recent ballots that were rejected for being lower than the current leader. Useful for investigating split-brain scenarios.
+//! * `hardened` - Add a random nonce to heartbeat requests that must be echoed in the replies. Replies with a nonce that does not match an outstanding request are ignored, which protects the leader election against replayed heartbeats. Changes the wire format of the heartbeat messages.
+//! * `checksum` - Provide a CRC32 checksum (`util::crc32()`) that the network layer can send along with serialized messages, and `OmniPaxos::verify_checksum()` to detect and count corrupted messages at the receiver.
+//! * `signing` - Authenticate serialized messages with a user-provided `util::MessageSigner`. The receiver drops and counts messages whose signature does not verify for their sender, or that are replays, with `OmniPaxos::handle_incoming_signed()`. This is only message authentication, not Byzantine fault tolerance.
+//! * `testing` - Provide `testing::TraceReplayer` to replay a recorded trace of heartbeat messages against a fresh leader election and check the elected leaders, e.g. to turn a captured incident into a regression test.
+//! * `framing` - Provide `messages::FramedCodec`, a `tokio_util` codec that splits a stream of bytes, e.g. from TCP, into length-prefixed frames of serialized messages, handling partial reads and rejecting oversized frames.
 
 #![deny(missing_docs)]
 /// Trait and struct related to the leader election in Omni-Paxos.
@@ -22,6 +28,9 @@ pub(crate) mod sequence_paxos;
 // pub mod sequence_paxos;
 /// Traits and structs related to the backend storage of an Omni-Paxos replica.
 pub mod storage;
+/// Utilities for testing applications with Omni-Paxos.
+#[cfg(feature = "testing")]
+pub mod testing;
 /// A module containing helper functions and structs.
 pub mod util;
 pub(crate) mod utils;
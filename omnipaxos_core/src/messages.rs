@@ -1,7 +1,8 @@
 use crate::{
     messages::{ballot_leader_election::BLEMessage, sequence_paxos::PaxosMessage},
+    omni_paxos::VersionMismatch,
     storage::{Entry, Snapshot},
-    util::NodeId,
+    util::{NodeId, MESSAGE_VERSION, MIN_MESSAGE_VERSION},
 };
 
 /// Internal component for log replication
@@ -9,7 +10,7 @@ pub mod sequence_paxos {
     use crate::{
         ballot_leader_election::Ballot,
         storage::{Entry, Snapshot, SnapshotType, StopSign},
-        util::NodeId,
+        util::{ClusterId, NodeId},
     };
     use std::fmt::Debug;
 
@@ -87,6 +88,8 @@ pub mod sequence_paxos {
         pub n: Ballot,
         /// The decided index.
         pub decided_idx: u64,
+        /// The log index of the first entry in `entries`.
+        pub start_idx: u64,
         /// Entries to be replicated.
         pub entries: Vec<T>,
     }
@@ -152,6 +155,8 @@ pub mod sequence_paxos {
         PrepareReq,
         #[allow(missing_docs)]
         Prepare(Prepare),
+        /// Sent in response to a [`Prepare`] with a smaller ballot than the leader that the follower has elected. Contains the ballot of that leader.
+        PrepareNack(Ballot),
         Promise(Promise<T, S>),
         AcceptSync(AcceptSync<T, S>),
         FirstAccept(FirstAccept),
@@ -160,11 +165,17 @@ pub mod sequence_paxos {
         Decide(Decide),
         /// Forward client proposals to the leader.
         ProposalForward(Vec<T>),
+        /// Returns forwarded proposals to the follower that forwarded them, as the storage or the in-flight window of the leader is full.
+        ProposalRejected(Vec<T>),
         Compaction(Compaction),
         AcceptStopSign(AcceptStopSign),
         AcceptedStopSign(AcceptedStopSign),
         DecideStopSign(DecideStopSign),
         ForwardStopSign(StopSign),
+        /// Report the index up to which the sender has applied the decided entries to the leader.
+        Applied(u64),
+        /// Acknowledges an `Applied` report of a follower, which resends its report until it is acknowledged.
+        AppliedAck(u64),
     }
 
     /// A struct for a Paxos message that also includes sender and receiver.
@@ -176,6 +187,8 @@ pub mod sequence_paxos {
     {
         /// Sender of `msg`.
         pub from: NodeId,
+        /// The cluster of the sender.
+        pub cluster_id: ClusterId,
         /// Receiver of `msg`.
         pub to: NodeId,
         /// The message content.
@@ -185,7 +198,10 @@ pub mod sequence_paxos {
 
 /// The different messages BLE uses to communicate with other replicas.
 pub mod ballot_leader_election {
-    use crate::{ballot_leader_election::Ballot, util::NodeId};
+    use crate::{
+        ballot_leader_election::Ballot,
+        util::{ClusterId, NodeId},
+    };
 
     /// An enum for all the different BLE message types.
     #[allow(missing_docs)]
@@ -193,6 +209,8 @@ pub mod ballot_leader_election {
     pub enum HeartbeatMsg {
         Request(HeartbeatRequest),
         Reply(HeartbeatReply),
+        TimeoutNow(TimeoutNow),
+        LeaderAnnouncement(LeaderAnnouncement),
     }
 
     /// Requests a reply from all the other replicas.
@@ -200,6 +218,9 @@ pub mod ballot_leader_election {
     pub struct HeartbeatRequest {
         /// Number of the current round.
         pub round: u32,
+        /// Random nonce that must be echoed in the reply.
+        #[cfg(feature = "hardened")]
+        pub nonce: u64,
     }
 
     /// Replies
@@ -207,17 +228,36 @@ pub mod ballot_leader_election {
     pub struct HeartbeatReply {
         /// Number of the current round.
         pub round: u32,
+        /// The nonce of the request that is replied to.
+        #[cfg(feature = "hardened")]
+        pub nonce: u64,
         /// Ballot of a replica.
         pub ballot: Ballot,
         /// States if the replica is a candidate to become a leader.
         pub quorum_connected: bool,
     }
 
+    /// Sent by the leader to the target of a leadership transfer once it has caught up, to make it take over right away.
+    #[derive(Clone, Debug)]
+    pub struct TimeoutNow {
+        /// Ballot of the leader that transfers its leadership.
+        pub ballot: Ballot,
+    }
+
+    /// Sent by the leader with the heartbeat requests of `OmniPaxos::announce_leadership()`, to make the followers adopt it right away.
+    #[derive(Clone, Debug)]
+    pub struct LeaderAnnouncement {
+        /// Ballot of the leader.
+        pub ballot: Ballot,
+    }
+
     /// A struct for a Paxos message that also includes sender and receiver.
     #[derive(Clone, Debug)]
     pub struct BLEMessage {
         /// Sender of `msg`.
         pub from: NodeId,
+        /// The cluster of the sender.
+        pub cluster_id: ClusterId,
         /// Receiver of `msg`.
         pub to: NodeId,
         /// The message content.
@@ -258,3 +298,169 @@ where
         }
     }
 }
+
+/// A serialized message tagged with the version of its message format, so that servers running different versions during a rolling upgrade
+/// can detect and skip messages they cannot deserialize. The network layer wraps every serialized message with `Envelope::new()` and sends
+/// `to_bytes()`, and the receiver only deserializes the payload returned by `OmniPaxos::open_envelope()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// The version of the message format of the sender.
+    pub version: u16,
+    /// The serialized message.
+    pub payload: Vec<u8>,
+}
+
+impl Envelope {
+    /// Wraps the serialized message `payload` with the message version of this build.
+    pub fn new(payload: Vec<u8>) -> Self {
+        Self {
+            version: MESSAGE_VERSION,
+            payload,
+        }
+    }
+
+    /// Encodes the envelope as the big-endian version followed by the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + self.payload.len());
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    /// Decodes an envelope encoded with `to_bytes()`. Returns `None` if `bytes` is too short to contain the version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 2 {
+            return None;
+        }
+        Some(Self {
+            version: u16::from_be_bytes([bytes[0], bytes[1]]),
+            payload: bytes[2..].to_vec(),
+        })
+    }
+
+    /// Returns the payload if its version is in `MIN_MESSAGE_VERSION..=MESSAGE_VERSION`.
+    pub fn open(self) -> Result<Vec<u8>, VersionMismatch> {
+        if (MIN_MESSAGE_VERSION..=MESSAGE_VERSION).contains(&self.version) {
+            Ok(self.payload)
+        } else {
+            Err(VersionMismatch {
+                version: self.version,
+                min_version: MIN_MESSAGE_VERSION,
+                max_version: MESSAGE_VERSION,
+            })
+        }
+    }
+}
+
+/// A length-prefixed framing of serialized messages for stream transports such as TCP, where a message can arrive split across several reads
+/// or together with the next message. Every frame is the big-endian `u32` length of the payload followed by the payload, e.g. the bytes of an
+/// [`Envelope`]. Implements the `Encoder` and `Decoder` traits of `tokio_util::codec`, so it can be used with `Framed` on any tokio stream.
+#[cfg(feature = "framing")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FramedCodec {
+    max_frame_len: usize,
+}
+
+/// A frame is longer than the maximum frame length of the [`FramedCodec`]. The stream cannot be resynchronized and should be closed.
+#[cfg(feature = "framing")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameTooLarge {
+    /// The length of the payload of the frame.
+    pub len: usize,
+    /// The maximum length of the payload of a frame.
+    pub max_frame_len: usize,
+}
+
+/// An error of the [`FramedCodec`].
+#[cfg(feature = "framing")]
+#[derive(Debug)]
+pub enum FramingError {
+    /// A frame is longer than the maximum frame length.
+    TooLarge(FrameTooLarge),
+    /// Reading from or writing to the underlying stream failed.
+    Io(std::io::Error),
+}
+
+#[cfg(feature = "framing")]
+impl From<std::io::Error> for FramingError {
+    fn from(e: std::io::Error) -> Self {
+        FramingError::Io(e)
+    }
+}
+
+#[cfg(feature = "framing")]
+impl std::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramingError::TooLarge(e) => write!(
+                f,
+                "frame of {} bytes exceeds the maximum of {} bytes",
+                e.len, e.max_frame_len
+            ),
+            FramingError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "framing")]
+impl std::error::Error for FramingError {}
+
+#[cfg(feature = "framing")]
+impl FramedCodec {
+    const LEN_BYTES: usize = 4;
+
+    /// Creates a codec that rejects frames with a payload longer than `max_frame_len` bytes, both when encoding and decoding.
+    pub fn with(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len: max_frame_len.min(u32::MAX as usize),
+        }
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), FramingError> {
+        if len > self.max_frame_len {
+            Err(FramingError::TooLarge(FrameTooLarge {
+                len,
+                max_frame_len: self.max_frame_len,
+            }))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "framing")]
+impl<'a> tokio_util::codec::Encoder<&'a [u8]> for FramedCodec {
+    type Error = FramingError;
+
+    /// Appends the frame of `payload` to `dst`.
+    fn encode(&mut self, payload: &'a [u8], dst: &mut bytes::BytesMut) -> Result<(), FramingError> {
+        self.check_len(payload.len())?;
+        dst.reserve(Self::LEN_BYTES + payload.len());
+        dst.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        dst.extend_from_slice(payload);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "framing")]
+impl tokio_util::codec::Decoder for FramedCodec {
+    type Item = bytes::Bytes;
+    type Error = FramingError;
+
+    /// Removes the first complete frame from `src`, which holds the bytes read from the stream so far, and returns its payload.
+    /// Returns `None` if `src` does not contain a complete frame yet, in which case more bytes should be read and appended to `src`.
+    /// A frame that is too large is rejected as soon as its length has been read, without waiting for its payload.
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<bytes::Bytes>, FramingError> {
+        if src.len() < Self::LEN_BYTES {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+        self.check_len(len)?;
+        if src.len() < Self::LEN_BYTES + len {
+            src.reserve(Self::LEN_BYTES + len - src.len());
+            return Ok(None);
+        }
+        let _ = src.split_to(Self::LEN_BYTES);
+        Ok(Some(src.split_to(len).freeze()))
+    }
+}
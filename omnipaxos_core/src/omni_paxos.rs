@@ -1,37 +1,101 @@
+#[cfg(feature = "rejected_ballots")]
+use crate::ballot_leader_election::RejectedBallot;
+#[cfg(feature = "signing")]
+use crate::util::{MessageSignature, MessageSigner, ReplayWindow};
 #[cfg(feature = "hocon_config")]
 use crate::utils::hocon_kv::*;
 use crate::{
-    ballot_leader_election::{Ballot, BallotLeaderElection},
-    messages::Message,
+    ballot_leader_election::{Ballot, BallotLeaderElection, ElectionCheckpoint},
+    messages::{
+        ballot_leader_election::BLEMessage, sequence_paxos::PaxosMessage, Envelope, Message,
+    },
     sequence_paxos::SequencePaxos,
-    storage::{Entry, Snapshot, StopSign, Storage},
-    util::{defaults::BUFFER_SIZE, LogEntry, NodeId},
+    storage::{EncodedEntry, Entry, EntryMeta, Snapshot, StopSign, Storage, StorageErr, WithMeta},
+    util::{
+        defaults::{BUFFER_SIZE, DECIDED_ITER_BATCH_SIZE, RESEND_TIMEOUT},
+        BallotGenerator, ClusterId, DecideBatchCallback, DecideCallback, EntryCodec, EntrySizer,
+        LogEntry, NodeId, ProposalId, COMMIT_LATENCY_BUCKETS, MESSAGE_VERSION, MIN_MESSAGE_VERSION,
+    },
 };
 #[cfg(feature = "hocon_config")]
 use hocon::Hocon;
-use std::ops::RangeBounds;
+#[cfg(feature = "signing")]
+use std::collections::HashMap;
+use std::ops::{RangeBounds, RangeInclusive};
 
 /// Configuration for `OmniPaxos`.
 /// # Fields
 /// * `configuration_id`: The identifier for the configuration that this Sequence Paxos replica is part of.
+/// * `cluster_id`: The identifier of the cluster that this node is part of. Must be the same at all servers of a cluster.
 /// * `pid`: The unique identifier of this node. Must not be 0.
 /// * `peers`: The peers of this node i.e. the `pid`s of the other replicas in the configuration.
 /// * `buffer_size`: The buffer size for outgoing messages.
+/// * `expected_peers`: The expected number of peers, used to reserve the capacity of the internal buffers. Set with `with_capacity()`.
+/// * `expected_inflight`: The expected number of pending proposals, used to reserve the capacity of the internal buffers. Set with `with_capacity()`.
 /// * `skip_prepare_use_leader`: The initial leader of the cluster. Could be used in combination with reconfiguration to skip the prepare phase in the new configuration.
 /// * `logger`: Custom logger for logging events of Sequence Paxos.
 /// * `logger_file_path`: The path where the default logger logs events.
+/// * `resend_timeout`: The number of calls to `tick()` before unacknowledged messages are resent.
+/// * `proposal_ttl`: The number of calls to `tick()` after which an undecided tracked proposal fails with `ProposalFailure::TimedOut`.
+/// * `snapshot_trigger`: When the leader automatically snapshots the decided log on `tick()`.
+/// * `follower_timeout`: The number of calls to `tick()` without a message from a follower after which the leader excludes it from `min_replicated_idx()`.
+/// * `max_follower_backlog`: The maximum number of entries that the leader sends to a follower that has not accepted them.
+/// * `witnesses`: The nodes of the configuration that are witnesses, which do not store the log and never become the leader. Must be the same at all servers.
+/// * `reconfiguration_timeout`: The number of calls to `tick()` after which a stalled reconfiguration is recorded as `ConsensusEvent::ReconfigurationStalled`.
+/// * `prepare_timeout`: The number of calls to `tick()` in the `Prepare` phase after which the leader records `ConsensusEvent::PrepareTimedOut`.
+/// * `event_buffer_size`: The maximum number of `ConsensusEvent`s that are kept until `drain_events()`. The default `0` does not record any events.
+/// * `coalesce_decides`: The maximum number of entries that are passed in one call of the callback of `set_on_decide_batch()`. Must be greater than 0.
+/// * `max_entry_bytes`: The maximum size in bytes of an entry, as computed by the function of `set_entry_sizer()`.
+/// * `max_inflight_entries`: The maximum number of accepted but undecided entries, see `inflight_count()`. Must be greater than 0.
+/// * `leader_priority`: Custom priority for this node to be elected as the leader.
+/// * `initial_leader`: The initial leader of the cluster.
+/// * `persisted_leader`: The leader that this node knew of before it restarted. Set with `with_persisted_leader()`.
+/// * `sticky_leader`: If `true`, the leader keeps its leadership against candidates with the same ballot number and priority.
+/// * `quorum_grace_rounds`: The number of consecutive heartbeat rounds in which the leader may be disconnected from a quorum without losing its leadership.
+/// * `candidate_cooldown_rounds`: The number of heartbeat rounds after losing an election in which this node does not contest a leader that it still hears from.
+/// * `preferred_leaders`: The nodes among which the leader is elected as long as one of them is a candidate. Must be the same at all servers.
+/// * `rejoin_policy`: How a former leader that rejoins the cluster handles a current leader with a greater ballot.
+/// * `leader_rotation_rounds`: The number of consecutive heartbeat rounds after which a leader gives up its candidacy for one round.
+/// * `heartbeat_peers`: The number of peers that a heartbeat request is sent to per round, in addition to the leader.
+/// * `stable_leader_rounds`: The number of consecutive heartbeat rounds with the same leader after which `is_leader_stable()` returns `true`.
+/// * `fixed_leader`: **Only intended for testing.** The node that is elected by a fixed leader oracle instead of the leader election. Must be the same at all servers.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub struct OmniPaxosConfig {
     pub configuration_id: u32,
+    pub cluster_id: ClusterId,
     pub pid: NodeId,
     pub peers: Vec<u64>,
     pub buffer_size: usize,
+    pub expected_peers: usize,
+    pub expected_inflight: usize,
     pub skip_prepare_use_leader: Option<Ballot>,
     pub logger_file_path: Option<String>,
+    pub resend_timeout: u64,
+    pub proposal_ttl: Option<u64>,
+    pub snapshot_trigger: SnapshotTrigger,
+    pub follower_timeout: Option<u64>,
+    pub max_follower_backlog: Option<u64>,
+    pub witnesses: Vec<NodeId>,
+    pub reconfiguration_timeout: Option<u64>,
+    pub prepare_timeout: Option<u64>,
+    pub event_buffer_size: usize,
+    pub coalesce_decides: Option<usize>,
+    pub max_entry_bytes: Option<usize>,
+    pub max_inflight_entries: Option<u64>,
     /*** BLE config fields ***/
     pub leader_priority: u64,
     pub initial_leader: Option<Ballot>,
+    pub persisted_leader: Option<Ballot>,
+    pub sticky_leader: bool,
+    pub quorum_grace_rounds: u32,
+    pub candidate_cooldown_rounds: u32,
+    pub preferred_leaders: Vec<NodeId>,
+    pub rejoin_policy: RejoinPolicy,
+    pub leader_rotation_rounds: Option<u32>,
+    pub heartbeat_peers: Option<usize>,
+    pub stable_leader_rounds: Option<u32>,
+    pub fixed_leader: Option<NodeId>,
     #[cfg(feature = "logging")]
     pub logger_path: Option<String>,
 }
@@ -42,6 +106,9 @@ impl OmniPaxosConfig {
     pub fn with_hocon(h: &Hocon) -> Self {
         let mut config = Self::default();
         config.configuration_id = h[CONFIG_ID].as_i64().expect("Failed to load config ID") as u32;
+        if let Some(c) = h[CLUSTER_ID].as_i64() {
+            config.cluster_id = c as u64;
+        }
         config.pid = h[PID].as_i64().expect("Failed to load PID") as u64;
         match &h[PEERS] {
             Hocon::Array(v) => {
@@ -66,6 +133,24 @@ impl OmniPaxosConfig {
         config
     }
 
+    /// Sets the leader that this node knew of before it restarted, e.g. read from durable storage, see `persisted_leader`.
+    /// A follower adopts it as its leader right away instead of waiting for the first heartbeat rounds, and does not disturb the leader with an election of its own.
+    /// If the persisted leader is this node itself, it resumes its candidacy with the next greater ballot number, such that it is re-elected by the first heartbeat round. A stale persisted leader is replaced by the current leader in the first heartbeat round.
+    pub fn with_persisted_leader(mut self, leader: Option<Ballot>) -> Self {
+        self.persisted_leader = leader;
+        self
+    }
+
+    /// Reserves the capacity of the internal buffers for `expected_peers` peers and `expected_inflight` pending proposals, see `expected_peers` and `expected_inflight`.
+    /// `expected_peers` is e.g. the number of peers after planned reconfigurations, and `expected_inflight` the number of proposals that are pending or not decided yet at the same time under load,
+    /// such that the buffers of proposals and the initial buffer of outgoing messages do not have to grow under load. The buffers that replace it after every `outgoing_messages()` are sized by `buffer_size`.
+    /// By default, no capacity is reserved beyond `buffer_size`.
+    pub fn with_capacity(mut self, expected_peers: usize, expected_inflight: usize) -> Self {
+        self.expected_peers = expected_peers;
+        self.expected_inflight = expected_inflight;
+        self
+    }
+
     /// Checks all configurations and returns the local OmniPaxos node if successful.
     pub fn build<T, S, B>(self, storage: B) -> OmniPaxos<T, S, B>
     where
@@ -81,12 +166,74 @@ impl OmniPaxosConfig {
             "Peers should not include self pid"
         );
         assert!(self.buffer_size > 0, "Buffer size must be greater than 0");
+        assert!(
+            self.resend_timeout > 0,
+            "Resend timeout must be greater than 0"
+        );
+        if let SnapshotTrigger::LogLength(n) = self.snapshot_trigger {
+            assert!(n > 0, "Snapshot trigger log length must be greater than 0");
+        }
+        assert_ne!(
+            self.coalesce_decides,
+            Some(0),
+            "Coalesce decides must be greater than 0"
+        );
+        assert_ne!(
+            self.max_inflight_entries,
+            Some(0),
+            "Max inflight entries must be greater than 0"
+        );
+        if let Some(k) = self.heartbeat_peers {
+            assert!(
+                2 * k >= self.peers.len(),
+                "Heartbeat peers must be enough to reach a majority"
+            );
+        }
+        if let Some(l) = self.fixed_leader {
+            assert!(
+                l == self.pid || self.peers.contains(&l),
+                "Fixed leader must be in the configuration"
+            );
+        }
         if let Some(x) = self.skip_prepare_use_leader {
-            assert_ne!(x.pid, 0, "Initial leader cannot be 0")
+            assert_ne!(x.pid, 0, "Initial leader cannot be 0");
+            assert!(
+                !self.witnesses.contains(&x.pid),
+                "Initial leader cannot be a witness"
+            );
         };
+        if let Some(x) = self.initial_leader {
+            assert!(
+                !self.witnesses.contains(&x.pid),
+                "Initial leader cannot be a witness"
+            );
+        }
+        if let Some(l) = self.fixed_leader {
+            assert!(
+                !self.witnesses.contains(&l),
+                "Fixed leader cannot be a witness"
+            );
+        }
         OmniPaxos {
             seq_paxos: SequencePaxos::with(self.clone().into(), storage),
             ble: BallotLeaderElection::with(self.into()),
+            entry_codec: None,
+            leaderless_ticks: 0,
+            leaderless_streak: 0,
+            registered_groups: Vec::new(),
+            group_leaders: Vec::new(),
+            #[cfg(feature = "checksum")]
+            corrupt_messages_dropped: 0,
+            #[cfg(feature = "signing")]
+            signer: None,
+            #[cfg(feature = "signing")]
+            unauthenticated_messages_dropped: 0,
+            #[cfg(feature = "signing")]
+            signing_session: 0,
+            #[cfg(feature = "signing")]
+            next_signing_seq: 0,
+            #[cfg(feature = "signing")]
+            replay_windows: HashMap::new(),
         }
     }
 }
@@ -95,13 +242,38 @@ impl Default for OmniPaxosConfig {
     fn default() -> Self {
         Self {
             configuration_id: 0,
+            cluster_id: 0,
             pid: 0,
             peers: Vec::new(),
             buffer_size: BUFFER_SIZE,
+            expected_peers: 0,
+            expected_inflight: 0,
             skip_prepare_use_leader: None,
             logger_file_path: None,
+            resend_timeout: RESEND_TIMEOUT,
+            proposal_ttl: None,
+            snapshot_trigger: SnapshotTrigger::Manual,
+            follower_timeout: None,
+            max_follower_backlog: None,
+            witnesses: Vec::new(),
+            reconfiguration_timeout: None,
+            prepare_timeout: None,
+            event_buffer_size: 0,
+            coalesce_decides: None,
+            max_entry_bytes: None,
+            max_inflight_entries: None,
             leader_priority: 0,
             initial_leader: None,
+            persisted_leader: None,
+            sticky_leader: false,
+            quorum_grace_rounds: 0,
+            candidate_cooldown_rounds: 0,
+            preferred_leaders: vec![],
+            rejoin_policy: RejoinPolicy::FastYield,
+            leader_rotation_rounds: None,
+            heartbeat_peers: None,
+            stable_leader_rounds: None,
+            fixed_leader: None,
             #[cfg(feature = "logging")]
             logger_path: None,
         }
@@ -118,6 +290,23 @@ where
 {
     seq_paxos: SequencePaxos<T, S, B>,
     ble: BallotLeaderElection,
+    entry_codec: Option<(Box<dyn EntryCodec + Send + Sync>, usize)>, // the codec and the size in bytes from which entries are encoded
+    leaderless_ticks: u64,
+    leaderless_streak: u64,
+    registered_groups: Vec<ClusterId>,
+    group_leaders: Vec<(ClusterId, Ballot)>,
+    #[cfg(feature = "checksum")]
+    corrupt_messages_dropped: u64,
+    #[cfg(feature = "signing")]
+    signer: Option<Box<dyn MessageSigner + Send + Sync>>,
+    #[cfg(feature = "signing")]
+    unauthenticated_messages_dropped: u64,
+    #[cfg(feature = "signing")]
+    signing_session: u64,
+    #[cfg(feature = "signing")]
+    next_signing_seq: u64,
+    #[cfg(feature = "signing")]
+    replay_windows: HashMap<NodeId, ReplayWindow>,
 }
 
 impl<T, S, B> OmniPaxos<T, S, B>
@@ -133,6 +322,44 @@ where
         self.seq_paxos.trim(trim_index)
     }
 
+    /// Returns the minimum index accepted by this leader and all of its live followers, i.e., the followers that the leader has heard from within `follower_timeout` ticks.
+    /// A lagging follower drags the minimum down. Snapshots triggered by `snapshot_trigger` never compact the log beyond this index, and `trim()` never beyond the index accepted by all servers.
+    /// Returns `0` if this server is not the leader.
+    pub fn min_replicated_idx(&self) -> u64 {
+        self.seq_paxos.min_replicated_idx()
+    }
+
+    /// Returns the number of entries that this leader sent to the follower `pid` since the follower was last synchronized, and that it has not accepted yet.
+    /// Once it exceeds `max_follower_backlog`, the follower is resynchronized instead and its backlog is `0` until it promises again.
+    /// Returns `0` if this server is not the leader or `pid` is not one of its followers.
+    pub fn follower_backlog(&self, pid: NodeId) -> usize {
+        self.seq_paxos.follower_backlog(pid)
+    }
+
+    /// Sets the index up to which the application has applied the decided entries, i.e., `idx` is the index after the last applied entry.
+    /// Followers report it to the leader at the next call to `tick()` for [`barrier()`](Self::barrier). The index is capped at the decided index and never decreases.
+    pub fn set_applied_idx(&mut self, idx: u64) {
+        self.seq_paxos.set_applied_idx(idx)
+    }
+
+    /// Returns the index up to which the application has applied the decided entries, as set with `set_applied_idx()`.
+    pub fn get_applied_idx(&self) -> u64 {
+        self.seq_paxos.get_applied_idx()
+    }
+
+    /// Creates a barrier at `idx` that completes once this leader and all of its live followers have applied the log up to `idx`, e.g. to make a write visible at every replica before acknowledging it.
+    /// The returned [`BarrierHandle`] is polled with [`poll_barrier()`](Self::poll_barrier). Witnesses do not apply entries and are not waited for.
+    pub fn barrier(&self, idx: u64) -> BarrierHandle {
+        BarrierHandle { idx }
+    }
+
+    /// Returns whether all live members have applied the log up to the index of `barrier`, according to the applied indexes reported to this leader.
+    /// A member that is down blocks the barrier until it has been silent for more than `follower_timeout` ticks, after which it is excluded. If `follower_timeout` is not set, the barrier waits for all members.
+    /// Returns `BarrierStatus::NotLeader` if this server is not the leader, in which case the barrier should be retried at the new leader.
+    pub fn poll_barrier(&self, barrier: &BarrierHandle) -> BarrierStatus {
+        self.seq_paxos.barrier_status(barrier.idx)
+    }
+
     /// Trim the log and create a snapshot. ** Note: only up to the `decided_idx` can be snapshotted **
     /// # Arguments
     /// `compact_idx` - Snapshots all entries < [`compact_idx`], if the [`compact_idx`] is None then the decided index will be used.
@@ -145,6 +372,29 @@ where
         self.seq_paxos.snapshot(compact_idx, local_only)
     }
 
+    /// Initializes the storage of a new server with `snapshot`, a trusted snapshot of the decided log up to `compacted_idx` that was obtained out-of-band,
+    /// e.g. from a backup, and sets the decided index to `decided_idx`. The server then only needs to be synchronized with the entries after `compacted_idx` by the leader.
+    /// Since the server has no entries after the snapshot, `decided_idx` must equal `compacted_idx`, otherwise `BootstrapErr::InvalidDecidedIdx` is returned.
+    /// Must be called right after building the server, before handling any messages. Returns `BootstrapErr::NotEmpty` if the storage already has a log, a decided index
+    /// or a snapshot. A promised round, e.g. of the `initial_leader`, is kept.
+    pub fn bootstrap_from_snapshot(
+        &mut self,
+        snapshot: S,
+        compacted_idx: u64,
+        decided_idx: u64,
+    ) -> Result<(), BootstrapErr> {
+        self.seq_paxos
+            .bootstrap_from_snapshot(snapshot, compacted_idx, decided_idx)
+    }
+
+    /// Compacts the log of all servers right away so that only the last `keep_last` decided entries are kept, e.g. when running out of memory. Must be called at the leader.
+    /// If snapshots are used, the rest of the decided log is snapshotted and servers that are lagging behind will be synchronized with the snapshot.
+    /// Otherwise, the log is only trimmed up to the index accepted by all servers, so that lagging servers can still catch up.
+    /// Returns the number of entries that were compacted at this server.
+    pub fn emergency_trim(&mut self, keep_last: u64) -> Result<u64, CompactionErr> {
+        self.seq_paxos.emergency_trim(keep_last)
+    }
+
     /// Return the decided index.
     pub fn get_decided_idx(&self) -> u64 {
         self.seq_paxos.get_decided_idx()
@@ -175,8 +425,47 @@ where
         }
     }
 
+    /// Returns a summary of the health of this server, e.g. to be used in liveness or readiness probes.
+    /// Whether the log is advancing can be derived from `last_decide_tick`, or by comparing the `decided_idx` of consecutive calls.
+    pub fn health(&self) -> ConsensusHealth {
+        ConsensusHealth {
+            has_leader: self.get_current_leader().is_some(),
+            is_leader: self.seq_paxos.is_leader(),
+            majority_connected: self.ble.is_quorum_connected(),
+            decided_idx: self.get_decided_idx(),
+            last_decide_tick: self.seq_paxos.get_last_decide_tick(),
+            storage_error: self.seq_paxos.get_storage_error(),
+        }
+    }
+
+    /// Returns the number of calls to `tick()` since the current leader was elected, or `None` if there is no leader.
+    /// Every change of the leader's ballot restarts the count, even if the same server is re-elected.
+    pub fn ticks_as_leader(&self) -> Option<u64> {
+        self.seq_paxos.ticks_as_leader()
+    }
+
+    /// Returns the number of calls to `tick()` at which the leader election of this server knew of no leader, in total since this server was created, e.g. to compute its availability.
+    /// Only calls to `tick()` are counted, so a server that is paused by not calling `tick()` does not accumulate any ticks until it is resumed. The count is not affected by `reset_metrics()`.
+    /// Every configuration starts counting from `0`, so after a reconfiguration, the ticks of the previous configurations must be added to account the whole lifetime of a server.
+    pub fn total_leaderless_ticks(&self) -> u64 {
+        self.leaderless_ticks
+    }
+
+    /// Returns the number of consecutive calls to `tick()` up to the latest one at which there was no leader, or `0` if there was a leader at the latest call to `tick()`.
+    pub fn leaderless_streak(&self) -> u64 {
+        self.leaderless_streak
+    }
+
     /// Returns the outgoing messages from this replica. The messages should then be sent via the network implementation.
     pub fn outgoing_messages(&mut self) -> Vec<Message<T, S>> {
+        self.outgoing_iter().collect()
+    }
+
+    /// Takes the same messages as `outgoing_messages()`, but returns them one at a time without collecting them into a new `Vec`.
+    /// This lets an asynchronous network implementation serialize and send each message and yield to its runtime in between, e.g. after a newly elected leader
+    /// has created the `AcceptSync` messages for a long log. The iterator is not lazy: the messages are already created when handling the incoming messages, and are all taken
+    /// from this server when this is called, i.e. dropping the iterator early drops the remaining messages.
+    pub fn outgoing_iter(&mut self) -> impl Iterator<Item = Message<T, S>> {
         let paxos_msgs = self
             .seq_paxos
             .get_outgoing_msgs()
@@ -187,7 +476,20 @@ where
             .get_outgoing_msgs()
             .into_iter()
             .map(|b| Message::BLE(b));
-        ble_msgs.chain(paxos_msgs).collect()
+        ble_msgs.chain(paxos_msgs)
+    }
+
+    /// Returns the number of outgoing messages of Sequence Paxos and the leader election, without taking them.
+    /// A steadily growing number indicates that the network implementation does not keep up, and proposals should be throttled.
+    pub fn outgoing_len(&self) -> usize {
+        self.seq_paxos.outgoing_len() + self.ble.outgoing_len()
+    }
+
+    /// Returns `true` if there are no outgoing messages to be sent, no heartbeat round is waiting for replies (see `round_in_progress()`), no pending proposals and all entries in the log of this server are decided.
+    /// Can be used for a graceful shutdown: stop appending and calling `election_timeout()`, then keep sending the outgoing messages until this returns `true`.
+    /// A round stays in progress while a peer does not reply, so the shutdown should be bounded by a timeout if peers might have failed.
+    pub fn is_quiescent(&self) -> bool {
+        self.seq_paxos.is_quiescent() && self.ble.is_quiescent()
     }
 
     /// Read entry at index `idx` in the log. Returns `None` if `idx` is out of bounds.
@@ -198,6 +500,18 @@ where
         }
     }
 
+    /// Read entry at index `idx` in the log once this server has decided at least `target` entries, e.g. to read-your-writes at any server.
+    /// `target` is a session token that the client obtained from its previous write: the decided index after its entry, i.e. `idx + 1` for a proposal that was completed at `idx`.
+    /// Returns `ReadResult::NotReady` with the current decided index if this server has not caught up to `target` yet, in which case the read should be retried later.
+    pub fn read_at_least(&self, idx: u64, target: u64) -> ReadResult<T, S> {
+        let decided_idx = self.get_decided_idx();
+        if decided_idx < target {
+            ReadResult::NotReady(decided_idx)
+        } else {
+            ReadResult::Ready(self.read(idx))
+        }
+    }
+
     /// Read entries in the range `r` in the log. Returns `None` if `r` is out of bounds.
     pub fn read_entries<R>(&self, r: R) -> Option<Vec<LogEntry<T, S>>>
     where
@@ -207,17 +521,316 @@ where
     }
 
     /// Read all decided entries from `from_idx` in the log. Returns `None` if `from_idx` is out of bounds.
+    /// If this configuration has been stopped, the decided StopSign is returned as the last entry, i.e., `LogEntry::StopSign`.
     pub fn read_decided_suffix(&self, from_idx: u64) -> Option<Vec<LogEntry<T, S>>> {
         self.seq_paxos
             .internal_storage
             .read_decided_suffix(from_idx)
     }
 
-    /// Handle an incoming message.
+    /// Reads a page of at most `max` decided entries, starting at `cursor` or at the beginning of the log if `cursor` is `None`, e.g. to export a large decided log with bounded memory.
+    /// Returns the entries and the cursor of the next page. An empty page means that there are no more decided entries at the moment, and the returned cursor can be used to continue once there are.
+    /// If the entries at the cursor have been compacted since the previous page, the page starts with `LogEntry::Trimmed` (or `LogEntry::Snapshotted`) at the cursor and the next page continues after the compacted entries.
+    /// Returns `None` as the next cursor once the decided StopSign has been read, as no entries follow it in this configuration.
+    pub fn read_page(
+        &self,
+        cursor: Option<Cursor>,
+        max: usize,
+    ) -> (Vec<LogEntry<T, S>>, Option<Cursor>) {
+        let start = cursor.map_or(0, |c| c.next_idx);
+        let decided_idx = self.get_decided_idx();
+        let end = decided_idx.min(start.saturating_add(max as u64));
+        let entries = if start < end {
+            self.read_entries(start..end).unwrap_or_default()
+        } else {
+            vec![]
+        };
+        let next_idx = entries.iter().fold(start, |idx, e| match e {
+            LogEntry::Trimmed(trimmed_idx) => *trimmed_idx,
+            LogEntry::Snapshotted(s) => s.trimmed_idx,
+            _ => idx + 1,
+        });
+        let next = match self.is_reconfigured() {
+            Some(_) if next_idx >= decided_idx => None,
+            _ => Some(Cursor { next_idx }),
+        };
+        (entries, next)
+    }
+
+    /// Subscribes to the decided entries of the log from index `from_idx`, e.g. to tail the log with a read replica that is not part of the configuration.
+    /// The returned [`CommittedStream`] is a cursor that is advanced with [`poll_committed()`](Self::poll_committed).
+    pub fn subscribe_committed(&self, from_idx: u64) -> CommittedStream {
+        CommittedStream { next_idx: from_idx }
+    }
+
+    /// Returns the entries that were decided since the previous poll of `stream`, together with their index in the log, and advances `stream` past them.
+    /// If the subscriber fell behind and the entries it has not polled yet have been compacted, the first entry is `LogEntry::Snapshotted` (or `LogEntry::Trimmed` if snapshots are not used) at the index of `stream`, covering all entries up to its `trimmed_idx`.
+    /// The decided StopSign is returned as the last entry.
+    ///
+    /// Delivery is at-least-once: a snapshot might include entries that have already been polled, and a subscriber that restarts from its last processed index might receive entries again.
+    pub fn poll_committed(&self, stream: &mut CommittedStream) -> Vec<(u64, LogEntry<T, S>)> {
+        let entries = match self.read_decided_suffix(stream.next_idx) {
+            Some(entries) => entries,
+            None => return vec![],
+        };
+        entries
+            .into_iter()
+            .map(|e| {
+                let idx = stream.next_idx;
+                stream.next_idx = match &e {
+                    LogEntry::Trimmed(trimmed_idx) => *trimmed_idx,
+                    LogEntry::Snapshotted(s) => s.trimmed_idx,
+                    _ => idx + 1,
+                };
+                (idx, e)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the decided entries after the compacted prefix, i.e., from index `get_compacted_idx()` to `get_decided_idx()`.
+    /// The entries are read lazily from storage in batches, so the log is never cloned as a whole. The decided StopSign is not included.
+    /// Every entry is yielded as an owned clone rather than a reference, since `Storage::get_entries()` returns owned entries.
+    /// The iterator borrows `OmniPaxos`, hence the log cannot be compacted (and nothing can be decided) while the iterator is alive.
+    pub fn decided_iter(&self) -> impl Iterator<Item = T> + '_ {
+        let storage = &self.seq_paxos.internal_storage;
+        let decided_idx = storage.get_decided_idx();
+        (storage.get_compacted_idx()..decided_idx)
+            .step_by(DECIDED_ITER_BATCH_SIZE as usize)
+            .flat_map(move |from| {
+                let to = (from + DECIDED_ITER_BATCH_SIZE).min(decided_idx);
+                storage.get_entries(from, to)
+            })
+    }
+
+    /// Returns the decided entries from index `from` together with the watermark, i.e. the index after the last returned entry, for checkpointing.
+    /// The watermark is `from + entries.len()`, and equal to `get_decided_idx()` unless `from` is greater. The decided StopSign is not included.
+    /// If `from` precedes the compacted index, the compacted entries cannot be returned: the entries are returned from `get_compacted_idx()` instead,
+    /// such that the watermark is `get_compacted_idx() + entries.len()`. The compacted entries can be read with `read(from)`, e.g. as a snapshot.
+    pub fn decided_since(&self, from: u64) -> (Vec<T>, u64) {
+        let storage = &self.seq_paxos.internal_storage;
+        let from = from.max(storage.get_compacted_idx());
+        let decided_idx = storage.get_decided_idx().min(storage.get_log_len());
+        if from >= decided_idx {
+            return (vec![], from);
+        }
+        let entries = storage.get_entries(from, decided_idx);
+        let watermark = from + entries.len() as u64;
+        (entries, watermark)
+    }
+
+    /// Returns the number of entries yielded by `decided_iter()`, i.e., the number of decided entries that have not been compacted.
+    pub fn decided_len(&self) -> u64 {
+        self.get_decided_idx() - self.get_compacted_idx()
+    }
+
+    /// Returns the number of entries in the log that are accepted but not decided yet.
+    /// At the leader, these are the proposals that are currently being replicated, which can be used for admission control of new proposals.
+    pub fn inflight_count(&self) -> usize {
+        let storage = &self.seq_paxos.internal_storage;
+        storage
+            .get_log_len()
+            .saturating_sub(storage.get_decided_idx()) as usize
+    }
+
+    /// Returns the number of entries that can still be appended before the window of `max_inflight_entries` is full and `append()` returns `ProposeErr::WouldBlock`,
+    /// or `None` if the number of in-flight entries is not limited. The window is occupied by the entries of `inflight_count()` and by the proposals that wait for the leader to finish its prepare phase.
+    pub fn inflight_available(&self) -> Option<u64> {
+        self.seq_paxos.inflight_available()
+    }
+
+    /// Returns the total size of the entries in the log that are accepted but not decided yet, where the size of each entry is given by `size_of`.
+    pub fn inflight_bytes<F>(&self, size_of: F) -> usize
+    where
+        F: Fn(&T) -> usize,
+    {
+        let storage = &self.seq_paxos.internal_storage;
+        storage
+            .get_entries(storage.get_decided_idx(), storage.get_log_len())
+            .iter()
+            .map(size_of)
+            .sum()
+    }
+
+    /// Verifies that `checksum` (computed by the sender with `util::crc32()`) matches the serialized message `bytes`.
+    /// Returns `false` if the message is corrupt, in which case it should be dropped instead of being deserialized and passed to `handle_incoming()`.
+    #[cfg(feature = "checksum")]
+    pub fn verify_checksum(&mut self, bytes: &[u8], checksum: u32) -> bool {
+        let valid = crate::util::crc32(bytes) == checksum;
+        if !valid {
+            self.corrupt_messages_dropped += 1;
+        }
+        valid
+    }
+
+    /// Returns the serialized message of `envelope` if its message version is supported by this server, see `supported_message_versions()`.
+    /// Otherwise, the mismatch is logged and returned, and the message should be dropped instead of being deserialized.
+    pub fn open_envelope(&self, envelope: Envelope) -> Result<Vec<u8>, VersionMismatch> {
+        let result = envelope.open();
+        #[cfg(feature = "logging")]
+        if let Err(e) = &result {
+            self.seq_paxos.log_version_mismatch(e);
+        }
+        result
+    }
+
+    /// Returns the range of message versions that this server can handle, i.e. `MIN_MESSAGE_VERSION..=MESSAGE_VERSION`.
+    pub fn supported_message_versions(&self) -> RangeInclusive<u16> {
+        MIN_MESSAGE_VERSION..=MESSAGE_VERSION
+    }
+
+    /// Returns the number of messages that were dropped because they were sent by a server of another cluster, see `cluster_id` in `OmniPaxosConfig`.
+    pub fn foreign_messages_dropped(&self) -> u64 {
+        self.seq_paxos.foreign_messages_dropped() + self.ble.foreign_messages_dropped()
+    }
+
+    /// Returns the number of leader election messages that were dropped because they were sent by a server that is not a peer, see `HandleErr::UnknownPeer`.
+    pub fn unknown_peer_messages_dropped(&self) -> u64 {
+        self.ble.unknown_peer_messages_dropped()
+    }
+
+    /// Returns the number of messages that were dropped due to a mismatching checksum in `verify_checksum()`.
+    #[cfg(feature = "checksum")]
+    pub fn corrupt_messages_dropped(&self) -> u64 {
+        self.corrupt_messages_dropped
+    }
+
+    /// Sets the signer that is used by `sign_message()` and `handle_incoming_signed()` and starts the signing session `session`.
+    /// The session must be greater than the sessions of all previous runs of this server, e.g. a restart counter that is persisted before calling this, or the receivers drop its messages as replays.
+    #[cfg(feature = "signing")]
+    pub fn set_message_signer(
+        &mut self,
+        signer: Box<dyn MessageSigner + Send + Sync>,
+        session: u64,
+    ) {
+        self.signer = Some(signer);
+        self.signing_session = session;
+        self.next_signing_seq = 0;
+    }
+
+    /// Returns the signature of the serialized outgoing message `bytes`, which the network layer should send along with the message.
+    /// Every signature carries the next sequence number of the current session, so each call must be for a distinct message. Returns `None` if no signer has been set.
+    #[cfg(feature = "signing")]
+    pub fn sign_message(&mut self, bytes: &[u8]) -> Option<MessageSignature> {
+        let signer = self.signer.as_ref()?;
+        let (session, seq) = (self.signing_session, self.next_signing_seq);
+        self.next_signing_seq += 1;
+        Some(MessageSignature {
+            session,
+            seq,
+            signature: signer.sign(&MessageSignature::signed_bytes(session, seq, bytes)),
+        })
+    }
+
+    /// Handles the incoming message that `deserialize` returns for the serialized message `bytes`, if `signature` is a valid signature of `bytes` by the sender of that message, it is addressed to this server and it is not a replay of a message that was already handled.
+    /// Otherwise, e.g. for a message with a spoofed sender, tampered bytes, a message redirected from another receiver or a replayed signature, or if `deserialize` returns `None`, the message is dropped and `false` is returned. All messages are dropped if no signer has been set.
+    #[cfg(feature = "signing")]
+    pub fn handle_incoming_signed<F>(
+        &mut self,
+        bytes: &[u8],
+        signature: &MessageSignature,
+        deserialize: F,
+    ) -> bool
+    where
+        F: FnOnce(&[u8]) -> Option<Message<T, S>>,
+    {
+        let m = match (self.signer.as_ref(), deserialize(bytes)) {
+            (Some(signer), Some(m))
+                if signer.verify(
+                    m.get_sender(),
+                    &MessageSignature::signed_bytes(signature.session, signature.seq, bytes),
+                    &signature.signature,
+                ) =>
+            {
+                m
+            }
+            _ => {
+                self.unauthenticated_messages_dropped += 1;
+                return false;
+            }
+        };
+        if m.get_receiver() != self.ble.pid() {
+            self.unauthenticated_messages_dropped += 1;
+            return false;
+        }
+        let fresh = match self.replay_windows.get_mut(&m.get_sender()) {
+            Some(window) => window.accept(signature.session, signature.seq),
+            None => {
+                self.replay_windows.insert(
+                    m.get_sender(),
+                    ReplayWindow::with(signature.session, signature.seq),
+                );
+                true
+            }
+        };
+        if fresh {
+            self.handle_incoming(m);
+        } else {
+            self.unauthenticated_messages_dropped += 1;
+        }
+        fresh
+    }
+
+    /// Returns the number of messages that were dropped due to an invalid signature, another receiver or as a replay in `handle_incoming_signed()`.
+    #[cfg(feature = "signing")]
+    pub fn unauthenticated_messages_dropped(&self) -> u64 {
+        self.unauthenticated_messages_dropped
+    }
+
+    /// Handle an incoming message of the leader election. Returns `true` if the message was applied, or `false` if it was ignored as stale (e.g. a heartbeat reply from a previous round) or from an unknown sender.
+    /// Unlike `handle_incoming()`, this lets the network layer meter the dropped messages.
+    pub fn handle_ble_checked(&mut self, m: BLEMessage) -> bool {
+        self.try_handle_ble(m).is_ok()
+    }
+
+    /// Handles a message of Sequence Paxos. A leader that Sequence Paxos followed after a `Prepare` with a greater ballot is adopted by the leader election too,
+    /// such that it does not try to override the leader in its next heartbeat round.
+    fn try_handle_seq_paxos(&mut self, m: PaxosMessage<T, S>) -> Result<(), HandleErr> {
+        let res = self.seq_paxos.try_handle(m);
+        if let Some(b) = self
+            .ble
+            .accept_external_leader(self.seq_paxos.get_current_leader())
+        {
+            self.handle_leader(b);
+        }
+        res
+    }
+
+    /// Handles a message of the leader election. A leader that announced itself is passed to Sequence Paxos, which requests a `Prepare` from it to follow it right away.
+    fn try_handle_ble(&mut self, m: BLEMessage) -> Result<(), HandleErr> {
+        let res = self.ble.try_handle(m);
+        if let Some(b) = self.ble.take_announced_leader() {
+            self.handle_leader(b);
+            self.seq_paxos.reconnected(b.pid);
+        }
+        res
+    }
+
+    /// Handle an incoming message. Unlike `handle_incoming()`, returns the reason if the message was ignored, e.g. `HandleErr::UnknownPeer` for a message of the leader election
+    /// or `HandleErr::ForeignCluster` for a message of another cluster, which lets the network layer log or disconnect misbehaving peers.
+    pub fn try_handle(&mut self, m: Message<T, S>) -> Result<(), HandleErr> {
+        match m {
+            Message::SequencePaxos(p) => self.try_handle_seq_paxos(p),
+            Message::BLE(b) => self.try_handle_ble(b),
+        }
+    }
+
+    /// Handle an incoming message. Ignored messages are counted, e.g. by `unknown_peer_messages_dropped()`.
     pub fn handle_incoming(&mut self, m: Message<T, S>) {
         match m {
-            Message::SequencePaxos(p) => self.seq_paxos.handle(p),
-            Message::BLE(b) => self.ble.handle(b),
+            Message::SequencePaxos(p) => {
+                let _ = self.try_handle_seq_paxos(p);
+            }
+            Message::BLE(b) => {
+                let _ = self.try_handle_ble(b);
+            }
+        }
+    }
+
+    /// Handle a batch of incoming messages of both Sequence Paxos and the leader election, in the order of `msgs`.
+    /// Together with [`outgoing_messages()`](Self::outgoing_messages), this lets the network layer exchange all messages of an iteration with two calls.
+    pub fn handle_all(&mut self, msgs: Vec<Message<T, S>>) {
+        for m in msgs {
+            self.handle_incoming(m);
         }
     }
 
@@ -231,34 +844,471 @@ where
         self.seq_paxos.append(entry)
     }
 
+    /// Append an entry to the replicated log and track whether it gets decided. Returns the id of the proposal.
+    /// The outcome of the proposal can later be retrieved using [`take_completed()`](Self::take_completed) and [`take_failed()`](Self::take_failed).
+    /// Unlike [`append()`](Self::append), the entry is not forwarded. Returns `ProposeErr::NotLeader` with the currently known leader if this server is not the leader.
+    pub fn append_tracked(&mut self, entry: T) -> Result<ProposalId, ProposeErr<T>> {
+        self.seq_paxos.append_tracked(entry)
+    }
+
+    /// Returns the leadership state of this server at the time it last rejected a proposal of `append_tracked()` with `ProposeErr::NotLeader`, or `None` if it never did.
+    pub fn last_rejection(&self) -> Option<RejectionContext> {
+        self.seq_paxos.get_last_rejection()
+    }
+
+    /// Returns the tracked proposals that have been decided since the last call, together with their index in the log.
+    pub fn take_completed(&mut self) -> Vec<(ProposalId, u64)> {
+        self.seq_paxos.take_completed_proposals()
+    }
+
+    /// Returns the tracked proposals that have failed since the last call, together with the reason, e.g. a leader change before they got decided.
+    /// **Note:** A failed proposal might still get decided, e.g. by the new leader or after timing out. Retried proposals should therefore be idempotent.
+    pub fn take_failed(&mut self) -> Vec<(ProposalId, ProposalFailure)> {
+        self.seq_paxos.take_failed_proposals()
+    }
+
+    /// Returns the proposals that this server forwarded with `append()` and that the leader rejected since the last call, as the storage or the window of `max_inflight_entries` of the leader is full.
+    pub fn take_rejected(&mut self) -> Vec<T> {
+        self.seq_paxos.take_rejected_proposals()
+    }
+
     /// Propose a reconfiguration. Returns error if already stopped or new configuration is empty.
     pub fn reconfigure(&mut self, rc: ReconfigurationRequest) -> Result<(), ProposeErr<T>> {
         self.seq_paxos.reconfigure(rc)
     }
 
+    /// Confirms that the new configuration of the decided `StopSign` is up, e.g. once the instance of the new configuration knows of a leader.
+    /// Without a confirmation, this node records `ConsensusEvent::ReconfigurationStalled` `reconfiguration_timeout` ticks after the `StopSign` was decided.
+    pub fn confirm_reconfiguration(&mut self) {
+        self.seq_paxos.confirm_reconfiguration()
+    }
+
     /// Handles re-establishing a connection to a previously disconnected peer.
     /// This should only be called if the underlying network implementation indicates that a connection has been re-established.
     pub fn reconnected(&mut self, pid: NodeId) {
         self.seq_paxos.reconnected(pid)
     }
 
+    /// Drives the time-based behaviour of Sequence Paxos, e.g. resending messages that might have been lost.
+    /// This function should be called periodically, preferably more often than `election_timeout()`. For instance, it could be called every time the outgoing messages are sent.
+    pub fn tick(&mut self) {
+        if self.ble.has_leader() {
+            self.leaderless_streak = 0;
+        } else {
+            self.leaderless_ticks = self.leaderless_ticks.saturating_add(1);
+            self.leaderless_streak = self.leaderless_streak.saturating_add(1);
+        }
+        self.seq_paxos.tick();
+        self.check_transfer();
+    }
+
+    /// Transfers the leadership of this leader to `pid`, e.g. to move the leader off a server before maintenance. Once `pid` has accepted the whole log
+    /// of this leader, checked immediately and at every `tick()`, it is sent a `TimeoutNow` that makes it increase its ballot number to take over in the next heartbeat rounds.
+    /// The `TimeoutNow` is resent every `resend_timeout` ticks until the leadership changes. This leader replicates new entries while the target catches up.
+    /// Once the `TimeoutNow` is sent, it still accepts appends but holds them back and forwards them to the target when it has taken over,
+    /// so that no entry is appended to a log that the target has already caught up with. Writes remain available and no entry is lost during the transfer.
+    /// Unlike `hint_leader()`, the target is guaranteed to have all the entries of the log, so it can start accepting entries as soon as it is elected.
+    /// Transferring to this server itself has no effect. The transfer is abandoned if this server is no longer the leader before the target caught up.
+    pub fn transfer_leadership_to(&mut self, pid: NodeId) -> Result<(), TransferErr> {
+        self.seq_paxos.transfer_leadership_to(pid)?;
+        self.check_transfer();
+        Ok(())
+    }
+
+    /// Returns the target of the leadership transfer that has not caught up yet, if any.
+    pub fn pending_transfer(&self) -> Option<NodeId> {
+        self.seq_paxos.pending_transfer()
+    }
+
+    /// Sends a `TimeoutNow` to the target of the leadership transfer if it has caught up, or resends it if the target has not taken over yet.
+    fn check_transfer(&mut self) {
+        if let Some(pid) = self.seq_paxos.take_transfer_timeout_now() {
+            self.ble.send_timeout_now(pid);
+        }
+    }
+
     /*** BLE calls ***/
     /// Update the custom priority used in the Ballot for this server.
     pub fn set_priority(&mut self, p: u64) {
         self.ble.set_priority(p)
     }
 
+    /// Starts a heartbeat round right away instead of waiting for the next `election_timeout()`, e.g. after a network blip, and announces the leadership of this server to its peers.
+    /// A peer adopts the announced leader right away if its ballot is greater than the one of the leader the peer knows of, like with `hint_leader()`. The peers reply with their current ballots,
+    /// which refreshes the view of the leader on which peers are connected. The round is still ended by the next `election_timeout()`, and the replies of the round that was interrupted keep counting for it.
+    /// Does nothing if this server is not the leader.
+    pub fn announce_leadership(&mut self) {
+        self.ble.announce_leadership();
+    }
+
+    /// Hint the leader of the cluster, e.g., from an external service that knows the current leader. The hint is adopted only if `leader_ballot` is greater than the ballot of the current leader, otherwise it is ignored.
+    /// Unlike `initial_leader` in `OmniPaxosConfig`, this can be called at any time and multiple times.
+    /// An adopted hint is passed to both the leader election and Sequence Paxos, which keeps their views of the leader consistent.
+    /// The leader election therefore does not try to override the hinted leader, unless it later observes a greater ballot or the hinted leader is not connected to a majority.
+    /// Returns `true` if the hint was adopted.
+    pub fn hint_leader(&mut self, leader_ballot: Ballot) -> bool {
+        match self.ble.accept_external_leader(leader_ballot) {
+            Some(b) => {
+                self.handle_leader(b);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the same leader has been present for `stable_leader_rounds` consecutive heartbeat rounds, without any round since in which
+    /// the leader was missed or no majority replied. Returns `false` as soon as such a round occurs, or if `stable_leader_rounds` is not set.
+    /// Can be used to choose the interval at which `election_timeout()` is called, e.g. a longer interval while the leader is stable.
+    pub fn is_leader_stable(&self) -> bool {
+        self.ble.is_leader_stable()
+    }
+
+    /// Returns a copy of the state of the leader election of this server that can later be restored with `restore_election()`.
+    /// Only copies the leader election, the log and the state of Sequence Paxos are not included.
+    pub fn checkpoint_election(&self) -> ElectionCheckpoint {
+        ElectionCheckpoint(self.ble.clone())
+    }
+
+    /// Restores the state of the leader election from `checkpoint`, discarding the current state including the outgoing messages of the leader election.
+    /// Panics if the checkpoint was taken at another server.
+    pub fn restore_election(&mut self, checkpoint: ElectionCheckpoint) {
+        assert_eq!(
+            checkpoint.0.pid(),
+            self.ble.pid(),
+            "Checkpoint was taken at another server"
+        );
+        self.ble.restore(checkpoint.0);
+    }
+
+    /// Serializes the state of the leader election of this server, e.g. to hand it over to a cold standby that takes over the identity of this server.
+    /// Only the state that changes while running is included, i.e. the ballots, the heartbeat round, the leader and the round counters, prefixed with `ELECTION_STATE_VERSION`.
+    /// The outgoing messages, the configuration and hooks such as the ballot generator are not serialized, and the log and the state of Sequence Paxos are not included.
+    pub fn serialize_election_state(&self) -> Vec<u8> {
+        self.ble.serialize_state()
+    }
+
+    /// Replaces the state of the leader election with a state serialized by `serialize_election_state()` at a server with the same pid, discarding the outgoing messages of the leader election.
+    /// The server must be built with the same config as the server that serialized the state, and hooks such as the ballot generator have to be set again at the new server.
+    /// The leader of the restored state is passed to Sequence Paxos, as after an election. The state is not changed if an error is returned.
+    pub fn deserialize_election_state(&mut self, bytes: &[u8]) -> Result<(), ElectionStateErr> {
+        if let Some(b) = self.ble.deserialize_state(bytes)? {
+            self.handle_leader(b);
+        }
+        Ok(())
+    }
+
+    /// Returns whether this server could reach a majority of the servers in the latest heartbeat round, i.e. during the latest interval between two calls to `election_timeout()`.
+    /// A server in a minority partition cannot elect a leader or decide new entries, and can report this to its clients right away, e.g. to only serve stale reads.
+    /// Returns `PartitionStatus::Unknown` before the end of the first round, and with a `fixed_leader`.
+    pub fn partition_status(&self) -> PartitionStatus {
+        self.ble.partition_status()
+    }
+
+    /// Sets a hook that computes the ballot number this server uses to become a candidate after the leader was lost, from the ballot number of the previous leader,
+    /// e.g. to derive ballot numbers from an external epoch. By default, the number is incremented by one. A returned number that is not greater than the number
+    /// of the previous leader is ignored and the number is incremented instead, as this would violate the safety of the election.
+    pub fn set_ballot_generator(&mut self, f: BallotGenerator) {
+        self.ble.set_ballot_generator(f)
+    }
+
+    /// Returns whether this server is waiting for heartbeat replies, i.e., it sent the requests of the current round and not all peers have replied yet.
+    /// A round lasts until the next call to `election_timeout()`, which evaluates the replies and starts the next round. Returns `false` with a `fixed_leader`.
+    pub fn round_in_progress(&self) -> bool {
+        self.ble.round_in_progress()
+    }
+
+    /// Returns the number of heartbeat replies that this server received in the current round. If it stays at `0` across rounds, this server is isolated from its peers.
+    pub fn replies_this_round(&self) -> usize {
+        self.ble.replies_this_round()
+    }
+
+    /// Returns the greatest ballot of any peer that this server received in a heartbeat reply, including replies to previous rounds and ballots that were not elected.
+    /// Returns the default ballot if no reply has been received yet. If the own ballot is far below it, another server is dominating the elections.
+    pub fn highest_seen_ballot(&self) -> Ballot {
+        self.ble.highest_seen_ballot()
+    }
+
+    /// Returns the number of times this server voluntarily gave up its leadership due to `leader_rotation_rounds`.
+    pub fn voluntary_rotations(&self) -> u64 {
+        self.ble.voluntary_rotations()
+    }
+
+    /// Resets the observability counters to zero: `voluntary_rotations()`, `commit_latency_histogram()`, `foreign_messages_dropped()`, `unknown_peer_messages_dropped()` and, with the `checksum` and `signing` features, `corrupt_messages_dropped()` and `unauthenticated_messages_dropped()`.
+    /// The state of the protocol, e.g. the ballots, the leader and the log, is not affected, so this can be called at any time, e.g. at the start of every measurement window.
+    pub fn reset_metrics(&mut self) {
+        self.ble.reset_metrics();
+        self.seq_paxos.reset_metrics();
+        #[cfg(feature = "checksum")]
+        {
+            self.corrupt_messages_dropped = 0;
+        }
+        #[cfg(feature = "signing")]
+        {
+            self.unauthenticated_messages_dropped = 0;
+        }
+    }
+
+    /// Panics with a descriptive message if an internal invariant of the leader election is violated,
+    /// e.g. the elected leader is not a member of the configuration. Intended to be called after every operation in tests and fuzzing.
+    /// Only available in debug builds.
+    #[cfg(debug_assertions)]
+    pub fn verify_invariants(&self) {
+        self.ble.verify_invariants()
+    }
+
+    /// Returns the `(Ballot, quorum_connected)` pairs of all servers (including this server) that were considered in the latest heartbeat round that reached a majority.
+    /// Useful for diagnosing why a particular server was elected.
+    pub fn last_round_ballots(&self) -> &[(Ballot, bool)] {
+        self.ble.last_round_ballots()
+    }
+
+    /// Returns the `(Ballot, quorum_connected)` pairs of the latest heartbeat round that reached a majority, ordered by who would be elected if the current leader fails:
+    /// first the servers that advertised their candidacy (i.e., `quorum_connected` is `true`) by descending ballot, then the servers that are not connected to a majority.
+    /// Useful to judge the failover readiness of the cluster, e.g. on an operator dashboard. The current leader is usually the first candidate.
+    pub fn candidates(&self) -> Vec<(Ballot, bool)> {
+        self.ble.candidates()
+    }
+
+    /// Predicts the leader that this server would elect from a heartbeat round with `ballots`, given its current leader and configuration, without changing any state.
+    /// `ballots` are `(Ballot, quorum_connected)` pairs as returned by `candidates()`, and should include the ballot of this server as in a real round.
+    /// Uses the same selection as the real election, i.e., priorities, `preferred_leaders` and `sticky_leader` apply. The timing of the election, such as `quorum_grace_rounds`, is not taken into account.
+    /// Returns `None` if the round would not elect a leader, i.e., there is no candidate, or the greatest candidate has a smaller ballot than the current leader.
+    /// In the latter case the current leader is not a candidate in `ballots` and the real round would give it up, without electing another leader in the same round.
+    pub fn would_elect(&self, ballots: &[(Ballot, bool)]) -> Option<Ballot> {
+        self.ble.would_elect(ballots)
+    }
+
+    /// Sets a callback that is called with the greater ballot of the new leader as soon as this server is demoted from leader to follower,
+    /// e.g. when handling a `Prepare` with a greater ballot. Can be used to immediately stop serving requests that require leadership.
+    /// The callback is called while handling the message that caused the demotion and must therefore not block.
+    pub fn on_demotion(&mut self, f: Box<dyn Fn(Ballot) + Send + Sync>) {
+        self.seq_paxos.on_demotion(f)
+    }
+
+    /// Returns the number of entries decided at this server by their commit latency, i.e. the number of calls to `tick()` between adding an entry to the log and deciding it.
+    /// At the leader, this is the time from appending a proposal to deciding it, and at a follower from accepting an entry to learning that it is decided.
+    /// Bucket `0` counts the entries decided in the same tick, and bucket `i` those with a latency in `[2^(i-1), 2^i)` ticks. The last bucket also counts all greater latencies.
+    pub fn commit_latency_histogram(&self) -> [u64; COMMIT_LATENCY_BUCKETS] {
+        self.seq_paxos.commit_latency_histogram()
+    }
+
+    /// Takes the recorded state transitions of both the leader election and Sequence Paxos, in the order in which they occurred.
+    /// Only records events if `event_buffer_size` is set in `OmniPaxosConfig`.
+    pub fn drain_events(&mut self) -> Vec<ConsensusEvent> {
+        self.seq_paxos.drain_events()
+    }
+
+    /// Sets a callback that is called with the new compacted index whenever this server has installed a snapshot received from a peer,
+    /// i.e. a follower that was synchronized by the leader, or a new leader that adopted the log of a follower. Can be used to rebuild state derived from the log.
+    /// The callback is called while handling the message that carried the snapshot and must therefore not block.
+    pub fn on_snapshot_installed(&mut self, f: Box<dyn Fn(u64) + Send + Sync>) {
+        self.seq_paxos.on_snapshot_installed(f)
+    }
+
+    /// Sets a callback that is called with the index and the entry of every entry as it is decided at this server, exactly once per index and in index order,
+    /// including the entries that were decided by a previous leader and learned during recovery. Only entries decided after setting the callback are passed.
+    /// Entries that are decided as part of a snapshot received from a peer are not passed, see `on_snapshot_installed()`.
+    /// The callback is called while handling the message that decided the entries and must therefore not block.
+    pub fn set_on_decide(&mut self, f: DecideCallback<T>) {
+        self.seq_paxos.set_on_decide(f)
+    }
+
+    /// Sets a callback that is called with the index of the first entry and a batch of consecutive entries as they are decided at this server, with the same guarantees as `set_on_decide()`.
+    /// The entries that are decided together are passed in batches of up to `coalesce_decides` entries, or one entry per call if it is not set.
+    pub fn set_on_decide_batch(&mut self, f: DecideBatchCallback<T>) {
+        self.seq_paxos.set_on_decide_batch(f)
+    }
+
+    /// Sets the function that returns the size in bytes of a serialized entry, e.g. the length of its encoding in the format of the network implementation.
+    /// Once it is set, `append()` and `append_tracked()` reject entries that are larger than `max_entry_bytes` with `ProposeErr::TooLarge`.
+    pub fn set_entry_sizer(&mut self, f: EntrySizer<T>) {
+        self.seq_paxos.set_entry_sizer(f)
+    }
+
+    /// Raises the ballot number of this server to at least the number of `ballot`, e.g. when joining an established cluster, so that it does not need several election rounds to catch up with the ballot of the cluster.
+    pub fn ensure_ballot_at_least(&mut self, ballot: Ballot) {
+        self.ble.ensure_ballot_at_least(ballot)
+    }
+
+    /// Returns the pids of all the servers in the configuration, including this server, sorted in ascending order.
+    pub fn members(&self) -> Vec<NodeId> {
+        self.ble.members()
+    }
+
+    /// Returns the pids that are added and removed, in ascending order, when moving from the current configuration to `new_configuration`,
+    /// e.g. the `nodes` of a decided `StopSign`. The current configuration includes this server, i.e. this server is returned as removed if it is not part of `new_configuration`.
+    /// Can be used to set up and tear down the connections to the servers of the new configuration.
+    pub fn membership_diff(&self, new_configuration: &[NodeId]) -> (Vec<NodeId>, Vec<NodeId>) {
+        let members = self.members();
+        let mut added: Vec<NodeId> = new_configuration
+            .iter()
+            .filter(|pid| !members.contains(pid))
+            .cloned()
+            .collect();
+        added.sort_unstable();
+        added.dedup();
+        let removed = members
+            .into_iter()
+            .filter(|pid| !new_configuration.contains(pid))
+            .collect();
+        (added, removed)
+    }
+
+    /// Returns whether the current leader claimed to be connected to a majority in its heartbeat reply of the latest heartbeat round in which this server heard from a majority,
+    /// or `None` if there is no leader. A leader that was not heard from in that round, e.g. a leader kept during `quorum_grace_rounds` or a hinted leader, returns `Some(false)`.
+    /// This tells whether the leader is backed by a majority or only a best guess during instability. The connectivity of this server is returned by `health()`.
+    pub fn leader_quorum_connected(&self) -> Option<bool> {
+        self.ble.leader_quorum_connected()
+    }
+
+    /// Returns the most recent ballots that were rejected by the leader election for being lower than the ballot of the current leader.
+    #[cfg(feature = "rejected_ballots")]
+    pub fn rejected_ballots(&self) -> Vec<RejectedBallot> {
+        self.ble.rejected_ballots()
+    }
+
     /// If the heartbeat of a leader is not received when election_timeout() is called, the server might attempt to become the leader.
     /// It is also used for the election process, where the server checks if it can become the leader.
     /// This function should be called periodically to detect leader failure and drive the election process.
     /// For instance if `election_timeout()` is called every 100ms, then if the leader fails, the servers will detect it after 100ms and elect a new server after another 100ms if possible.
     pub fn election_timeout(&mut self) {
         if let Some(b) = self.ble.hb_timeout() {
-            self.seq_paxos.handle_leader(b);
+            self.handle_leader(b);
+        }
+    }
+
+    /// Registers another group with the same servers as this group to follow the leader elected by this group, so that only this group has to exchange heartbeats.
+    /// Every leader elected from then on, including the current leader, is returned for `group_id` by `take_group_leaders()`.
+    /// The registered groups should not call `election_timeout()`, but `tick()` should still be called on all groups. Groups with other servers need their own leader election,
+    /// since the elected leader must be connected to a majority of every group.
+    pub fn register_group(&mut self, group_id: ClusterId) {
+        if self.registered_groups.contains(&group_id) {
+            return;
+        }
+        self.registered_groups.push(group_id);
+        if let Some(b) = self.get_current_leader_ballot() {
+            self.group_leaders.push((group_id, b));
+        }
+    }
+
+    /// Takes the leaders elected for the groups registered with `register_group()` since the last call, in the order they were elected.
+    /// Each leader should be passed to `hint_leader()` of the group with the returned id at this server.
+    pub fn take_group_leaders(&mut self) -> Vec<(ClusterId, Ballot)> {
+        std::mem::take(&mut self.group_leaders)
+    }
+
+    /// Passes a leader elected by the leader election to Sequence Paxos and to the registered groups.
+    fn handle_leader(&mut self, b: Ballot) {
+        self.seq_paxos.handle_leader(b);
+        for group_id in &self.registered_groups {
+            self.group_leaders.push((*group_id, b));
         }
     }
 }
 
+impl<T, S, B> OmniPaxos<WithMeta<T>, S, B>
+where
+    T: Entry,
+    S: Snapshot<WithMeta<T>>,
+    B: Storage<WithMeta<T>, S>,
+{
+    /// Appends `entry` with its metadata `meta` to the replicated log, see `append()`. The metadata is decided together with the entry.
+    pub fn append_with_meta(
+        &mut self,
+        entry: T,
+        meta: EntryMeta,
+    ) -> Result<(), ProposeErr<WithMeta<T>>> {
+        self.append(WithMeta::with(entry, meta))
+    }
+}
+
+impl<S, B> OmniPaxos<EncodedEntry, S, B>
+where
+    S: Snapshot<EncodedEntry>,
+    B: Storage<EncodedEntry, S>,
+{
+    /// Sets the codec that `append_encoded()` uses to encode entries of at least `threshold` bytes. Smaller entries are appended as they are, as e.g. compressing them would not pay off.
+    /// Must be set to an equivalent codec at all servers that read the log.
+    pub fn set_entry_codec(&mut self, codec: Box<dyn EntryCodec + Send + Sync>, threshold: usize) {
+        self.entry_codec = Some((codec, threshold));
+    }
+
+    /// Appends the entry `bytes` to the replicated log, see `append()`. The entry is encoded with the codec of `set_entry_codec()` if it is at least as large as the threshold.
+    /// Without a codec, the entry is appended as it is. This and `read_decoded()` are the only methods that apply the codec: `append()` and `read()` store and return `EncodedEntry` as it is.
+    pub fn append_encoded(&mut self, bytes: Vec<u8>) -> Result<(), ProposeErr<EncodedEntry>> {
+        let entry = match &self.entry_codec {
+            Some((codec, threshold)) if bytes.len() >= *threshold => EncodedEntry {
+                bytes: codec.encode(&bytes),
+                encoded: true,
+            },
+            _ => EncodedEntry {
+                bytes,
+                encoded: false,
+            },
+        };
+        self.append(entry)
+    }
+
+    /// Returns the bytes of `entry`, which are decoded with the codec of `set_entry_codec()` if the entry was encoded, or `None` if the entry is encoded but no codec has been set.
+    pub fn decode_entry(&self, entry: &EncodedEntry) -> Option<Vec<u8>> {
+        match &self.entry_codec {
+            _ if !entry.encoded => Some(entry.bytes.clone()),
+            Some((codec, _)) => Some(codec.decode(&entry.bytes)),
+            None => None,
+        }
+    }
+
+    /// Read entry at index `idx` in the log like `read()`, but with the bytes of a decided or undecided entry decoded, see `decode_entry()`.
+    /// An encoded entry is returned as it is if no codec has been set.
+    pub fn read_decoded(&self, idx: u64) -> Option<LogEntry<EncodedEntry, S>> {
+        let decoded = |e: EncodedEntry| match self.decode_entry(&e) {
+            Some(bytes) => EncodedEntry {
+                bytes,
+                encoded: false,
+            },
+            None => e,
+        };
+        self.read(idx).map(|entry| match entry {
+            LogEntry::Decided(e) => LogEntry::Decided(decoded(e)),
+            LogEntry::Undecided(e) => LogEntry::Undecided(decoded(e)),
+            other => other,
+        })
+    }
+}
+
+/// Whether a server is connected to a majority, returned by [`OmniPaxos::partition_status()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PartitionStatus {
+    /// A majority of the servers (including this server) replied in the latest heartbeat round.
+    Majority,
+    /// Only `reachable` servers (including this server) replied in the latest heartbeat round, but `needed` are required for a majority.
+    Minority {
+        /// The number of servers that replied, including this server.
+        reachable: usize,
+        /// The number of servers in a majority.
+        needed: usize,
+    },
+    /// No heartbeat round has ended yet.
+    Unknown,
+}
+
+/// A summary of the health of an `OmniPaxos` server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusHealth {
+    /// Whether this server knows of a current leader.
+    pub has_leader: bool,
+    /// Whether this server is the leader.
+    pub is_leader: bool,
+    /// Whether this server received heartbeats from a majority in the latest election timeout.
+    pub majority_connected: bool,
+    /// The decided index of this server.
+    pub decided_idx: u64,
+    /// The number of calls to `tick()` after which the decided index last increased, or 0 if nothing has been decided.
+    pub last_decide_tick: u64,
+    /// The error of the storage for the latest proposal, e.g. `StorageErr::Full` if it had no capacity left, or `None` if the storage accepted it.
+    pub storage_error: Option<StorageErr>,
+}
+
 /// Used for proposing reconfiguration of the cluster.
 #[derive(Debug, Clone)]
 pub struct ReconfigurationRequest {
@@ -290,6 +1340,174 @@ where
 {
     Normal(T),
     Reconfiguration(Vec<NodeId>),
+    /// A tracked proposal was appended at a server that is not the leader. Returns this server's best knowledge of the leader, which is `None` if there is currently no known leader.
+    /// The leadership state of this server at the time of the rejection is returned by [`OmniPaxos::last_rejection()`].
+    NotLeader(T, Option<LeaderHint>),
+    /// The storage of this server has no capacity left for the proposal, i.e., [`Storage::check_capacity()`] returned `StorageErr::Full`. Entries must be compacted before new ones can be appended.
+    StorageFull(T),
+    /// The serialized entry is larger than `max_entry_bytes` (see [`OmniPaxos::set_entry_sizer()`]). Returns the entry, its size and the limit in bytes.
+    TooLarge {
+        entry: T,
+        size: usize,
+        limit: usize,
+    },
+    /// The log already holds `max_inflight_entries` entries that are not decided yet. The entry can be appended again once entries are decided, see [`OmniPaxos::inflight_available()`].
+    WouldBlock(T),
+}
+
+/// A significant state transition of a server, recorded with the number of calls to `tick()` at the time it occurred. Returned by [`OmniPaxos::drain_events()`].
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub enum ConsensusEvent {
+    /// The leader election elected the leader with `ballot`, which might be this server.
+    LeaderElected { tick: u64, ballot: Ballot },
+    /// This server was demoted from leader to follower of the greater `ballot`.
+    Demoted { tick: u64, ballot: Ballot },
+    /// The decided index of the log advanced to `decided_idx`.
+    Decided { tick: u64, decided_idx: u64 },
+    /// The log was trimmed or snapshotted up to `compacted_idx`.
+    Compacted { tick: u64, compacted_idx: u64 },
+    /// The `StopSign` of a reconfiguration was decided.
+    Reconfigured { tick: u64, stopsign: StopSign },
+    /// The undecided entries of this server from `from_idx` were discarded and replaced with the log of a new leader. Divergent entries that were accepted from a previous leader whose entries were not chosen are removed this way.
+    LogTruncated { tick: u64, from_idx: u64 },
+    /// No leader of the new configuration `configuration_id` was known `reconfiguration_timeout` ticks after this node was started, or the new configuration `configuration_id` was not confirmed `reconfiguration_timeout` ticks after the `StopSign` was decided, e.g. because the new nodes of a reconfiguration never came up.
+    ReconfigurationStalled { tick: u64, configuration_id: u32 },
+    /// This server has been the leader with `ballot` for `prepare_timeout` ticks without receiving promises from a majority.
+    PrepareTimedOut { tick: u64, ballot: Ballot },
+}
+
+impl ConsensusEvent {
+    /// Returns the number of calls to `tick()` at the time the event occurred.
+    pub fn tick(&self) -> u64 {
+        match self {
+            ConsensusEvent::LeaderElected { tick, .. }
+            | ConsensusEvent::Demoted { tick, .. }
+            | ConsensusEvent::Decided { tick, .. }
+            | ConsensusEvent::Compacted { tick, .. }
+            | ConsensusEvent::Reconfigured { tick, .. }
+            | ConsensusEvent::LogTruncated { tick, .. }
+            | ConsensusEvent::ReconfigurationStalled { tick, .. }
+            | ConsensusEvent::PrepareTimedOut { tick, .. } => *tick,
+        }
+    }
+}
+
+/// Determines when the leader automatically snapshots the decided log.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotTrigger {
+    /// Only snapshot when `snapshot()` is called.
+    Manual,
+    /// Snapshot the decided log on all servers once there are at least this many decided entries that have not been compacted.
+    /// Only applies if `S` uses snapshots.
+    LogLength(u64),
+}
+
+/// Determines how a former leader that rejoins the cluster reacts to a current leader with a greater ballot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RejoinPolicy {
+    /// Adopt the current leader, which minimizes the disruption of the cluster.
+    FastYield,
+    /// Contest the current leader with a greater ballot, i.e., take part in a fresh election.
+    Contest,
+}
+
+/// The reason why a tracked proposal failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProposalFailure {
+    /// The leader changed before the proposal got decided.
+    LeaderChanged,
+    /// The proposal was not decided within `proposal_ttl` ticks.
+    TimedOut,
+}
+
+/// The result of [`OmniPaxos::read_at_least()`].
+#[derive(Debug, Clone)]
+pub enum ReadResult<T, S>
+where
+    T: Entry,
+    S: Snapshot<T>,
+{
+    /// The server has decided the target index. Contains the entry at the read index, or `None` if it is out of bounds.
+    Ready(Option<LogEntry<T, S>>),
+    /// The server has not decided the target index yet. Contains the current decided index of the server.
+    NotReady(u64),
+}
+
+/// A cursor over the decided entries of the log. Created with [`OmniPaxos::subscribe_committed()`] and advanced with [`OmniPaxos::poll_committed()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommittedStream {
+    next_idx: u64,
+}
+
+impl CommittedStream {
+    /// Returns the index of the next entry that will be returned by [`OmniPaxos::poll_committed()`].
+    /// Can be persisted by the subscriber to resume from after a restart.
+    pub fn next_idx(&self) -> u64 {
+        self.next_idx
+    }
+}
+
+/// The position of the next page of decided entries returned by [`OmniPaxos::read_page()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    next_idx: u64,
+}
+
+impl Cursor {
+    /// Creates a cursor that starts reading at index `next_idx`, e.g. to resume an export after a restart.
+    pub fn with(next_idx: u64) -> Self {
+        Self { next_idx }
+    }
+
+    /// Returns the index of the first entry of the next page.
+    pub fn next_idx(&self) -> u64 {
+        self.next_idx
+    }
+}
+
+/// A barrier created with [`OmniPaxos::barrier()`] and polled with [`OmniPaxos::poll_barrier()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BarrierHandle {
+    idx: u64,
+}
+
+impl BarrierHandle {
+    /// Returns the index that all live members must have applied for the barrier to complete.
+    pub fn idx(&self) -> u64 {
+        self.idx
+    }
+}
+
+/// The status of a barrier returned by [`OmniPaxos::poll_barrier()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BarrierStatus {
+    /// All live members have applied the log up to the index of the barrier.
+    Complete,
+    /// Contains the pids of the live members that have not reported applying the log up to the index of the barrier yet.
+    Pending(Vec<NodeId>),
+    /// The server is not the leader.
+    NotLeader,
+}
+
+/// The leader as currently known by a server. Returned in errors to redirect clients to the leader.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LeaderHint {
+    /// The pid of the leader.
+    pub pid: NodeId,
+    /// The ballot of the leader. Can be compared to the ballot of other hints to detect a stale hint.
+    pub ballot: Ballot,
+}
+
+/// The leadership state of a server at the time it rejected a proposal with `ProposeErr::NotLeader`, e.g. to correlate the retries of a client with leader changes. Returned by [`OmniPaxos::last_rejection()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RejectionContext {
+    /// The greatest ballot that the server had promised.
+    pub ballot: Ballot,
+    /// The pid of the leader that the server believed in, or `None` if it did not know of a leader.
+    pub leader: Option<NodeId>,
+    /// The number of calls to `tick()` at the server at the time of the rejection.
+    pub tick: u64,
 }
 
 /// An error returning the proposal that was failed due to that the current configuration is stopped.
@@ -299,6 +1517,70 @@ pub enum CompactionErr {
     UndecidedIndex(u64),
     /// Trim was called with an index that is not decided by all servers yet. Returns the index decided by ALL servers currently.
     NotAllDecided(u64),
-    /// Trim was called at a follower node. Trim must be called by the leader, which is the returned NodeId.
-    NotCurrentLeader(NodeId),
+    /// Trim was called at a follower node. Trim must be called by the leader, which is returned if known.
+    NotCurrentLeader(Option<LeaderHint>),
+}
+
+/// An error returned by [`OmniPaxos::transfer_leadership_to()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransferErr {
+    /// The server is not the leader. Returns the leader, if known.
+    NotLeader(Option<LeaderHint>),
+    /// The target is not a peer of the leader or is a witness, and can therefore not become the leader. Returns the pid of the target.
+    InvalidTarget(NodeId),
+}
+
+/// An error returned by [`OmniPaxos::bootstrap_from_snapshot()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BootstrapErr {
+    /// The storage of the server already has state, e.g. because it has handled messages or was recovered from a previous run.
+    NotEmpty,
+    /// The snapshot type of the server does not support snapshots, e.g. `()`.
+    SnapshotsNotUsed,
+    /// The decided index is not the index the snapshot was taken at. Returns the decided index.
+    InvalidDecidedIdx(u64),
+}
+
+/// An error returned by [`OmniPaxos::open_envelope()`] for a message in a format that this server cannot handle, e.g. from a server running a newer version during a rolling upgrade.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// The message version of the sender.
+    pub version: u16,
+    /// The oldest message version supported by this server.
+    pub min_version: u16,
+    /// The newest message version supported by this server.
+    pub max_version: u16,
+}
+
+/// An error returned by [`OmniPaxos::deserialize_election_state()`] for a state that was not restored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElectionStateErr {
+    /// The bytes are not a state serialized by `serialize_election_state()`, e.g. because they are truncated or were serialized by a build with other features.
+    Malformed,
+    /// The state was serialized at another server. Returns the pid of that server.
+    OtherServer(NodeId),
+    /// The state was serialized in another format than `ELECTION_STATE_VERSION`. Returns the version of the state.
+    UnsupportedVersion(u8),
+}
+
+/// An error returned by [`OmniPaxos::try_handle()`] for a message that was not applied. Sequence Paxos messages are only rejected with `ForeignCluster`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HandleErr {
+    /// A heartbeat reply to a round before the previous round. A reply to the previous round still counts for the current round. Returns the round of the reply.
+    StaleRound(u32),
+    /// A heartbeat reply with the same ballot number and pid as a ballot that was already received in this round, or as the ballot of this server.
+    /// Without the `hardened` feature this can be a duplicated message, otherwise it indicates that two servers are configured with the same pid. Returns the duplicate ballot.
+    DuplicateBallot(Ballot),
+    /// The message was sent by this server itself.
+    Loopback,
+    /// The sender is not a peer of this server. Returns the pid of the sender.
+    UnknownPeer(NodeId),
+    /// The sender is part of another cluster. Returns the cluster of the sender.
+    ForeignCluster(ClusterId),
+    /// A heartbeat reply that does not answer an outstanding request of the sender, e.g. a duplicated or forged reply. Only returned with the `hardened` feature. Returns the sender.
+    UnknownNonce(NodeId),
+    /// The leader election is replaced by `fixed_leader` and does not handle any messages.
+    FixedLeader,
+    /// A `TimeoutNow` that was not sent by the current leader, or sent to a witness. Returns the sender.
+    NotLeader(NodeId),
 }
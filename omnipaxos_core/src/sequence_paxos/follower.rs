@@ -2,7 +2,6 @@ use super::super::ballot_leader_election::Ballot;
 
 use super::*;
 
-use crate::storage::SnapshotType;
 #[cfg(feature = "logging")]
 use slog::debug;
 
@@ -14,14 +13,28 @@ where
 {
     /*** Follower ***/
     pub(crate) fn handle_prepare(&mut self, prep: Prepare, from: NodeId) {
+        if prep.n < self.ble_leader {
+            // a leader with a greater ballot has been elected, do not let the stale one duel with it
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: from,
+                msg: PaxosMsg::PrepareNack(self.ble_leader),
+            });
+            return;
+        }
         if self.internal_storage.get_promise() <= prep.n {
-            self.leader = prep.n;
+            self.demote(prep.n);
+            self.set_leader(prep.n);
             self.internal_storage.set_promise(prep.n);
             self.state = (Role::Follower, Phase::Prepare);
             let na = self.internal_storage.get_accepted_round();
             let accepted_idx = self.internal_storage.get_log_len();
             let decided_idx = self.get_decided_idx();
-            let (decided_snapshot, suffix) = if na > prep.n_accepted {
+            let (decided_snapshot, suffix) = if self.internal_storage.is_witness() {
+                // a witness has no entries to send, the leader syncs with a full node that has them
+                (None, vec![])
+            } else if na > prep.n_accepted {
                 let ld = prep.decided_idx;
                 if ld < decided_idx && Self::use_snapshots() {
                     let delta_snapshot =
@@ -59,6 +72,7 @@ where
             };
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: from,
                 msg: PaxosMsg::Promise(promise),
             });
@@ -69,17 +83,17 @@ where
         if self.internal_storage.get_promise() == accsync.n
             && self.state == (Role::Follower, Phase::Prepare)
         {
+            let installed_snapshot = accsync.decided_snapshot.is_some();
+            let log_len = self.internal_storage.get_log_len();
+            // the entries after the synced prefix are replaced, i.e. divergent entries of a previous round are discarded
+            let truncated_idx = if installed_snapshot {
+                accsync.decided_idx
+            } else {
+                accsync.sync_idx
+            };
             let accepted = match accsync.decided_snapshot {
                 Some(s) => {
-                    match s {
-                        SnapshotType::Complete(c) => {
-                            self.internal_storage.set_snapshot(accsync.decided_idx, c);
-                        }
-                        SnapshotType::Delta(d) => {
-                            self.internal_storage.merge_snapshot(accsync.decided_idx, d);
-                        }
-                        _ => unimplemented!(),
-                    }
+                    self.install_snapshot(accsync.decided_idx, s);
                     let accepted_idx = self.internal_storage.append_entries(accsync.suffix);
                     Accepted {
                         n: accsync.n,
@@ -98,12 +112,27 @@ where
                 }
             };
             self.internal_storage.set_accepted_round(accsync.n);
-            self.internal_storage.set_decided_idx(accsync.decided_idx);
+            if truncated_idx < log_len {
+                #[cfg(feature = "logging")]
+                debug!(
+                    self.logger,
+                    "Truncated the accepted entries from {} to {}", truncated_idx, log_len
+                );
+                self.record_event(ConsensusEvent::LogTruncated {
+                    tick: self.ticks,
+                    from_idx: truncated_idx,
+                });
+            }
+            self.set_decided_idx(accsync.decided_idx);
+            if installed_snapshot {
+                self.notify_snapshot_installed();
+            }
             self.state = (Role::Follower, Phase::Accept);
             let cached_idx = self.outgoing.len();
             self.latest_accepted_meta = Some((accsync.n, cached_idx));
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: from,
                 msg: PaxosMsg::Accepted(accepted),
             });
@@ -123,18 +152,20 @@ where
                     let a = AcceptedStopSign { n: accsync.n };
                     self.outgoing.push(PaxosMessage {
                         from: self.pid,
+                        cluster_id: self.cluster_id,
                         to: from,
                         msg: PaxosMsg::AcceptedStopSign(a),
                     });
                 }
                 None => self.forward_pending_proposals(),
             }
-            self.internal_storage.set_decided_idx(accsync.decided_idx);
+            self.set_decided_idx(accsync.decided_idx);
         }
     }
 
     fn forward_pending_proposals(&mut self) {
-        let proposals = std::mem::take(&mut self.pending_proposals);
+        self.fail_pending_tracked();
+        let proposals = self.take_pending_proposals();
         if !proposals.is_empty() {
             self.forward_proposals(proposals);
         }
@@ -153,18 +184,53 @@ where
     }
 
     pub(crate) fn handle_acceptdecide(&mut self, acc: AcceptDecide<T>) {
+        if self.internal_storage.get_promise() == acc.n {
+            self.leader_decided_idx = self.leader_decided_idx.max(acc.decided_idx);
+        }
         if self.internal_storage.get_promise() == acc.n
             && self.state == (Role::Follower, Phase::Accept)
         {
-            let entries = acc.entries;
+            let log_len = self.internal_storage.get_log_len();
+            if acc.start_idx > log_len {
+                // missed preceding entries, the leader will resynchronize us
+                return;
+            }
+            // skip entries that were already accepted, e.g. if this is a resend
+            let num_accepted = (log_len - acc.start_idx) as usize;
+            let entries = acc.entries.into_iter().skip(num_accepted).collect();
             self.accept_entries(acc.n, entries);
             // handle decide
-            if acc.decided_idx > self.internal_storage.get_decided_idx() {
-                self.internal_storage.set_decided_idx(acc.decided_idx);
+            let decided_idx = acc.decided_idx.min(self.internal_storage.get_log_len());
+            if decided_idx > self.internal_storage.get_decided_idx() {
+                self.set_decided_idx(decided_idx);
             }
         }
     }
 
+    /// Send a `PrepareReq` to the leader if we are lagging behind, i.e. the leader has decided entries that we have not
+    /// and our decided index has not advanced since the previous check. Called at most once every `resend_timeout` ticks.
+    pub(crate) fn request_catchup(&mut self) {
+        let decided_idx = self.get_decided_idx();
+        let lagging =
+            self.leader_decided_idx > decided_idx && self.catchup_check_idx == Some(decided_idx);
+        self.catchup_check_idx = Some(decided_idx);
+        if lagging && self.leader != Ballot::default() && self.leader.pid != self.pid {
+            #[cfg(feature = "logging")]
+            debug!(
+                self.logger,
+                "Decided index {} is behind leader's {}, requesting catch-up",
+                decided_idx,
+                self.leader_decided_idx
+            );
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: self.leader.pid,
+                msg: PaxosMsg::PrepareReq,
+            });
+        }
+    }
+
     pub(crate) fn handle_accept_stopsign(&mut self, acc_ss: AcceptStopSign) {
         if self.internal_storage.get_promise() == acc_ss.n
             && self.state == (Role::Follower, Phase::Accept)
@@ -173,6 +239,7 @@ where
             let a = AcceptedStopSign { n: acc_ss.n };
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: self.leader.pid,
                 msg: PaxosMsg::AcceptedStopSign(a),
             });
@@ -180,8 +247,27 @@ where
     }
 
     pub(crate) fn handle_decide(&mut self, dec: Decide) {
+        if self.internal_storage.get_promise() == dec.n {
+            self.leader_decided_idx = self.leader_decided_idx.max(dec.decided_idx);
+        }
         if self.internal_storage.get_promise() == dec.n && self.state.1 == Phase::Accept {
-            self.internal_storage.set_decided_idx(dec.decided_idx);
+            // only decide entries that have been accepted, some might have been lost
+            let decided_idx = dec.decided_idx.min(self.internal_storage.get_log_len());
+            if decided_idx > self.internal_storage.get_decided_idx() {
+                self.set_decided_idx(decided_idx);
+            }
+        }
+    }
+
+    pub(crate) fn handle_applied_ack(&mut self, idx: u64, from: NodeId) {
+        if from == self.leader.pid {
+            self.acked_applied = Some((self.leader, idx));
+        }
+    }
+
+    pub(crate) fn handle_rejected_proposals(&mut self, mut entries: Vec<T>, from: NodeId) {
+        if self.peers.contains(&from) {
+            self.rejected_proposals.append(&mut entries);
         }
     }
 
@@ -193,8 +279,7 @@ where
                 .expect("No stopsign found when deciding!");
             ss.decided = true;
             self.internal_storage.set_stopsign(ss); // need to set it again now with the modified decided flag
-            self.internal_storage
-                .set_decided_idx(self.internal_storage.get_log_len() + 1);
+            self.set_decided_idx(self.internal_storage.get_log_len() + 1);
         }
     }
 
@@ -214,6 +299,7 @@ where
                 self.latest_accepted_meta = Some((n, cached_idx));
                 self.outgoing.push(PaxosMessage {
                     from: self.pid,
+                    cluster_id: self.cluster_id,
                     to: self.leader.pid,
                     msg: PaxosMsg::Accepted(accepted),
                 });
@@ -2,7 +2,6 @@ use super::super::{
     ballot_leader_election::Ballot,
     util::{LeaderState, PromiseMetaData},
 };
-use crate::storage::SnapshotType;
 
 use super::*;
 
@@ -17,20 +16,36 @@ where
     pub(crate) fn handle_leader(&mut self, n: Ballot) {
         #[cfg(feature = "logging")]
         debug!(self.logger, "Newly elected leader: {:?}", n);
+        // the election is recorded even if this server already promised `n`, e.g. after a `Prepare` that arrived before the leader election elected `n`
+        if n != self.ble_leader {
+            self.record_event(ConsensusEvent::LeaderElected {
+                tick: self.ticks,
+                ballot: n,
+            });
+        }
+        self.ble_leader = n;
         if n <= self.leader_state.n_leader || n <= self.internal_storage.get_promise() {
             return;
         }
+        self.handover = None;
         if self.stopped() {
+            self.fail_pending_tracked();
             self.pending_proposals.clear();
+            self.pending_size = 0;
         }
         if self.pid == n.pid {
+            if self.internal_storage.is_witness() {
+                // a witness does not store the log and can therefore never lead
+                return;
+            }
             self.leader_state = LeaderState::with(
                 n,
                 None,
                 self.leader_state.max_pid,
                 self.leader_state.majority,
+                std::mem::take(&mut self.leader_state.witnesses),
             );
-            self.leader = n;
+            self.set_leader(n);
             self.internal_storage.set_promise(n);
             /* insert my promise */
             let na = self.internal_storage.get_accepted_round();
@@ -58,11 +73,24 @@ where
             for pid in &self.peers {
                 self.outgoing.push(PaxosMessage {
                     from: self.pid,
+                    cluster_id: self.cluster_id,
                     to: *pid,
                     msg: PaxosMsg::Prepare(prep),
                 });
             }
         } else {
+            self.demote(n);
+            self.state.0 = Role::Follower;
+        }
+    }
+
+    /// Steps down if a follower has elected a leader with a greater ballot than the one this server is preparing with.
+    pub(crate) fn handle_prepare_nack(&mut self, n: Ballot) {
+        if self.state == (Role::Leader, Phase::Prepare) && n > self.leader_state.n_leader {
+            #[cfg(feature = "logging")]
+            debug!(self.logger, "Prepare rejected in favour of leader: {:?}", n);
+            self.demote(n);
+            self.set_leader(n);
             self.state.0 = Role::Follower;
         }
     }
@@ -87,12 +115,62 @@ where
             };
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: from,
                 msg: PaxosMsg::Prepare(prep),
             });
         }
     }
 
+    /// Resend `Prepare` to the peers that have not promised yet, e.g. if the `Prepare` or `Promise` was lost.
+    pub(crate) fn resend_prepare(&mut self) {
+        let unpromised: Vec<NodeId> = self
+            .peers
+            .iter()
+            .filter(|pid| !self.leader_state.is_promised(**pid))
+            .copied()
+            .collect();
+        if unpromised.is_empty() {
+            return;
+        }
+        #[cfg(feature = "logging")]
+        trace!(self.logger, "Resending Prepare to {:?}", unpromised);
+        let prep = Prepare {
+            n: self.leader_state.n_leader,
+            decided_idx: self.internal_storage.get_decided_idx(),
+            n_accepted: self.internal_storage.get_accepted_round(),
+            accepted_idx: self.internal_storage.get_log_len(),
+        };
+        for pid in unpromised {
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: pid,
+                msg: PaxosMsg::Prepare(prep),
+            });
+        }
+    }
+
+    /// Resynchronize the promised followers that have not accepted all entries that were in the log at the previous resend, e.g. if an `AcceptDecide` or `Accepted` was lost.
+    /// The follower is synchronized with a new `Prepare`, as if it had sent a `PrepareReq`.
+    pub(crate) fn resend_accepts(&mut self) {
+        let resend_idx = self.leader_state.get_resend_idx();
+        for pid in self.leader_state.get_promised_followers() {
+            if self.leader_state.get_accepted_idx(pid) < resend_idx {
+                #[cfg(feature = "logging")]
+                trace!(
+                    self.logger,
+                    "Follower {} has not accepted up to {}, resynchronizing",
+                    pid,
+                    resend_idx
+                );
+                self.handle_preparereq(pid);
+            }
+        }
+        self.leader_state
+            .set_resend_idx(self.internal_storage.get_log_len());
+    }
+
     pub(crate) fn forward_proposals(&mut self, mut entries: Vec<T>) {
         if self.leader.pid > 0 && self.leader.pid != self.pid {
             #[cfg(feature = "logging")]
@@ -104,12 +182,13 @@ where
             let pf = PaxosMsg::ProposalForward(entries);
             let msg = PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: self.leader.pid,
                 msg: pf,
             };
             self.outgoing.push(msg);
         } else {
-            self.pending_proposals.append(&mut entries);
+            self.buffer_proposals(&mut entries);
         }
     }
 
@@ -124,6 +203,7 @@ where
             let fs = PaxosMsg::ForwardStopSign(ss);
             let msg = PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: self.leader.pid,
                 msg: fs,
             };
@@ -133,10 +213,27 @@ where
         }
     }
 
-    pub(crate) fn handle_forwarded_proposal(&mut self, mut entries: Vec<T>) {
-        if !self.stopped() {
+    pub(crate) fn handle_forwarded_proposal(&mut self, mut entries: Vec<T>, from: NodeId) {
+        if self.stopped() {
+            return;
+        }
+        let is_leader = self.state.0 == Role::Leader;
+        let window_full = match self.inflight_available() {
+            Some(available) => available < entries.len() as u64,
+            None => false,
+        };
+        if is_leader && (window_full || self.check_capacity(&entries).is_err()) {
+            // the proposals are returned to the follower that forwarded them
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: from,
+                msg: PaxosMsg::ProposalRejected(entries),
+            });
+        } else {
             match self.state {
-                (Role::Leader, Phase::Prepare) => self.pending_proposals.append(&mut entries),
+                (Role::Leader, _) if self.is_handing_over() => self.buffer_proposals(&mut entries),
+                (Role::Leader, Phase::Prepare) => self.buffer_proposals(&mut entries),
                 (Role::Leader, Phase::Accept) => self.send_batch_accept(entries),
                 (Role::Leader, Phase::FirstAccept) => {
                     self.send_first_accept();
@@ -147,6 +244,18 @@ where
         }
     }
 
+    pub(crate) fn handle_applied(&mut self, idx: u64, from: NodeId) {
+        if self.state.0 == Role::Leader && self.peers.contains(&from) {
+            self.leader_state.set_applied_idx(from, idx);
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: from,
+                msg: PaxosMsg::AppliedAck(idx),
+            });
+        }
+    }
+
     pub(crate) fn handle_forwarded_stopsign(&mut self, ss: StopSign) {
         if !self.stopped() {
             match self.state {
@@ -178,6 +287,7 @@ where
         for pid in self.leader_state.get_promised_followers() {
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: pid,
                 msg: PaxosMsg::FirstAccept(f.clone()),
             });
@@ -186,14 +296,16 @@ where
     }
 
     #[cfg(feature = "batch_accept")]
-    fn send_accept_and_cache(&mut self, to: NodeId, entries: Vec<T>) {
+    fn send_accept_and_cache(&mut self, to: NodeId, start_idx: u64, entries: Vec<T>) {
         let acc = AcceptDecide {
             n: self.leader_state.n_leader,
             decided_idx: self.leader_state.get_chosen_idx(),
+            start_idx,
             entries,
         };
         self.outgoing.push(PaxosMessage {
             from: self.pid,
+            cluster_id: self.cluster_id,
             to,
             msg: PaxosMsg::AcceptDecide(acc),
         });
@@ -201,9 +313,28 @@ where
             .set_batch_accept_meta(to, Some(self.outgoing.len() - 1));
     }
 
+    /// Resynchronizes the promised followers that have more than `max_follower_backlog` unacknowledged entries, as if they had sent a `PrepareReq`.
+    /// No more entries are sent to such a follower until it promises again, which bounds the entries buffered for a follower that stopped responding.
+    fn resync_backlogged_followers(&mut self) {
+        if let Some(max) = self.max_follower_backlog {
+            for pid in self.leader_state.get_promised_followers() {
+                if self.follower_backlog(pid) as u64 > max {
+                    #[cfg(feature = "logging")]
+                    debug!(
+                        self.logger,
+                        "Follower {} exceeded the backlog of {} entries, resynchronizing", pid, max
+                    );
+                    self.handle_preparereq(pid);
+                }
+            }
+        }
+    }
+
     pub(crate) fn send_accept(&mut self, entry: T) {
         let accepted_idx = self.internal_storage.append_entry(entry.clone());
         self.leader_state.set_accepted_idx(self.pid, accepted_idx);
+        self.resync_backlogged_followers();
+        let start_idx = accepted_idx - 1;
         for pid in self.leader_state.get_promised_followers() {
             if cfg!(feature = "batch_accept") {
                 #[cfg(feature = "batch_accept")]
@@ -212,19 +343,21 @@ where
                         let PaxosMessage { msg, .. } = self.outgoing.get_mut(outgoing_idx).unwrap();
                         match msg {
                             PaxosMsg::AcceptDecide(a) => a.entries.push(entry.clone()),
-                            _ => self.send_accept_and_cache(pid, vec![entry.clone()]),
+                            _ => self.send_accept_and_cache(pid, start_idx, vec![entry.clone()]),
                         }
                     }
-                    _ => self.send_accept_and_cache(pid, vec![entry.clone()]),
+                    _ => self.send_accept_and_cache(pid, start_idx, vec![entry.clone()]),
                 }
             } else {
                 let acc = AcceptDecide {
                     n: self.leader_state.n_leader,
                     decided_idx: self.leader_state.get_chosen_idx(),
+                    start_idx,
                     entries: vec![entry.clone()],
                 };
                 self.outgoing.push(PaxosMessage {
                     from: self.pid,
+                    cluster_id: self.cluster_id,
                     to: pid,
                     msg: PaxosMsg::AcceptDecide(acc),
                 });
@@ -235,6 +368,8 @@ where
     fn send_batch_accept(&mut self, entries: Vec<T>) {
         let accepted_idx = self.internal_storage.append_entries(entries.clone());
         self.leader_state.set_accepted_idx(self.pid, accepted_idx);
+        self.resync_backlogged_followers();
+        let start_idx = accepted_idx - entries.len() as u64;
         for pid in self.leader_state.get_promised_followers() {
             if cfg!(feature = "batch_accept") {
                 #[cfg(feature = "batch_accept")]
@@ -243,19 +378,21 @@ where
                         let PaxosMessage { msg, .. } = self.outgoing.get_mut(outgoing_idx).unwrap();
                         match msg {
                             PaxosMsg::AcceptDecide(a) => a.entries.append(entries.clone().as_mut()),
-                            _ => self.send_accept_and_cache(pid, entries.clone()),
+                            _ => self.send_accept_and_cache(pid, start_idx, entries.clone()),
                         }
                     }
-                    _ => self.send_accept_and_cache(pid, entries.clone()),
+                    _ => self.send_accept_and_cache(pid, start_idx, entries.clone()),
                 }
             } else {
                 let acc = AcceptDecide {
                     n: self.leader_state.n_leader,
                     decided_idx: self.leader_state.get_chosen_idx(),
+                    start_idx,
                     entries: entries.clone(),
                 };
                 self.outgoing.push(PaxosMessage {
                     from: self.pid,
+                    cluster_id: self.cluster_id,
                     to: pid,
                     msg: PaxosMsg::AcceptDecide(acc),
                 });
@@ -280,8 +417,9 @@ where
             .leader_state
             .get_decided_idx(*pid)
             .expect("Received PromiseMetaData but not found in ld");
+        // a follower that accepted in the round of the adopted log already holds its prefix, so it is only synced from where its log ends
         let (delta_snapshot, suffix, sync_idx) =
-            if (promise_n == max_promise_n) && (promise_accepted_idx < max_accepted_idx) {
+            if (promise_n == max_promise_n) && (promise_accepted_idx <= max_accepted_idx) {
                 if self.internal_storage.get_compacted_idx() > *promise_accepted_idx
                     && Self::use_snapshots()
                 {
@@ -316,10 +454,13 @@ where
         };
         let msg = PaxosMessage {
             from: self.pid,
+            cluster_id: self.cluster_id,
             to: *pid,
             msg: PaxosMsg::AcceptSync(acc_sync),
         };
         self.outgoing.push(msg);
+        self.leader_state
+            .set_sync_idx(to, self.internal_storage.get_log_len());
     }
 
     fn adopt_pending_stopsign(&mut self) {
@@ -330,10 +471,14 @@ where
 
     fn append_pending_proposals(&mut self) {
         if !self.pending_proposals.is_empty() {
-            let new_entries = std::mem::take(&mut self.pending_proposals);
+            let new_entries = self.take_pending_proposals();
+            let log_len = self.internal_storage.get_log_len();
             // append new proposals in my sequence
             let accepted_idx = self.internal_storage.append_entries(new_entries);
             self.leader_state.set_accepted_idx(self.pid, accepted_idx);
+            for (id, pending_idx, appended_at) in std::mem::take(&mut self.pending_tracked) {
+                self.track_proposal(id, log_len + pending_idx as u64, appended_at);
+            }
         }
     }
 
@@ -357,16 +502,9 @@ where
                             .leader_state
                             .get_decided_idx(max_promise_meta.pid)
                             .unwrap();
-                        match s {
-                            SnapshotType::Complete(c) => {
-                                self.internal_storage.set_snapshot(decided_idx, c);
-                            }
-                            SnapshotType::Delta(d) => {
-                                self.internal_storage.merge_snapshot(decided_idx, d);
-                            }
-                            _ => unimplemented!(),
-                        }
+                        self.install_snapshot(decided_idx, s);
                         self.internal_storage.append_entries(suffix);
+                        self.notify_snapshot_installed();
                         if let Some(ss) = max_stopsign {
                             self.accept_stopsign(ss);
                         } else {
@@ -398,7 +536,7 @@ where
         }
         self.internal_storage
             .set_accepted_round(self.leader_state.n_leader);
-        self.internal_storage.set_decided_idx(decided_idx);
+        self.set_decided_idx(decided_idx);
         for pid in self.leader_state.get_promised_followers() {
             self.send_accsync(pid);
         }
@@ -467,6 +605,7 @@ where
                                     _ => {
                                         self.outgoing.push(PaxosMessage {
                                             from: self.pid,
+                                            cluster_id: self.cluster_id,
                                             to: pid,
                                             msg: PaxosMsg::Decide(d),
                                         });
@@ -476,6 +615,7 @@ where
                             _ => {
                                 self.outgoing.push(PaxosMessage {
                                     from: self.pid,
+                                    cluster_id: self.cluster_id,
                                     to: pid,
                                     msg: PaxosMsg::Decide(d),
                                 });
@@ -484,12 +624,14 @@ where
                     } else {
                         self.outgoing.push(PaxosMessage {
                             from: self.pid,
+                            cluster_id: self.cluster_id,
                             to: pid,
                             msg: PaxosMsg::Decide(d),
                         });
                     }
                 }
                 self.handle_decide(d);
+                self.update_tracked_proposals();
             }
         }
     }
@@ -511,6 +653,7 @@ where
                 for pid in self.leader_state.get_promised_followers() {
                     self.outgoing.push(PaxosMessage {
                         from: self.pid,
+                        cluster_id: self.cluster_id,
                         to: pid,
                         msg: PaxosMsg::DecideStopSign(d),
                     });
@@ -1,19 +1,26 @@
 use super::{
     ballot_leader_election::Ballot,
     messages::sequence_paxos::*,
-    storage::{Entry, Snapshot, StopSign, StopSignEntry, Storage},
-    util::{defaults::BUFFER_SIZE, LeaderState},
+    storage::{Entry, Snapshot, SnapshotType, StopSign, StopSignEntry, Storage, StorageErr},
+    util::LeaderState,
 };
 #[cfg(feature = "logging")]
 use crate::utils::logger::create_logger;
 use crate::{
-    omni_paxos::{CompactionErr, OmniPaxosConfig, ProposeErr, ReconfigurationRequest},
+    omni_paxos::{
+        BarrierStatus, BootstrapErr, CompactionErr, ConsensusEvent, HandleErr, LeaderHint,
+        OmniPaxosConfig, ProposalFailure, ProposeErr, ReconfigurationRequest, RejectionContext,
+        SnapshotTrigger, TransferErr,
+    },
     storage::InternalStorage,
-    util::{ConfigurationId, NodeId},
+    util::{
+        ClusterId, ConfigurationId, DecideBatchCallback, DecideCallback, EntrySizer, NodeId,
+        ProposalId, COMMIT_LATENCY_BUCKETS,
+    },
 };
 #[cfg(feature = "logging")]
-use slog::{debug, info, trace, Logger};
-use std::{fmt::Debug, marker::PhantomData, vec};
+use slog::{debug, info, trace, warn, Logger};
+use std::{collections::VecDeque, fmt::Debug, marker::PhantomData, vec};
 
 pub mod follower;
 pub mod leader;
@@ -29,16 +36,63 @@ where
 {
     pub(crate) internal_storage: InternalStorage<B, T, S>,
     config_id: ConfigurationId,
+    cluster_id: ClusterId,
     pid: NodeId,
     peers: Vec<u64>, // excluding self pid
     state: (Role, Phase),
     leader: Ballot,
+    ble_leader: Ballot, // latest leader elected by the leader election
     pending_proposals: Vec<T>,
+    pending_size: usize, // total size of the pending proposals as returned by `Storage::entries_size()`
     pending_stopsign: Option<StopSign>,
+    next_proposal_id: ProposalId,
+    pending_tracked: Vec<(ProposalId, usize, u64)>, // (id, index in pending_proposals, appended at tick)
+    tracked_proposals: Vec<(ProposalId, u64, Ballot, u64)>, // (id, log index, round, appended at tick)
+    completed_proposals: Vec<(ProposalId, u64)>,
+    failed_proposals: Vec<(ProposalId, ProposalFailure)>,
+    last_rejection: Option<RejectionContext>, // the state at the last rejection with `ProposeErr::NotLeader`
+    rejected_proposals: Vec<T>, // forwarded proposals that the leader rejected as its storage is full
+    proposal_ttl: Option<u64>,
+    snapshot_trigger: SnapshotTrigger,
+    follower_timeout: Option<u64>,
+    max_follower_backlog: Option<u64>,
+    reconfiguration_timeout: Option<u64>, // only set for a new configuration, cleared once a leader is known or the stall has been reported
+    stopsign_timeout: Option<u64>, // cleared once the new configuration is confirmed or the stall has been reported
+    stopsign_decided_at: Option<u64>, // tick at which the decided StopSign was first observed
+    prepare_timeout: Option<u64>,
+    prepare_started_at: Option<(Ballot, u64)>, // the ballot of the current Prepare phase of this leader and the tick at which it was first observed
+    ticks: u64,
+    leader_since: u64, // tick at which the current leader was adopted
+    storage_error: Option<StorageErr>, // the error of the latest capacity check of the storage
+    last_decide_tick: u64, // tick at which the decided index last increased
+    on_demotion: Option<Box<dyn Fn(Ballot) + Send + Sync>>,
+    on_snapshot_installed: Option<Box<dyn Fn(u64) + Send + Sync>>,
+    on_decide: Option<DecideCallback<T>>,
+    on_decide_batch: Option<DecideBatchCallback<T>>,
+    coalesce_decides: Option<usize>,
+    notified_decided_idx: u64, // entries before this index have been passed to the decide callbacks
+    events: VecDeque<ConsensusEvent>,
+    event_buffer_size: usize,
+    max_entry_bytes: Option<usize>,
+    max_inflight_entries: Option<u64>,
+    entry_sizer: Option<EntrySizer<T>>,
+    latency_marks: VecDeque<(u64, u64, u64)>, // (from idx, to idx, tick) of entries added to the log that are not decided yet
+    marked_log_len: u64,
+    commit_latency: [u64; COMMIT_LATENCY_BUCKETS],
+    foreign_messages_dropped: u64,
+    applied_idx: u64, // index up to which the application has applied the decided entries
+    reported_applied: Option<(Ballot, u64, u64)>, // (leader, applied idx, tick) of the latest report to the leader
+    acked_applied: Option<(Ballot, u64)>, // (leader, applied idx) of the latest report that the leader acknowledged
+    transfer_target: Option<NodeId>,
+    handover: Option<(NodeId, u64)>, // (target, tick) of the latest TimeoutNow of a leadership transfer
     outgoing: Vec<PaxosMessage<T, S>>,
     leader_state: LeaderState<T, S>,
     latest_accepted_meta: Option<(Ballot, usize)>,
     buffer_size: usize,
+    resend_timeout: u64,
+    ticks_since_resend: u64,
+    leader_decided_idx: u64, // highest decided index received from the leader
+    catchup_check_idx: Option<u64>, // decided index at the previous catch-up check
     s: PhantomData<S>,
     #[cfg(feature = "logging")]
     logger: Logger,
@@ -79,23 +133,87 @@ where
             None => {
                 let state = (Role::Follower, Phase::None);
                 let lds = None;
-                (state, Ballot::default(), lds)
+                // a persisted leader of another node is adopted until it sends a `Prepare`, e.g. to forward proposals to it
+                let leader = config
+                    .persisted_leader
+                    .filter(|l| l.pid != pid)
+                    .unwrap_or_default();
+                (state, leader, lds)
             }
         };
 
+        let witness = config.witnesses.contains(&pid);
+        // without `batch_accept`, the leader sends a message per peer and in-flight proposal
+        let outgoing_capacity = config
+            .buffer_size
+            .max(config.expected_peers.max(peers.len()) * config.expected_inflight);
         let mut paxos = SequencePaxos {
-            internal_storage: InternalStorage::with(storage),
+            internal_storage: InternalStorage::with(storage, witness),
             config_id,
+            cluster_id: config.cluster_id,
             pid,
             peers,
             state,
-            pending_proposals: vec![],
+            pending_proposals: Vec::with_capacity(config.expected_inflight),
+            pending_size: 0,
             pending_stopsign: None,
+            next_proposal_id: 0,
+            pending_tracked: Vec::with_capacity(config.expected_inflight),
+            tracked_proposals: Vec::with_capacity(config.expected_inflight),
+            completed_proposals: vec![],
+            failed_proposals: vec![],
+            last_rejection: None,
+            rejected_proposals: vec![],
+            proposal_ttl: config.proposal_ttl,
+            snapshot_trigger: config.snapshot_trigger,
+            follower_timeout: config.follower_timeout,
+            max_follower_backlog: config.max_follower_backlog,
+            // the initial configuration does not follow a decided `StopSign`, so a slow election is not a stalled reconfiguration
+            reconfiguration_timeout: config.reconfiguration_timeout.filter(|_| config_id > 1),
+            stopsign_timeout: config.reconfiguration_timeout,
+            stopsign_decided_at: None,
+            prepare_timeout: config.prepare_timeout,
+            prepare_started_at: None,
+            ticks: 0,
+            leader_since: 0,
+            storage_error: None,
+            last_decide_tick: 0,
+            on_demotion: None,
+            on_snapshot_installed: None,
+            on_decide: None,
+            on_decide_batch: None,
+            coalesce_decides: config.coalesce_decides,
+            notified_decided_idx: 0,
+            events: VecDeque::new(),
+            event_buffer_size: config.event_buffer_size,
+            max_entry_bytes: config.max_entry_bytes,
+            max_inflight_entries: config.max_inflight_entries,
+            entry_sizer: None,
+            latency_marks: VecDeque::with_capacity(config.expected_inflight),
+            marked_log_len: 0,
+            commit_latency: [0; COMMIT_LATENCY_BUCKETS],
+            foreign_messages_dropped: 0,
+            applied_idx: 0,
+            reported_applied: None,
+            acked_applied: None,
+            transfer_target: None,
+            handover: None,
             leader,
-            outgoing: Vec::with_capacity(BUFFER_SIZE),
-            leader_state: LeaderState::<T, S>::with(leader, lds, max_pid, majority),
+            ble_leader: leader,
+            outgoing: Vec::with_capacity(outgoing_capacity),
+            leader_state: LeaderState::<T, S>::with(
+                leader,
+                lds,
+                max_pid,
+                majority,
+                config.witnesses,
+            ),
             latest_accepted_meta: None,
             buffer_size: config.buffer_size,
+            resend_timeout: config.resend_timeout,
+            ticks_since_resend: 0,
+            leader_decided_idx: 0,
+            catchup_check_idx: None,
             s: PhantomData,
             #[cfg(feature = "logging")]
             logger: {
@@ -106,6 +224,7 @@ where
             },
         };
         paxos.internal_storage.set_promise(leader);
+        paxos.marked_log_len = paxos.internal_storage.get_log_len();
         #[cfg(feature = "logging")]
         {
             info!(paxos.logger, "Paxos component pid: {} created!", pid);
@@ -135,12 +254,15 @@ where
                         return Err(CompactionErr::NotAllDecided(*min_all_accepted_idx));
                     }
                 };
+                let compacted_idx = self.get_compacted_idx();
                 let result = self.internal_storage.try_trim(trimmed_idx);
+                self.record_compaction(compacted_idx);
                 if result.is_ok() {
                     for pid in &self.peers {
                         let msg = PaxosMsg::Compaction(Compaction::Trim(trimmed_idx));
                         self.outgoing.push(PaxosMessage {
                             from: self.pid,
+                            cluster_id: self.cluster_id,
                             to: *pid,
                             msg,
                         });
@@ -148,10 +270,61 @@ where
                 }
                 result
             }
-            _ => Err(CompactionErr::NotCurrentLeader(self.leader.pid)),
+            _ => Err(CompactionErr::NotCurrentLeader(self.get_leader_hint())),
         }
     }
 
+    /// Initializes the empty storage of a new server with a snapshot of the decided log up to `compacted_idx`, see `OmniPaxos::bootstrap_from_snapshot()`.
+    pub(crate) fn bootstrap_from_snapshot(
+        &mut self,
+        snapshot: S,
+        compacted_idx: u64,
+        decided_idx: u64,
+    ) -> Result<(), BootstrapErr> {
+        if !Self::use_snapshots() {
+            return Err(BootstrapErr::SnapshotsNotUsed);
+        }
+        // a promised round, e.g. of an `initial_leader`, is kept as it does not conflict with decided entries
+        let has_state = self.internal_storage.get_log_len() > 0
+            || self.get_decided_idx() > 0
+            || self.get_compacted_idx() > 0;
+        if has_state {
+            return Err(BootstrapErr::NotEmpty);
+        }
+        if decided_idx != compacted_idx {
+            return Err(BootstrapErr::InvalidDecidedIdx(decided_idx));
+        }
+        self.internal_storage.set_snapshot(compacted_idx, snapshot);
+        self.internal_storage.set_decided_idx(decided_idx);
+        // the entries of the snapshot are neither measured nor passed to `on_decide`
+        self.marked_log_len = compacted_idx;
+        self.notified_decided_idx = compacted_idx;
+        Ok(())
+    }
+
+    /// Compacts the log on all servers so that only the last `keep_last` decided entries are kept. Returns the number of entries compacted at this server.
+    /// Without snapshots, the log is only trimmed up to the index accepted by all servers, so that no server loses entries it still needs to catch up.
+    pub(crate) fn emergency_trim(&mut self, keep_last: u64) -> Result<u64, CompactionErr> {
+        if self.state.0 != Role::Leader {
+            return Err(CompactionErr::NotCurrentLeader(self.get_leader_hint()));
+        }
+        let compacted_idx = self.get_compacted_idx();
+        let mut idx = self.get_decided_idx().saturating_sub(keep_last);
+        if !Self::use_snapshots() {
+            idx = idx.min(*self.leader_state.get_min_all_accepted_idx());
+        }
+        if idx <= compacted_idx {
+            return Ok(0);
+        }
+        if Self::use_snapshots() {
+            // lagging followers are synchronized with a snapshot instead
+            self.snapshot(Some(idx), false)?;
+        } else {
+            self.trim(Some(idx))?;
+        }
+        Ok(self.get_compacted_idx() - compacted_idx)
+    }
+
     /// Trim the log and create a snapshot. ** Note: only up to the `decided_idx` can be snapshotted **
     /// # Arguments
     /// `idx` - Snapshots all entries with index < [`idx`], if the [`idx`] is None then the decided index will be used.
@@ -161,13 +334,16 @@ where
         idx: Option<u64>,
         local_only: bool,
     ) -> Result<(), CompactionErr> {
+        let compacted_idx = self.get_compacted_idx();
         let result = self.internal_storage.try_snapshot(idx);
+        self.record_compaction(compacted_idx);
         if !local_only && result.is_ok() {
             // since it is decided, it is ok even for a follower to send this
             for pid in &self.peers {
                 let msg = PaxosMsg::Compaction(Compaction::Snapshot(idx));
                 self.outgoing.push(PaxosMessage {
                     from: self.pid,
+                    cluster_id: self.cluster_id,
                     to: *pid,
                     msg,
                 });
@@ -176,6 +352,183 @@ where
         result
     }
 
+    /// Snapshots the decided log on all servers if the `snapshot_trigger` condition is met.
+    /// The log is only snapshotted up to `min_replicated_idx()`, so that no live follower has to be synchronized with a snapshot.
+    fn trigger_snapshot(&mut self) {
+        if let SnapshotTrigger::LogLength(n) = self.snapshot_trigger {
+            if Self::use_snapshots() && self.get_decided_idx() - self.get_compacted_idx() >= n {
+                let idx = self.get_decided_idx().min(self.min_replicated_idx());
+                if idx <= self.get_compacted_idx() {
+                    return;
+                }
+                #[cfg(feature = "logging")]
+                debug!(self.logger, "Triggering snapshot at index {}", idx);
+                let _ = self.snapshot(Some(idx), false);
+            }
+        }
+    }
+
+    /// Returns the minimum accepted index of this leader and its live followers, see `OmniPaxos::min_replicated_idx()`. Returns `0` if this server is not the leader.
+    pub(crate) fn min_replicated_idx(&self) -> u64 {
+        if self.state.0 != Role::Leader {
+            return 0;
+        }
+        self.peers
+            .iter()
+            .filter(|pid| !self.is_silent(**pid))
+            .map(|pid| self.leader_state.get_accepted_idx(*pid))
+            .fold(self.leader_state.get_accepted_idx(self.pid), u64::min)
+    }
+
+    /// Returns the number of entries that were sent to the follower `pid` since its latest synchronization and that it has not accepted yet, see `OmniPaxos::follower_backlog()`.
+    pub(crate) fn follower_backlog(&self, pid: NodeId) -> usize {
+        if self.state.0 != Role::Leader
+            || !self.peers.contains(&pid)
+            || !self.leader_state.is_promised(pid)
+        {
+            return 0;
+        }
+        let acked_idx = std::cmp::max(
+            self.leader_state.get_accepted_idx(pid),
+            self.leader_state.get_sync_idx(pid),
+        );
+        self.internal_storage
+            .get_log_len()
+            .saturating_sub(acked_idx) as usize
+    }
+
+    /// Returns whether the leader has not heard from the follower `pid` for more than `follower_timeout` ticks.
+    /// A follower that has not been heard from at all is counted from the tick at which this server became the leader.
+    fn is_silent(&self, pid: NodeId) -> bool {
+        match self.follower_timeout {
+            Some(timeout) => {
+                let last_heard = self
+                    .leader_state
+                    .get_last_heard(pid)
+                    .unwrap_or(self.leader_since);
+                self.ticks.saturating_sub(last_heard) > timeout
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the index up to which the application has applied the decided entries. The index is capped at the decided index and never decreases.
+    pub(crate) fn set_applied_idx(&mut self, idx: u64) {
+        self.applied_idx = self.applied_idx.max(idx.min(self.get_decided_idx()));
+    }
+
+    /// Returns the index up to which the application has applied the decided entries.
+    pub(crate) fn get_applied_idx(&self) -> u64 {
+        self.applied_idx
+    }
+
+    /// Reports the applied index to the leader if it has changed since the previous report or the leader has changed.
+    /// A report is resent every `resend_timeout` ticks until the leader acknowledges it, so a lost report does not block a barrier.
+    fn report_applied(&mut self) {
+        if self.state.0 == Role::Leader
+            || self.leader == Ballot::default()
+            || self.leader.pid == self.pid
+        {
+            return;
+        }
+        let report = (self.leader, self.applied_idx);
+        if self.acked_applied == Some(report) {
+            return;
+        }
+        let resend = match self.reported_applied {
+            Some((n, idx, sent_at)) if (n, idx) == report => {
+                self.ticks.saturating_sub(sent_at) >= self.resend_timeout
+            }
+            _ => true,
+        };
+        if resend {
+            self.reported_applied = Some((self.leader, self.applied_idx, self.ticks));
+            self.outgoing.push(PaxosMessage {
+                from: self.pid,
+                cluster_id: self.cluster_id,
+                to: self.leader.pid,
+                msg: PaxosMsg::Applied(self.applied_idx),
+            });
+        }
+    }
+
+    /// Returns the status of a barrier at `idx`, see `OmniPaxos::poll_barrier()`.
+    pub(crate) fn barrier_status(&self, idx: u64) -> BarrierStatus {
+        if self.state.0 != Role::Leader {
+            return BarrierStatus::NotLeader;
+        }
+        let mut waiting: Vec<NodeId> = self
+            .peers
+            .iter()
+            .filter(|pid| !self.leader_state.witnesses.contains(pid) && !self.is_silent(**pid))
+            .filter(|pid| self.leader_state.get_applied_idx(**pid) < idx)
+            .cloned()
+            .collect();
+        if self.applied_idx < idx {
+            waiting.push(self.pid);
+        }
+        if waiting.is_empty() {
+            BarrierStatus::Complete
+        } else {
+            waiting.sort_unstable();
+            BarrierStatus::Pending(waiting)
+        }
+    }
+
+    /// Starts transferring the leadership to `pid` once it has caught up, see `OmniPaxos::transfer_leadership_to()`.
+    pub(crate) fn transfer_leadership_to(&mut self, pid: NodeId) -> Result<(), TransferErr> {
+        if self.state.0 != Role::Leader {
+            return Err(TransferErr::NotLeader(self.get_leader_hint()));
+        }
+        if pid == self.pid {
+            self.transfer_target = None;
+            return Ok(());
+        }
+        if !self.peers.contains(&pid) || self.leader_state.witnesses.contains(&pid) {
+            return Err(TransferErr::InvalidTarget(pid));
+        }
+        self.transfer_target = Some(pid);
+        Ok(())
+    }
+
+    /// Returns the target of the pending leadership transfer, if this server is still the leader.
+    pub(crate) fn pending_transfer(&self) -> Option<NodeId> {
+        self.transfer_target
+            .filter(|_| self.state.0 == Role::Leader)
+    }
+
+    /// Returns the target of the leadership transfer to send a `TimeoutNow` to, once it has accepted the whole log of this leader and then every `resend_timeout` ticks
+    /// until it takes over. Abandons the transfer if this server is no longer the leader.
+    pub(crate) fn take_transfer_timeout_now(&mut self) -> Option<NodeId> {
+        if self.state.0 != Role::Leader {
+            self.transfer_target = None;
+            self.handover = None;
+            return None;
+        }
+        if let Some((pid, sent_at)) = self.handover {
+            if self.ticks.saturating_sub(sent_at) < self.resend_timeout {
+                return None;
+            }
+            self.handover = Some((pid, self.ticks));
+            return Some(pid);
+        }
+        let pid = self.transfer_target?;
+        let caught_up = self.state.1 == Phase::Accept
+            && self.leader_state.get_accepted_idx(pid) >= self.internal_storage.get_log_len();
+        if caught_up {
+            self.transfer_target = None;
+            self.handover = Some((pid, self.ticks));
+            Some(pid)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether this leader has sent a `TimeoutNow` to the target of a leadership transfer. New entries are then held back and forwarded to the target once it takes over.
+    pub(crate) fn is_handing_over(&self) -> bool {
+        self.handover.is_some() && self.state.0 == Role::Leader
+    }
+
     /// Return the decided index.
     pub(crate) fn get_decided_idx(&self) -> u64 {
         self.internal_storage.get_decided_idx()
@@ -186,12 +539,251 @@ where
         self.internal_storage.get_compacted_idx()
     }
 
+    /// Sets a callback that is called with the greater ballot when this server is demoted from leader to follower.
+    pub(crate) fn on_demotion(&mut self, f: Box<dyn Fn(Ballot) + Send + Sync>) {
+        self.on_demotion = Some(f);
+    }
+
+    /// Notifies the demotion callback if this server is the leader and is about to become a follower of the greater ballot `n`.
+    pub(crate) fn demote(&mut self, n: Ballot) {
+        if self.state.0 == Role::Leader && n.pid != self.pid {
+            #[cfg(feature = "logging")]
+            info!(
+                self.logger,
+                "Demoted from leader {:?} by {:?}", self.leader_state.n_leader, n
+            );
+            if let Some(f) = &self.on_demotion {
+                f(n);
+            }
+            self.record_event(ConsensusEvent::Demoted {
+                tick: self.ticks,
+                ballot: n,
+            });
+        }
+    }
+
+    /// Sets a callback that is called with the compacted index when a snapshot received from a peer has been installed.
+    pub(crate) fn on_snapshot_installed(&mut self, f: Box<dyn Fn(u64) + Send + Sync>) {
+        self.on_snapshot_installed = Some(f);
+    }
+
+    /// Installs a snapshot of the decided log up to `idx` that was received from a peer.
+    fn install_snapshot(&mut self, idx: u64, snapshot: SnapshotType<T, S>) {
+        let compacted_idx = self.get_compacted_idx();
+        match snapshot {
+            SnapshotType::Complete(c) => {
+                self.internal_storage.set_snapshot(idx, c);
+            }
+            SnapshotType::Delta(d) => {
+                self.internal_storage.merge_snapshot(idx, d);
+            }
+            _ => unimplemented!(),
+        }
+        self.record_compaction(compacted_idx);
+    }
+
+    /// Notifies the snapshot callback after a snapshot received from a peer and the entries after it have been applied to the storage.
+    fn notify_snapshot_installed(&self) {
+        if let Some(f) = &self.on_snapshot_installed {
+            f(self.internal_storage.get_compacted_idx());
+        }
+    }
+
+    /// Sets the function that is used to check the size of new proposals against `max_entry_bytes`.
+    pub(crate) fn set_entry_sizer(&mut self, f: EntrySizer<T>) {
+        self.entry_sizer = Some(f);
+    }
+
+    /// Returns the number of entries that can be appended before the log holds `max_inflight_entries` undecided entries, or `None` if it is not limited.
+    pub(crate) fn inflight_available(&self) -> Option<u64> {
+        let max = self.max_inflight_entries?;
+        let inflight = self
+            .internal_storage
+            .get_log_len()
+            .saturating_sub(self.internal_storage.get_decided_idx())
+            + self.pending_proposals.len() as u64;
+        Some(max.saturating_sub(inflight))
+    }
+
+    /// Returns the size of `entry` and the limit if `entry` exceeds `max_entry_bytes`.
+    fn exceeds_max_entry_bytes(&self, entry: &T) -> Option<(usize, usize)> {
+        let limit = self.max_entry_bytes?;
+        let size = self.entry_sizer.as_ref()?(entry);
+        if size > limit {
+            Some((size, limit))
+        } else {
+            None
+        }
+    }
+
+    /// Sets a callback that is called with the index and the entry for every entry that is decided after this call, in index order.
+    pub(crate) fn set_on_decide(&mut self, f: DecideCallback<T>) {
+        self.on_decide = Some(f);
+        self.notified_decided_idx = self.get_decided_idx();
+    }
+
+    /// Sets a callback that is called with batches of up to `coalesce_decides` consecutive entries that are decided after this call, in index order.
+    pub(crate) fn set_on_decide_batch(&mut self, f: DecideBatchCallback<T>) {
+        self.on_decide_batch = Some(f);
+        self.notified_decided_idx = self.get_decided_idx();
+    }
+
+    /// Passes the newly decided entries to the decide callbacks. Entries that were compacted before they could be passed, i.e. that were
+    /// decided as part of an installed snapshot, are skipped.
+    fn notify_decided(&mut self) {
+        if self.on_decide.is_some() || self.on_decide_batch.is_some() {
+            let from = self
+                .notified_decided_idx
+                .max(self.internal_storage.get_compacted_idx());
+            let to = self
+                .internal_storage
+                .get_decided_idx()
+                .min(self.internal_storage.get_log_len());
+            if from < to {
+                let entries = self.internal_storage.get_entries(from, to);
+                if let Some(f) = self.on_decide.as_mut() {
+                    for (idx, entry) in (from..).zip(entries.iter()) {
+                        f(idx, entry);
+                    }
+                }
+                if let Some(f) = self.on_decide_batch.as_mut() {
+                    let batch_size = self.coalesce_decides.unwrap_or(1);
+                    for (idx, batch) in (from..).step_by(batch_size).zip(entries.chunks(batch_size))
+                    {
+                        f(idx, batch);
+                    }
+                }
+                self.notified_decided_idx = to;
+            }
+        }
+    }
+
+    /// Logs a message that was dropped because its message version is not supported.
+    #[cfg(feature = "logging")]
+    pub(crate) fn log_version_mismatch(&self, e: &crate::omni_paxos::VersionMismatch) {
+        warn!(
+            self.logger,
+            "Dropped message with unsupported version {}, supported versions: {}..={}",
+            e.version,
+            e.min_version,
+            e.max_version
+        );
+    }
+
+    /// Records `event`, dropping the oldest event if the buffer is full.
+    pub(crate) fn record_event(&mut self, event: ConsensusEvent) {
+        if self.event_buffer_size == 0 {
+            return;
+        }
+        if self.events.len() >= self.event_buffer_size {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Takes the recorded events.
+    pub(crate) fn drain_events(&mut self) -> Vec<ConsensusEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Records a `Compacted` event if the compacted index advanced from `compacted_idx`.
+    fn record_compaction(&mut self, compacted_idx: u64) {
+        let new_compacted_idx = self.get_compacted_idx();
+        if new_compacted_idx > compacted_idx {
+            self.record_event(ConsensusEvent::Compacted {
+                tick: self.ticks,
+                compacted_idx: new_compacted_idx,
+            });
+        }
+    }
+
+    /// Marks the entries that were added to the log since the previous call with the current tick, to measure their commit latency.
+    /// Must be called at the end of every call that adds entries to the log.
+    fn mark_appended(&mut self) {
+        let log_len = self.internal_storage.get_log_len();
+        if log_len < self.marked_log_len {
+            // the log was overwritten by a new leader
+            self.latency_marks.retain(|(from, _, _)| *from < log_len);
+            if let Some((_, to, _)) = self.latency_marks.back_mut() {
+                *to = (*to).min(log_len);
+            }
+        } else if log_len > self.marked_log_len {
+            self.latency_marks
+                .push_back((self.marked_log_len, log_len, self.ticks));
+        }
+        self.marked_log_len = log_len;
+    }
+
+    /// Adds the commit latency of the marked entries that are decided at `decided_idx` to the histogram.
+    /// Entries that are added to the log and decided within the same call, e.g. when catching up, are not counted.
+    fn record_commit_latency(&mut self, decided_idx: u64) {
+        while let Some((from, to, tick)) = self.latency_marks.front_mut() {
+            if *from >= decided_idx {
+                break;
+            }
+            let decided = (*to).min(decided_idx) - *from;
+            let latency = self.ticks - *tick;
+            let bucket =
+                ((u64::BITS - latency.leading_zeros()) as usize).min(COMMIT_LATENCY_BUCKETS - 1);
+            self.commit_latency[bucket] += decided;
+            if *to <= decided_idx {
+                self.latency_marks.pop_front();
+            } else {
+                *from = decided_idx;
+            }
+        }
+        self.marked_log_len = self
+            .marked_log_len
+            .max(decided_idx.min(self.internal_storage.get_log_len()));
+    }
+
+    /// Returns the number of decided entries by their commit latency in ticks, see `OmniPaxos::commit_latency_histogram()`.
+    pub(crate) fn commit_latency_histogram(&self) -> [u64; COMMIT_LATENCY_BUCKETS] {
+        self.commit_latency
+    }
+
+    /// Returns the number of messages that were ignored because they were sent by another cluster.
+    pub(crate) fn foreign_messages_dropped(&self) -> u64 {
+        self.foreign_messages_dropped
+    }
+
+    /// Resets the counters that are only used for observability. The entries that are not decided yet are still measured.
+    pub(crate) fn reset_metrics(&mut self) {
+        self.commit_latency = [0; COMMIT_LATENCY_BUCKETS];
+        self.foreign_messages_dropped = 0;
+    }
+
+    /// Sets the decided index and records the `Decided` event, or `Reconfigured` if the index includes the `StopSign`.
+    fn set_decided_idx(&mut self, decided_idx: u64) {
+        let prev_decided_idx = self.get_decided_idx();
+        self.internal_storage.set_decided_idx(decided_idx);
+        if decided_idx <= prev_decided_idx {
+            return;
+        }
+        self.last_decide_tick = self.ticks;
+        self.record_commit_latency(decided_idx);
+        self.notify_decided();
+        match self.internal_storage.get_stopsign() {
+            Some(ss) if decided_idx > self.internal_storage.get_log_len() => {
+                self.record_event(ConsensusEvent::Reconfigured {
+                    tick: self.ticks,
+                    stopsign: ss.stopsign,
+                })
+            }
+            _ => self.record_event(ConsensusEvent::Decided {
+                tick: self.ticks,
+                decided_idx,
+            }),
+        }
+    }
+
     /// Recover from failure. Goes into recover state and sends `PrepareReq` to all peers.
     pub(crate) fn fail_recovery(&mut self) {
         self.state = (Role::Follower, Phase::Recover);
         for pid in &self.peers {
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: *pid,
                 msg: PaxosMsg::PrepareReq,
             });
@@ -202,7 +794,9 @@ where
         // try trimming and snapshotting forwarded compaction. Errors are ignored as that the data will still be kept.
         match c {
             Compaction::Trim(idx) => {
+                let compacted_idx = self.get_compacted_idx();
                 let _ = self.internal_storage.try_trim(idx);
+                self.record_compaction(compacted_idx);
             }
             Compaction::Snapshot(idx) => {
                 let _ = self.snapshot(idx, true);
@@ -215,6 +809,49 @@ where
         self.leader
     }
 
+    /// Adopts `n` as the current leader. Restarts the term of the leader if the ballot changed.
+    pub(crate) fn set_leader(&mut self, n: Ballot) {
+        if n != self.leader {
+            self.leader = n;
+            self.leader_since = self.ticks;
+        }
+    }
+
+    /// Returns the number of ticks since the current leader was adopted, or `None` if there is no leader.
+    pub(crate) fn ticks_as_leader(&self) -> Option<u64> {
+        if self.leader == Ballot::default() {
+            None
+        } else {
+            Some(self.ticks.saturating_sub(self.leader_since))
+        }
+    }
+
+    pub(crate) fn get_last_rejection(&self) -> Option<RejectionContext> {
+        self.last_rejection
+    }
+
+    /// Returns the current leader, or `None` if there is no known leader.
+    pub(crate) fn get_leader_hint(&self) -> Option<LeaderHint> {
+        if self.leader == Ballot::default() {
+            None
+        } else {
+            Some(LeaderHint {
+                pid: self.leader.pid,
+                ballot: self.leader,
+            })
+        }
+    }
+
+    /// Returns whether this replica is currently the leader.
+    pub(crate) fn is_leader(&self) -> bool {
+        self.state.0 == Role::Leader
+    }
+
+    /// Returns the number of outgoing messages, without taking them.
+    pub(crate) fn outgoing_len(&self) -> usize {
+        self.outgoing.len()
+    }
+
     /// Returns the outgoing messages from this replica. The messages should then be sent via the network implementation.
     pub(crate) fn get_outgoing_msgs(&mut self) -> Vec<PaxosMessage<T, S>> {
         let mut outgoing = Vec::with_capacity(self.buffer_size);
@@ -227,11 +864,144 @@ where
         outgoing
     }
 
-    /// Handle an incoming message.
-    pub(crate) fn handle(&mut self, m: PaxosMessage<T, S>) {
+    /// Returns whether there are no outgoing messages, no pending proposals and all entries in the log are decided.
+    pub(crate) fn is_quiescent(&self) -> bool {
+        self.outgoing.is_empty()
+            && self.pending_proposals.is_empty()
+            && self.pending_stopsign.is_none()
+            && self.get_decided_idx() >= self.internal_storage.get_log_len()
+    }
+
+    /// Drives the time-based behaviour of Sequence Paxos. Should be called periodically.
+    pub(crate) fn tick(&mut self) {
+        self.ticks = self.ticks.saturating_add(1);
+        self.flush_batches();
+        self.expire_tracked_proposals();
+        if self.state.0 == Role::Leader {
+            self.trigger_snapshot();
+        }
+        self.check_prepare_timeout();
+        self.ticks_since_resend = self.ticks_since_resend.saturating_add(1);
+        if self.ticks_since_resend >= self.resend_timeout {
+            self.ticks_since_resend = 0;
+            if self.state.0 == Role::Leader {
+                self.resend_prepare();
+                if self.state.1 == Phase::Accept {
+                    self.resend_accepts();
+                }
+            } else {
+                self.request_catchup();
+            }
+        }
+        self.report_applied();
+        self.check_reconfiguration_stalled();
+    }
+
+    /// Closes the batched `AcceptDecide` and `Accepted` messages, so that entries appended or accepted after this tick are sent in new messages.
+    /// A batched message therefore never holds the entries of more than one tick, even if the outgoing messages are taken less often.
+    fn flush_batches(&mut self) {
+        #[cfg(feature = "batch_accept")]
+        {
+            self.leader_state.reset_batch_accept_meta();
+        }
+        self.latest_accepted_meta = None;
+    }
+
+    /// Records `ConsensusEvent::PrepareTimedOut` once per ballot if this leader has been in the `Prepare` phase for `prepare_timeout` ticks.
+    fn check_prepare_timeout(&mut self) {
+        let timeout = match self.prepare_timeout {
+            Some(timeout) => timeout,
+            None => return,
+        };
+        if self.state != (Role::Leader, Phase::Prepare) {
+            self.prepare_started_at = None;
+            return;
+        }
+        let n = self.leader_state.n_leader;
+        let started_at = match self.prepare_started_at {
+            Some((b, started_at)) if b == n => started_at,
+            _ => {
+                self.prepare_started_at = Some((n, self.ticks));
+                self.ticks
+            }
+        };
+        if self.ticks - started_at == timeout {
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "No majority of promises for {:?} after {} ticks", n, timeout
+            );
+            self.record_event(ConsensusEvent::PrepareTimedOut {
+                tick: self.ticks,
+                ballot: n,
+            });
+        }
+    }
+
+    /// Records `ConsensusEvent::ReconfigurationStalled` once if no leader is known `reconfiguration_timeout` ticks after this replica of a new configuration, i.e. with a `config_id` greater than 1, was created,
+    /// or if the new configuration has not been confirmed `reconfiguration_timeout` ticks after the `StopSign` of this configuration was decided.
+    fn check_reconfiguration_stalled(&mut self) {
+        self.check_stopsign_stalled();
+        let timeout = match self.reconfiguration_timeout {
+            Some(timeout) => timeout,
+            None => return,
+        };
+        if self.leader != Ballot::default() {
+            self.reconfiguration_timeout = None;
+        } else if self.ticks >= timeout {
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "No leader of configuration {} after {} ticks", self.config_id, self.ticks
+            );
+            self.reconfiguration_timeout = None;
+            self.record_event(ConsensusEvent::ReconfigurationStalled {
+                tick: self.ticks,
+                configuration_id: self.config_id,
+            });
+        }
+    }
+
+    fn check_stopsign_stalled(&mut self) {
+        let (timeout, ss) = match (self.stopsign_timeout, self.is_reconfigured()) {
+            (Some(timeout), Some(ss)) => (timeout, ss),
+            _ => return,
+        };
+        let decided_at = *self.stopsign_decided_at.get_or_insert(self.ticks);
+        if self.ticks - decided_at >= timeout {
+            #[cfg(feature = "logging")]
+            warn!(
+                self.logger,
+                "Configuration {} not confirmed {} ticks after the StopSign was decided",
+                ss.config_id,
+                timeout
+            );
+            self.stopsign_timeout = None;
+            self.record_event(ConsensusEvent::ReconfigurationStalled {
+                tick: self.ticks,
+                configuration_id: ss.config_id,
+            });
+        }
+    }
+
+    /// Stops reporting the configuration of the decided `StopSign` as stalled.
+    pub(crate) fn confirm_reconfiguration(&mut self) {
+        self.stopsign_timeout = None;
+    }
+
+    /// Handle an incoming message. Returns an error if the message was ignored because it is from another cluster.
+    pub(crate) fn try_handle(&mut self, m: PaxosMessage<T, S>) -> Result<(), HandleErr> {
+        if m.cluster_id != self.cluster_id {
+            self.foreign_messages_dropped += 1;
+            return Err(HandleErr::ForeignCluster(m.cluster_id));
+        }
+        if self.state.0 == Role::Leader && self.peers.contains(&m.from) {
+            self.leader_state.set_last_heard(m.from, self.ticks);
+        }
         match m.msg {
             PaxosMsg::PrepareReq => self.handle_preparereq(m.from),
             PaxosMsg::Prepare(prep) => self.handle_prepare(prep, m.from),
+            PaxosMsg::PrepareNack(n) => self.handle_prepare_nack(n),
             PaxosMsg::Promise(prom) => match &self.state {
                 (Role::Leader, Phase::Prepare) => self.handle_promise_prepare(prom, m.from),
                 (Role::Leader, Phase::Accept) => self.handle_promise_accept(prom, m.from),
@@ -242,13 +1012,22 @@ where
             PaxosMsg::AcceptDecide(acc) => self.handle_acceptdecide(acc),
             PaxosMsg::Accepted(accepted) => self.handle_accepted(accepted, m.from),
             PaxosMsg::Decide(d) => self.handle_decide(d),
-            PaxosMsg::ProposalForward(proposals) => self.handle_forwarded_proposal(proposals),
+            PaxosMsg::ProposalForward(proposals) => {
+                self.handle_forwarded_proposal(proposals, m.from)
+            }
+            PaxosMsg::ProposalRejected(proposals) => {
+                self.handle_rejected_proposals(proposals, m.from)
+            }
             PaxosMsg::Compaction(c) => self.handle_compaction(c),
             PaxosMsg::AcceptStopSign(acc_ss) => self.handle_accept_stopsign(acc_ss),
             PaxosMsg::AcceptedStopSign(acc_ss) => self.handle_accepted_stopsign(acc_ss, m.from),
             PaxosMsg::DecideStopSign(d_ss) => self.handle_decide_stopsign(d_ss),
             PaxosMsg::ForwardStopSign(f_ss) => self.handle_forwarded_stopsign(f_ss),
+            PaxosMsg::Applied(idx) => self.handle_applied(idx, m.from),
+            PaxosMsg::AppliedAck(idx) => self.handle_applied_ack(idx, m.from),
         }
+        self.mark_appended();
+        Ok(())
     }
 
     /// Returns whether this Sequence Paxos has been reconfigured
@@ -268,12 +1047,184 @@ where
     pub(crate) fn append(&mut self, entry: T) -> Result<(), ProposeErr<T>> {
         if self.stopped() {
             Err(ProposeErr::Normal(entry))
+        } else if let Some((size, limit)) = self.exceeds_max_entry_bytes(&entry) {
+            Err(ProposeErr::TooLarge { entry, size, limit })
+        } else if self.check_capacity(std::slice::from_ref(&entry)).is_err() {
+            Err(ProposeErr::StorageFull(entry))
+        } else if self.inflight_available() == Some(0) {
+            Err(ProposeErr::WouldBlock(entry))
         } else {
             self.propose_entry(entry);
+            self.mark_appended();
             Ok(())
         }
     }
 
+    /// Append an entry to the replicated log and track whether it gets decided. Must be called at the leader.
+    pub(crate) fn append_tracked(&mut self, entry: T) -> Result<ProposalId, ProposeErr<T>> {
+        if self.stopped() {
+            return Err(ProposeErr::Normal(entry));
+        }
+        if let Some((size, limit)) = self.exceeds_max_entry_bytes(&entry) {
+            return Err(ProposeErr::TooLarge { entry, size, limit });
+        }
+        if self.check_capacity(std::slice::from_ref(&entry)).is_err() {
+            return Err(ProposeErr::StorageFull(entry));
+        }
+        if self.inflight_available() == Some(0) {
+            return Err(ProposeErr::WouldBlock(entry));
+        }
+        let id = self.next_proposal_id;
+        match self.state {
+            (Role::Leader, _) if self.is_handing_over() => {
+                self.pending_tracked
+                    .push((id, self.pending_proposals.len(), self.ticks));
+                self.buffer_proposal(entry);
+            }
+            (Role::Leader, Phase::Prepare) => {
+                self.pending_tracked
+                    .push((id, self.pending_proposals.len(), self.ticks));
+                self.buffer_proposal(entry);
+            }
+            (Role::Leader, Phase::Accept) => {
+                self.send_accept(entry);
+                self.track_proposal(id, self.internal_storage.get_log_len() - 1, self.ticks);
+            }
+            (Role::Leader, Phase::FirstAccept) => {
+                self.send_first_accept();
+                self.send_accept(entry);
+                self.track_proposal(id, self.internal_storage.get_log_len() - 1, self.ticks);
+            }
+            _ => {
+                let hint = self.get_leader_hint();
+                self.last_rejection = Some(RejectionContext {
+                    ballot: self.internal_storage.get_promise(),
+                    leader: hint.map(|h| h.pid),
+                    tick: self.ticks,
+                });
+                return Err(ProposeErr::NotLeader(entry, hint));
+            }
+        }
+        self.next_proposal_id += 1;
+        self.mark_appended();
+        Ok(id)
+    }
+
+    pub(crate) fn track_proposal(&mut self, id: ProposalId, idx: u64, appended_at: u64) {
+        self.tracked_proposals
+            .push((id, idx, self.leader_state.n_leader, appended_at));
+    }
+
+    /// Moves the tracked proposals that are decided to the completed proposals. If this replica is no longer the leader in the round a proposal was appended in, the proposal is moved to the failed proposals.
+    pub(crate) fn update_tracked_proposals(&mut self) {
+        let decided_idx = self.get_decided_idx();
+        let n_leader = self.leader_state.n_leader;
+        let is_leader = self.state.0 == Role::Leader;
+        let tracked = std::mem::take(&mut self.tracked_proposals);
+        for (id, idx, n, appended_at) in tracked {
+            if is_leader && n == n_leader {
+                if idx < decided_idx {
+                    self.completed_proposals.push((id, idx));
+                } else {
+                    self.tracked_proposals.push((id, idx, n, appended_at));
+                }
+            } else {
+                self.failed_proposals
+                    .push((id, ProposalFailure::LeaderChanged));
+            }
+        }
+    }
+
+    /// Fails the tracked proposals that have not been decided within `proposal_ttl` ticks.
+    fn expire_tracked_proposals(&mut self) {
+        if let Some(ttl) = self.proposal_ttl {
+            self.update_tracked_proposals();
+            let ticks = self.ticks;
+            let expired = |appended_at: u64| ticks.saturating_sub(appended_at) >= ttl;
+            let failed = &mut self.failed_proposals;
+            self.tracked_proposals.retain(|(id, _, _, appended_at)| {
+                if expired(*appended_at) {
+                    failed.push((*id, ProposalFailure::TimedOut));
+                }
+                !expired(*appended_at)
+            });
+            self.pending_tracked.retain(|(id, _, appended_at)| {
+                if expired(*appended_at) {
+                    failed.push((*id, ProposalFailure::TimedOut));
+                }
+                !expired(*appended_at)
+            });
+        }
+    }
+
+    /// Fails the tracked proposals that are still pending, i.e. proposals for which the log index is not known yet.
+    pub(crate) fn fail_pending_tracked(&mut self) {
+        let pending = std::mem::take(&mut self.pending_tracked);
+        self.failed_proposals.extend(
+            pending
+                .into_iter()
+                .map(|(id, _, _)| (id, ProposalFailure::LeaderChanged)),
+        );
+    }
+
+    /// Returns the tracked proposals that have been decided since the last call, together with their index in the log.
+    pub(crate) fn take_completed_proposals(&mut self) -> Vec<(ProposalId, u64)> {
+        self.update_tracked_proposals();
+        std::mem::take(&mut self.completed_proposals)
+    }
+
+    /// Returns the tracked proposals that have failed since the last call, together with the reason.
+    pub(crate) fn take_failed_proposals(&mut self) -> Vec<(ProposalId, ProposalFailure)> {
+        self.update_tracked_proposals();
+        std::mem::take(&mut self.failed_proposals)
+    }
+
+    /// Returns the forwarded proposals that the leader rejected since the last call.
+    pub(crate) fn take_rejected_proposals(&mut self) -> Vec<T> {
+        std::mem::take(&mut self.rejected_proposals)
+    }
+
+    /// Returns `StorageErr::Full` if the storage has no capacity left for `entries` in addition to the pending proposals, which are appended once this server is the leader in the accept phase.
+    pub(crate) fn check_capacity(&mut self, entries: &[T]) -> Result<(), StorageErr> {
+        let res = self.internal_storage.check_capacity(
+            entries,
+            self.pending_proposals.len(),
+            self.pending_size,
+        );
+        self.storage_error = res.err();
+        res
+    }
+
+    /// Buffers `entry` until it can be appended, keeping track of the total size of the pending proposals for `check_capacity()`.
+    fn buffer_proposal(&mut self, entry: T) {
+        self.pending_size += self
+            .internal_storage
+            .entries_size(std::slice::from_ref(&entry));
+        self.pending_proposals.push(entry);
+    }
+
+    /// Buffers `entries` until they can be appended, see `buffer_proposal()`.
+    pub(crate) fn buffer_proposals(&mut self, entries: &mut Vec<T>) {
+        self.pending_size += self.internal_storage.entries_size(entries);
+        self.pending_proposals.append(entries);
+    }
+
+    /// Takes the pending proposals, e.g. to append or forward them.
+    pub(crate) fn take_pending_proposals(&mut self) -> Vec<T> {
+        self.pending_size = 0;
+        std::mem::take(&mut self.pending_proposals)
+    }
+
+    /// Returns the tick at which the decided index last increased.
+    pub(crate) fn get_last_decide_tick(&self) -> u64 {
+        self.last_decide_tick
+    }
+
+    /// Returns the error of the latest capacity check of the storage, i.e., why the latest proposal was rejected with `ProposeErr::StorageFull`.
+    pub(crate) fn get_storage_error(&self) -> Option<StorageErr> {
+        self.storage_error
+    }
+
     /// Propose a reconfiguration. Returns error if already stopped or new configuration is empty.
     pub(crate) fn reconfigure(&mut self, rc: ReconfigurationRequest) -> Result<(), ProposeErr<T>> {
         let ReconfigurationRequest {
@@ -333,6 +1284,7 @@ where
         for pid in self.leader_state.get_promised_followers() {
             self.outgoing.push(PaxosMessage {
                 from: self.pid,
+                cluster_id: self.cluster_id,
                 to: pid,
                 msg: acc_ss.clone(),
             });
@@ -357,6 +1309,7 @@ where
         }
         self.outgoing.push(PaxosMessage {
             from: self.pid,
+            cluster_id: self.cluster_id,
             to: pid,
             msg: PaxosMsg::PrepareReq,
         });
@@ -364,7 +1317,8 @@ where
 
     fn propose_entry(&mut self, entry: T) {
         match self.state {
-            (Role::Leader, Phase::Prepare) => self.pending_proposals.push(entry),
+            (Role::Leader, _) if self.is_handing_over() => self.buffer_proposal(entry),
+            (Role::Leader, Phase::Prepare) => self.buffer_proposal(entry),
             (Role::Leader, Phase::Accept) => self.send_accept(entry),
             (Role::Leader, Phase::FirstAccept) => {
                 self.send_first_accept();
@@ -401,19 +1355,51 @@ enum Role {
 /// Configuration for `SequencePaxos`.
 /// # Fields
 /// * `configuration_id`: The identifier for the configuration that this Sequence Paxos replica is part of.
+/// * `cluster_id`: The identifier of the cluster that this replica is part of. Messages of other clusters are ignored.
 /// * `pid`: The unique identifier of this node. Must not be 0.
 /// * `peers`: The peers of this node i.e. the `pid`s of the other replicas in the configuration.
 /// * `buffer_size`: The buffer size for outgoing messages.
+/// * `expected_peers`: The expected number of peers, used to reserve the capacity of the outgoing messages.
+/// * `expected_inflight`: The expected number of pending proposals, used to reserve the capacity of the buffers of proposals and outgoing messages.
 /// * `skip_prepare_use_leader`: The initial leader of the cluster. Could be used in combination with reconfiguration to skip the prepare phase in the new configuration.
+/// * `persisted_leader`: The leader before a restart, adopted as the leader if it is another node.
 /// * `logger`: Custom logger for logging events of Sequence Paxos.
 /// * `logger_file_path`: The path where the default logger logs events.
+/// * `resend_timeout`: The number of ticks before the leader resends a `Prepare` to the peers that have not promised yet, or resynchronizes followers that have not accepted its entries. A lagging follower requests to catch up at most once per `resend_timeout` ticks.
+/// * `proposal_ttl`: The number of ticks after which a tracked proposal that is not decided yet is failed with `ProposalFailure::TimedOut`.
+/// * `snapshot_trigger`: When the leader automatically snapshots the decided log on `tick()`.
+/// * `follower_timeout`: The number of ticks without a message from a follower after which the leader excludes it from `min_replicated_idx()`.
+/// * `max_follower_backlog`: The number of unacknowledged entries after which the leader stops sending entries to a follower and resynchronizes it instead.
+/// * `witnesses`: The nodes that acknowledge entries without storing them and never become the leader.
+/// * `reconfiguration_timeout`: The number of ticks after which `ConsensusEvent::ReconfigurationStalled` is recorded if no leader of a new configuration (`configuration_id` greater than 1) is known yet, or if the new configuration is not confirmed after the `StopSign` was decided.
+/// * `prepare_timeout`: The number of ticks in the `Prepare` phase after which a leader records `ConsensusEvent::PrepareTimedOut`.
+/// * `event_buffer_size`: The maximum number of recorded events, `0` disables recording.
+/// * `coalesce_decides`: The maximum number of decided entries that are passed to the batch decide callback at once.
+/// * `max_entry_bytes`: The maximum size of a new proposal as computed by the entry sizer.
+/// * `max_inflight_entries`: The maximum number of accepted but undecided entries before new proposals are rejected.
 #[derive(Clone, Debug)]
 pub struct SequencePaxosConfig {
     configuration_id: u32,
+    cluster_id: ClusterId,
     pid: NodeId,
     peers: Vec<u64>,
     buffer_size: usize,
+    expected_peers: usize,
+    expected_inflight: usize,
     skip_prepare_use_leader: Option<Ballot>,
+    persisted_leader: Option<Ballot>,
+    resend_timeout: u64,
+    proposal_ttl: Option<u64>,
+    snapshot_trigger: SnapshotTrigger,
+    follower_timeout: Option<u64>,
+    max_follower_backlog: Option<u64>,
+    witnesses: Vec<NodeId>,
+    reconfiguration_timeout: Option<u64>,
+    prepare_timeout: Option<u64>,
+    event_buffer_size: usize,
+    coalesce_decides: Option<usize>,
+    max_entry_bytes: Option<usize>,
+    max_inflight_entries: Option<u64>,
     #[cfg(feature = "logging")]
     logger_file_path: Option<String>,
 }
@@ -422,10 +1408,26 @@ impl From<OmniPaxosConfig> for SequencePaxosConfig {
     fn from(config: OmniPaxosConfig) -> Self {
         SequencePaxosConfig {
             configuration_id: config.configuration_id,
+            cluster_id: config.cluster_id,
             pid: config.pid,
             peers: config.peers,
             buffer_size: config.buffer_size,
+            expected_peers: config.expected_peers,
+            expected_inflight: config.expected_inflight,
             skip_prepare_use_leader: config.skip_prepare_use_leader,
+            persisted_leader: config.persisted_leader,
+            resend_timeout: config.resend_timeout,
+            proposal_ttl: config.proposal_ttl,
+            snapshot_trigger: config.snapshot_trigger,
+            follower_timeout: config.follower_timeout,
+            max_follower_backlog: config.max_follower_backlog,
+            witnesses: config.witnesses,
+            reconfiguration_timeout: config.reconfiguration_timeout,
+            prepare_timeout: config.prepare_timeout,
+            event_buffer_size: config.event_buffer_size,
+            coalesce_decides: config.coalesce_decides,
+            max_entry_bytes: config.max_entry_bytes,
+            max_inflight_entries: config.max_inflight_entries,
             #[cfg(feature = "logging")]
             logger_file_path: config.logger_file_path,
         }
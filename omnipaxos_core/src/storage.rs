@@ -14,6 +14,49 @@ pub trait Entry: Clone + Debug {}
 
 impl<T> Entry for T where T: Clone + Debug {}
 
+/// An error of the storage when appending entries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StorageErr {
+    /// The storage has no capacity left for the entries, e.g. because it reached its configured limit of entries or bytes.
+    Full,
+}
+
+/// Metadata of an entry that is replicated together with it, e.g. to trace a command through the cluster without adding the fields to the command itself.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryMeta {
+    /// The id of the trace that the entry belongs to.
+    pub trace_id: u128,
+    /// The priority of the entry. Only interpreted by the application.
+    pub priority: u8,
+}
+
+/// An entry with its metadata. Use `WithMeta<T>` as the entry type of `OmniPaxos` and append entries with `OmniPaxos::append_with_meta()`,
+/// such that the metadata is stored and replicated with the entry, and returned with it by all reads of the log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithMeta<T> {
+    /// The entry of the application.
+    pub entry: T,
+    /// The metadata of the entry.
+    pub meta: EntryMeta,
+}
+
+impl<T> WithMeta<T> {
+    /// Creates an entry with metadata.
+    pub fn with(entry: T, meta: EntryMeta) -> Self {
+        Self { entry, meta }
+    }
+}
+
+/// An entry of opaque bytes that might be encoded by an [`EntryCodec`](crate::util::EntryCodec). Use `EncodedEntry` as the entry type of `OmniPaxos` and append entries with `OmniPaxos::append_encoded()`,
+/// such that large entries are stored and replicated encoded, and decode them with `OmniPaxos::read_decoded()` or `OmniPaxos::decode_entry()`. The plain `append()` and `read()` bypass the codec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedEntry {
+    /// The bytes of the entry, encoded if `encoded` is `true`.
+    pub bytes: Vec<u8>,
+    /// Whether `bytes` were encoded by the codec. Entries smaller than the threshold of the codec are stored as they are.
+    pub encoded: bool,
+}
+
 /// A StopSign entry that marks the end of a configuration. Used for reconfiguration.
 #[derive(Clone, Debug)]
 #[allow(missing_docs)]
@@ -150,6 +193,17 @@ where
 
     /// Returns the stored snapshot.
     fn get_snapshot(&self) -> Option<S>;
+
+    /// Returns `StorageErr::Full` if the storage has no capacity left to append `num_entries` entries whose total size, as returned by `entries_size()`, is `size`. New proposals are then rejected with `ProposeErr::StorageFull`.
+    /// The default implementation never is full.
+    fn check_capacity(&self, _num_entries: u64, _size: usize) -> Result<(), StorageErr> {
+        Ok(())
+    }
+
+    /// Returns the total size of `entries` that `check_capacity()` limits, e.g. their size on disk. The default implementation returns 0.
+    fn entries_size(&self, _entries: &[T]) -> usize {
+        0
+    }
 }
 
 #[allow(missing_docs)]
@@ -170,6 +224,7 @@ impl<T: Entry> Snapshot<T> for () {
 
 /// Internal representation of storage. Hides all complexities with the compacted index
 /// such that Sequence Paxos accesses the log with the uncompacted index.
+/// If `witness` is set, no entries are stored. Instead, the compacted index is used as the length of the log.
 pub(crate) struct InternalStorage<I, T, S>
 where
     I: Storage<T, S>,
@@ -177,6 +232,7 @@ where
     S: Snapshot<T>,
 {
     storage: I,
+    witness: bool,
     _t: PhantomData<T>,
     _i: PhantomData<S>,
 }
@@ -187,9 +243,10 @@ where
     T: Entry,
     S: Snapshot<T>,
 {
-    pub(crate) fn with(storage: I) -> Self {
+    pub(crate) fn with(storage: I, witness: bool) -> Self {
         InternalStorage {
             storage,
+            witness,
             _t: Default::default(),
             _i: Default::default(),
         }
@@ -352,17 +409,57 @@ where
         }
     }
 
+    pub(crate) fn is_witness(&self) -> bool {
+        self.witness
+    }
+
+    /// Checks the capacity for `entries` in addition to the `pending` entries of total size `pending_size` that are not appended yet.
+    pub(crate) fn check_capacity(
+        &self,
+        entries: &[T],
+        pending: usize,
+        pending_size: usize,
+    ) -> Result<(), StorageErr> {
+        if self.witness {
+            Ok(())
+        } else {
+            self.storage.check_capacity(
+                (pending + entries.len()) as u64,
+                pending_size + self.storage.entries_size(entries),
+            )
+        }
+    }
+
+    pub(crate) fn entries_size(&self, entries: &[T]) -> usize {
+        self.storage.entries_size(entries)
+    }
+
+    /// Sets the length of the log of a witness, which does not store any entries.
+    fn set_witness_log_len(&mut self, len: u64) -> u64 {
+        self.storage.set_compacted_idx(len);
+        len
+    }
+
     /*** Writing ***/
     pub(crate) fn append_entry(&mut self, entry: T) -> u64 {
+        if self.witness {
+            return self.set_witness_log_len(self.get_log_len() + 1);
+        }
         self.storage.append_entry(entry) + self.storage.get_compacted_idx()
     }
 
     pub(crate) fn append_entries(&mut self, entries: Vec<T>) -> u64 {
+        if self.witness {
+            return self.set_witness_log_len(self.get_log_len() + entries.len() as u64);
+        }
         self.storage.append_entries(entries) + self.storage.get_compacted_idx()
     }
 
     pub(crate) fn append_on_decided_prefix(&mut self, entries: Vec<T>) -> u64 {
         let decided_idx = self.storage.get_decided_idx();
+        if self.witness {
+            return self.set_witness_log_len(decided_idx + entries.len() as u64);
+        }
         let compacted_idx = self.storage.get_compacted_idx();
         self.storage
             .append_on_prefix(decided_idx - compacted_idx, entries)
@@ -370,6 +467,9 @@ where
     }
 
     pub(crate) fn append_on_prefix(&mut self, from_idx: u64, entries: Vec<T>) -> u64 {
+        if self.witness {
+            return self.set_witness_log_len(from_idx + entries.len() as u64);
+        }
         let compacted_idx = self.storage.get_compacted_idx();
         self.storage
             .append_on_prefix(from_idx - compacted_idx, entries)
@@ -462,6 +562,12 @@ where
 
     pub(crate) fn set_snapshot(&mut self, idx: u64, snapshot: S) {
         let compacted_idx = self.storage.get_compacted_idx();
+        if self.witness {
+            if idx > compacted_idx {
+                self.set_witness_log_len(idx);
+            }
+            return;
+        }
         if idx > compacted_idx {
             self.storage.trim(idx - compacted_idx);
             self.storage.set_snapshot(snapshot);
@@ -470,6 +576,9 @@ where
     }
 
     pub(crate) fn merge_snapshot(&mut self, idx: u64, delta: S) {
+        if self.witness {
+            return self.set_snapshot(idx, delta);
+        }
         let mut snapshot = self
             .storage
             .get_snapshot()
@@ -480,7 +589,7 @@ where
 
     pub(crate) fn try_trim(&mut self, idx: u64) -> Result<(), CompactionErr> {
         let compacted_idx = self.storage.get_compacted_idx();
-        if idx <= compacted_idx {
+        if idx <= compacted_idx || self.witness {
             Ok(()) // already trimmed or snapshotted this index.
         } else {
             let decided_idx = self.storage.get_decided_idx();
@@ -510,7 +619,7 @@ where
             }
             None => decided_idx,
         };
-        if idx > self.get_compacted_idx() {
+        if idx > self.get_compacted_idx() && !self.witness {
             let snapshot = self.create_snapshot(idx);
             self.set_snapshot(idx, snapshot);
         }
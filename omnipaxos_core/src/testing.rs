@@ -0,0 +1,98 @@
+use crate::{
+    ballot_leader_election::{Ballot, BallotLeaderElection},
+    messages::ballot_leader_election::BLEMessage,
+    omni_paxos::OmniPaxosConfig,
+};
+
+/// Replays a recorded trace of the incoming heartbeat messages of a server against a fresh leader election, e.g. to check that a new build elects the same leaders
+/// as the build that the trace was captured with. The tick of a message is the number of calls to `election_timeout()` at the server before it handled the message.
+/// The outgoing messages of the fresh leader election are dropped, as the replies of the peers are part of the trace.
+/// With the `hardened` feature, the nonces of the recorded replies are replaced by the nonces of the requests of the fresh leader election, so they are not compared.
+pub struct TraceReplayer {
+    ble: BallotLeaderElection,
+    trace: Vec<(u64, BLEMessage)>,
+    expectations: Vec<(u64, Ballot)>,
+}
+
+/// The leader that was elected during a replay differs from the expected leader.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TraceMismatch {
+    /// The tick at which the expectation did not hold.
+    pub tick: u64,
+    /// The ballot of the expected leader.
+    pub expected: Ballot,
+    /// The ballot of the latest leader elected by the replay, or `None` if no leader had been elected yet.
+    pub elected: Option<Ballot>,
+}
+
+impl TraceReplayer {
+    /// Creates a replayer of `trace` for a server with `config`, which should be the config of the server whose incoming messages were recorded.
+    pub fn with(config: OmniPaxosConfig, trace: Vec<(u64, BLEMessage)>) -> Self {
+        Self {
+            ble: BallotLeaderElection::with(config.into()),
+            trace,
+            expectations: vec![],
+        }
+    }
+
+    /// Expects that `leader` is the latest leader elected at `tick`, i.e., after `tick` calls to `election_timeout()` and handling the messages of that tick.
+    pub fn expect_leader(mut self, tick: u64, leader: Ballot) -> Self {
+        self.expectations.push((tick, leader));
+        self
+    }
+
+    /// Replays the trace in the order of the ticks and checks the expected leaders. Returns the first expectation that did not hold.
+    pub fn replay(mut self) -> Result<(), TraceMismatch> {
+        // stable sorts keep the recorded order of the messages within a tick
+        self.trace.sort_by_key(|(tick, _)| *tick);
+        self.expectations.sort_by_key(|(tick, _)| *tick);
+        let last_tick = self
+            .trace
+            .iter()
+            .map(|(tick, _)| *tick)
+            .chain(self.expectations.iter().map(|(tick, _)| *tick))
+            .max()
+            .unwrap_or(0);
+        let mut trace = self.trace.into_iter().peekable();
+        let mut expectations = self.expectations.into_iter().peekable();
+        let mut elected = None;
+        for tick in 0..=last_tick {
+            if tick > 0 {
+                if let Some(b) = self.ble.hb_timeout() {
+                    elected = Some(b);
+                }
+            }
+            while let Some((_, m)) = trace.next_if(|(t, _)| *t == tick) {
+                let m = Self::replay_nonce(&self.ble, m);
+                let _ = self.ble.try_handle(m);
+            }
+            self.ble.get_outgoing_msgs();
+            while let Some((_, expected)) = expectations.next_if(|(t, _)| *t == tick) {
+                if elected != Some(expected) {
+                    return Err(TraceMismatch {
+                        tick,
+                        expected,
+                        elected,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the nonce of a recorded reply with the nonce of the request of the replay that it answers.
+    #[cfg(feature = "hardened")]
+    fn replay_nonce(ble: &BallotLeaderElection, mut m: BLEMessage) -> BLEMessage {
+        if let crate::messages::ballot_leader_election::HeartbeatMsg::Reply(rep) = &mut m.msg {
+            if let Some(nonce) = ble.outstanding_nonce(m.from, rep.round) {
+                rep.nonce = nonce;
+            }
+        }
+        m
+    }
+
+    #[cfg(not(feature = "hardened"))]
+    fn replay_nonce(_ble: &BallotLeaderElection, m: BLEMessage) -> BLEMessage {
+        m
+    }
+}
@@ -46,13 +46,19 @@ where
     pub n_leader: Ballot,
     pub promises_meta: Vec<Option<PromiseMetaData>>,
     pub accepted_indexes: Vec<u64>,
+    pub last_heard: Vec<Option<u64>>, // tick of the latest message from each follower
+    pub sync_indexes: Vec<u64>,       // log length at the latest AcceptSync to each follower
+    pub applied_indexes: Vec<u64>,    // applied index reported by each follower
     pub decided_indexes: Vec<Option<u64>>,
     pub chosen_idx: u64, // length of longest chosen seq
     pub max_promise_meta: PromiseMetaData,
     pub max_promise: Option<(Option<SnapshotType<T, S>>, Vec<T>)>, // (decided_snapshot, suffix)
+    pub max_witness_promise: Option<(Ballot, u64)>, // (n_accepted, accepted_idx) of the most updated witness
+    pub witnesses: Vec<NodeId>,
     #[cfg(feature = "batch_accept")]
     pub batch_accept_meta: Vec<Option<(Ballot, usize)>>, //  index in outgoing
     pub accepted_stopsign: Vec<bool>,
+    pub resend_idx: u64, // log length at the previous resend
     pub max_pid: usize,
     pub majority: usize,
 }
@@ -67,18 +73,25 @@ where
         decided_indexes: Option<Vec<Option<u64>>>,
         max_pid: usize,
         majority: usize,
+        witnesses: Vec<NodeId>,
     ) -> Self {
         Self {
             n_leader,
             promises_meta: vec![None; max_pid],
             accepted_indexes: vec![0; max_pid],
+            last_heard: vec![None; max_pid],
+            sync_indexes: vec![0; max_pid],
+            applied_indexes: vec![0; max_pid],
             decided_indexes: decided_indexes.unwrap_or_else(|| vec![None; max_pid]),
             chosen_idx: 0,
             max_promise_meta: PromiseMetaData::default(),
             max_promise: None,
+            max_witness_promise: None,
+            witnesses,
             #[cfg(feature = "batch_accept")]
             batch_accept_meta: vec![None; max_pid],
             accepted_stopsign: vec![false; max_pid],
+            resend_idx: 0,
             max_pid,
             majority,
         }
@@ -99,14 +112,22 @@ where
             pid: from,
             stopsign: prom.stopsign,
         };
-        if check_max_prom && promise_meta > self.max_promise_meta {
+        if self.witnesses.contains(&from) {
+            // a witness has no entries to sync from, but a full node that has its accepted entries must be promised
+            let witness_promise = Some((prom.n_accepted, prom.accepted_idx));
+            if witness_promise > self.max_witness_promise {
+                self.max_witness_promise = witness_promise;
+            }
+        } else if check_max_prom && promise_meta > self.max_promise_meta {
             self.max_promise_meta = promise_meta.clone();
             self.max_promise = Some((prom.decided_snapshot, prom.suffix))
         }
         self.decided_indexes[Self::pid_to_idx(from)] = Some(prom.decided_idx);
         self.promises_meta[Self::pid_to_idx(from)] = Some(promise_meta);
         let num_promised = self.promises_meta.iter().filter(|x| x.is_some()).count();
-        num_promised >= self.majority
+        let max_full_promise = Some((self.max_promise_meta.n, self.max_promise_meta.accepted_idx));
+        let covers_witnesses = max_full_promise >= self.max_witness_promise;
+        num_promised >= self.majority && covers_witnesses
     }
 
     pub fn take_max_promise(&mut self) -> Option<(Option<SnapshotType<T, S>>, Vec<T>)> {
@@ -121,6 +142,11 @@ where
         self.accepted_stopsign[Self::pid_to_idx(from)] = true;
     }
 
+    pub fn is_promised(&self, pid: NodeId) -> bool {
+        // decided_indexes is also set for followers that implicitly promised, e.g. with skip_prepare_use_leader
+        self.decided_indexes[Self::pid_to_idx(pid)].is_some()
+    }
+
     pub fn get_promise_meta(&self, pid: NodeId) -> &PromiseMetaData {
         self.promises_meta[Self::pid_to_idx(pid)]
             .as_ref()
@@ -166,6 +192,44 @@ where
         self.accepted_indexes[Self::pid_to_idx(pid)] = idx;
     }
 
+    pub fn get_accepted_idx(&self, pid: NodeId) -> u64 {
+        self.accepted_indexes[Self::pid_to_idx(pid)]
+    }
+
+    pub fn set_sync_idx(&mut self, pid: NodeId, idx: u64) {
+        self.sync_indexes[Self::pid_to_idx(pid)] = idx;
+    }
+
+    pub fn get_sync_idx(&self, pid: NodeId) -> u64 {
+        self.sync_indexes[Self::pid_to_idx(pid)]
+    }
+
+    pub fn set_applied_idx(&mut self, pid: NodeId, idx: u64) {
+        // reports might be reordered, the applied index of a follower only grows
+        let applied_idx = &mut self.applied_indexes[Self::pid_to_idx(pid)];
+        *applied_idx = std::cmp::max(*applied_idx, idx);
+    }
+
+    pub fn get_applied_idx(&self, pid: NodeId) -> u64 {
+        self.applied_indexes[Self::pid_to_idx(pid)]
+    }
+
+    pub fn set_last_heard(&mut self, pid: NodeId, tick: u64) {
+        self.last_heard[Self::pid_to_idx(pid)] = Some(tick);
+    }
+
+    pub fn get_last_heard(&self, pid: NodeId) -> Option<u64> {
+        self.last_heard[Self::pid_to_idx(pid)]
+    }
+
+    pub fn set_resend_idx(&mut self, idx: u64) {
+        self.resend_idx = idx;
+    }
+
+    pub fn get_resend_idx(&self) -> u64 {
+        self.resend_idx
+    }
+
     #[cfg(feature = "batch_accept")]
     pub fn get_batch_accept_meta(&self, pid: NodeId) -> Option<(Ballot, usize)> {
         self.batch_accept_meta
@@ -237,7 +301,7 @@ where
     Trimmed(TrimmedIndex),
     /// The entry has been snapshotted.
     Snapshotted(SnapshottedEntry<T, S>),
-    /// This Sequence Paxos instance has been stopped for reconfiguration.
+    /// This Sequence Paxos instance has been stopped for reconfiguration. The StopSign is always the last entry of the log, no normal entries follow it in this configuration.
     StopSign(StopSign),
 }
 
@@ -278,12 +342,183 @@ where
 pub(crate) mod defaults {
     pub(crate) const BUFFER_SIZE: usize = 100000;
     pub(crate) const BLE_BUFFER_SIZE: usize = 100;
+    pub(crate) const RESEND_TIMEOUT: u64 = 10;
+    pub(crate) const DECIDED_ITER_BATCH_SIZE: u64 = 1000;
+    #[cfg(feature = "rejected_ballots")]
+    pub(crate) const REJECTED_BALLOTS_SIZE: usize = 100;
 }
 
 #[allow(missing_docs)]
 pub type TrimmedIndex = u64;
 
+/// The number of buckets of the commit latency histogram returned by `OmniPaxos::commit_latency_histogram()`.
+pub const COMMIT_LATENCY_BUCKETS: usize = 8;
+
+/// The version of the message format of this build, see `messages::Envelope`. The versions are:
+/// * `1`: the format without the additions below.
+/// * `2`: adds `PaxosMsg::PrepareNack`.
+/// * `3`: adds the `cluster_id` of the sender to `PaxosMessage` and `BLEMessage`.
+/// * `4`: adds `PaxosMsg::Applied` and `PaxosMsg::AppliedAck`.
+/// * `5`: adds the length-prefixed frames of `FramedCodec` for stream transports.
+/// * `6`: adds `HeartbeatMsg::TimeoutNow`.
+/// * `7`: adds `PaxosMsg::ProposalRejected`.
+/// * `8`: adds `HeartbeatMsg::LeaderAnnouncement`.
+pub const MESSAGE_VERSION: u16 = 8;
+/// The oldest version of the message format that this build can still handle.
+pub const MIN_MESSAGE_VERSION: u16 = 3;
+/// The version of the format of the election state returned by `OmniPaxos::serialize_election_state()`. A state of another version is rejected when deserializing.
+pub const ELECTION_STATE_VERSION: u8 = 1;
+
 /// ID for an OmniPaxos node
 pub type NodeId = u64;
+/// ID for a proposal appended with `append_tracked()`.
+pub type ProposalId = u64;
 /// ID for an OmniPaxos configuration (i.e., the set of servers in an OmniPaxos cluster)
 pub type ConfigurationId = u32;
+/// ID for an OmniPaxos cluster. Unlike the `ConfigurationId`, it stays the same across reconfigurations.
+pub type ClusterId = u64;
+/// Callback of `OmniPaxos::set_on_decide()` that is called with the index and the entry of every decided entry.
+pub type DecideCallback<T> = Box<dyn FnMut(u64, &T) + Send + Sync>;
+/// Callback of `OmniPaxos::set_on_decide_batch()` that is called with the index of the first entry and a batch of consecutive decided entries.
+pub type DecideBatchCallback<T> = Box<dyn FnMut(u64, &[T]) + Send + Sync>;
+/// Function of `OmniPaxos::set_entry_sizer()` that returns the size in bytes of an entry once it is serialized.
+pub type EntrySizer<T> = Box<dyn Fn(&T) -> usize + Send + Sync>;
+/// Hook of `OmniPaxos::set_ballot_generator()` that computes the next ballot number from the ballot number of the previous leader.
+pub type BallotGenerator = Box<dyn FnMut(u32) -> u32 + Send + Sync>;
+
+/// Encodes the bytes of an entry before it is appended to the log, e.g. by compressing it, and decodes it when it is read. Set with `OmniPaxos::set_entry_codec()`.
+pub trait EntryCodec {
+    /// Returns the encoding of the entry `bytes`.
+    fn encode(&self, bytes: &[u8]) -> Vec<u8>;
+    /// Returns the entry of the encoded `bytes`, i.e., reverses `encode()`.
+    fn decode(&self, bytes: &[u8]) -> Vec<u8>;
+}
+
+/// Signs serialized messages at the sender and verifies their signatures at the receiver, e.g. with a key pair per server.
+/// Set with `OmniPaxos::set_message_signer()`.
+#[cfg(feature = "signing")]
+pub trait MessageSigner {
+    /// Returns the signature of the serialized message `bytes` sent by this server.
+    fn sign(&self, bytes: &[u8]) -> Vec<u8>;
+    /// Returns whether `signature` is a valid signature of the serialized message `bytes` by the server `pid`.
+    fn verify(&self, pid: NodeId, bytes: &[u8], signature: &[u8]) -> bool;
+}
+
+/// The signature of a serialized outgoing message, returned by `OmniPaxos::sign_message()`. The network layer sends it along with the message, e.g. encoded with `to_bytes()`.
+#[cfg(feature = "signing")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageSignature {
+    /// The session of the signing server, as passed to `set_message_signer()`.
+    pub session: u64,
+    /// The sequence number of the message in the session of the signing server.
+    pub seq: u64,
+    /// The signature by the `MessageSigner` of the signing server of `session`, `seq` and the serialized message.
+    pub signature: Vec<u8>,
+}
+
+#[cfg(feature = "signing")]
+impl MessageSignature {
+    /// Returns the payload that is signed for the serialized message `bytes`.
+    pub(crate) fn signed_bytes(session: u64, seq: u64, bytes: &[u8]) -> Vec<u8> {
+        let mut signed = Vec::with_capacity(16 + bytes.len());
+        signed.extend_from_slice(&session.to_be_bytes());
+        signed.extend_from_slice(&seq.to_be_bytes());
+        signed.extend_from_slice(bytes);
+        signed
+    }
+
+    /// Returns the signature encoded as `session`, `seq` (both big-endian) and the signature bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        Self::signed_bytes(self.session, self.seq, &self.signature)
+    }
+
+    /// Returns the signature encoded with `to_bytes()`, or `None` if `bytes` is too short.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        use std::convert::TryInto;
+        if bytes.len() < 16 {
+            return None;
+        }
+        let (session, rest) = bytes.split_at(8);
+        let (seq, signature) = rest.split_at(8);
+        Some(Self {
+            session: u64::from_be_bytes(session.try_into().unwrap()),
+            seq: u64::from_be_bytes(seq.try_into().unwrap()),
+            signature: signature.to_vec(),
+        })
+    }
+}
+
+/// The signed messages that were received from a server, to drop replayed messages.
+/// Messages of older sessions are dropped, as are the messages of the latest session whose sequence number was already seen or is more than 64 below the highest one.
+#[cfg(feature = "signing")]
+#[derive(Clone, Debug)]
+pub(crate) struct ReplayWindow {
+    session: u64,
+    highest_seq: u64,
+    seen: u64, // bit i is set if `highest_seq - i` was received
+}
+
+#[cfg(feature = "signing")]
+impl ReplayWindow {
+    pub(crate) fn with(session: u64, seq: u64) -> Self {
+        Self {
+            session,
+            highest_seq: seq,
+            seen: 1,
+        }
+    }
+
+    /// Records the message `seq` of `session` and returns `false` if it is a replay.
+    pub(crate) fn accept(&mut self, session: u64, seq: u64) -> bool {
+        if session < self.session {
+            return false;
+        }
+        if session > self.session {
+            *self = Self::with(session, seq);
+            return true;
+        }
+        if seq > self.highest_seq {
+            let shift = seq - self.highest_seq;
+            self.seen = if shift >= 64 { 0 } else { self.seen << shift };
+            self.seen |= 1;
+            self.highest_seq = seq;
+            return true;
+        }
+        let age = self.highest_seq - seq;
+        if age >= 64 || self.seen & (1 << age) != 0 {
+            return false;
+        }
+        self.seen |= 1 << age;
+        true
+    }
+}
+
+#[cfg(feature = "checksum")]
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC32 (IEEE) checksum of `bytes`, e.g. of a serialized message.
+/// The sender should send the checksum together with the serialized message, so that the receiver can verify it with `OmniPaxos::verify_checksum()`.
+#[cfg(feature = "checksum")]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, b| {
+        (crc >> 8) ^ CRC32_TABLE[((crc ^ *b as u32) & 0xFF) as usize]
+    })
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 /// The identifier for the configuration that this Omni-Paxos replica is part of.
 pub const CONFIG_ID: &str = "config_id";
+/// The identifier for the cluster that this Omni-Paxos replica is part of.
+pub const CLUSTER_ID: &str = "cluster_id";
 /// The identifier of this replica.
 pub const PID: &str = "pid";
 /// The peers of this replica.
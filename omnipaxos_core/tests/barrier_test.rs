@@ -0,0 +1,156 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::{
+        sequence_paxos::{PaxosMessage, PaxosMsg},
+        Message,
+    },
+    omni_paxos::BarrierStatus,
+    util::NodeId,
+};
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader},
+    Value,
+};
+
+/// Verifies that a barrier completes once all members have reported applying the log up to its index,
+/// and that a member that is down is excluded after it has been silent for `follower_timeout` ticks.
+#[test]
+fn barrier_test() {
+    let all_pids: Vec<NodeId> = vec![1, 2, 3];
+    let mut nodes = create_cluster(3, |c| c.follower_timeout = Some(5));
+    let leader = elect_leader(&mut nodes);
+    let mut followers: Vec<NodeId> = all_pids
+        .iter()
+        .filter(|pid| **pid != leader)
+        .cloned()
+        .collect();
+    followers.sort_unstable();
+    let (slow, crashed) = (followers[0], followers[1]);
+    for i in 0..2 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    deliver_all(&mut nodes, None);
+
+    let barrier = nodes[&leader].barrier(2);
+    assert_eq!(barrier.idx(), 2);
+    let mut waiting = all_pids.clone();
+    waiting.sort_unstable();
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Pending(waiting)
+    );
+    assert_eq!(
+        nodes[&slow].poll_barrier(&barrier),
+        BarrierStatus::NotLeader
+    );
+
+    // the applied index is capped at the decided index
+    nodes.get_mut(&leader).unwrap().set_applied_idx(5);
+    assert_eq!(nodes[&leader].get_applied_idx(), 2);
+    nodes.get_mut(&crashed).unwrap().set_applied_idx(2);
+    nodes.values_mut().for_each(|n| n.tick());
+    deliver_all(&mut nodes, None);
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Pending(vec![slow])
+    );
+    nodes.get_mut(&slow).unwrap().set_applied_idx(2);
+    nodes.values_mut().for_each(|n| n.tick());
+    deliver_all(&mut nodes, None);
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Complete
+    );
+
+    // the crashed follower blocks the next barrier until it has been silent for too long
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(2))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, Some(crashed));
+    let barrier = nodes[&leader].barrier(3);
+    for pid in [leader, slow] {
+        nodes.get_mut(&pid).unwrap().set_applied_idx(3);
+    }
+    nodes.values_mut().for_each(|n| n.tick());
+    deliver_all(&mut nodes, Some(crashed));
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Pending(vec![crashed])
+    );
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(&mut nodes, Some(crashed));
+    }
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Complete
+    );
+}
+
+/// Verifies that a follower resends a lost report of its applied index until the leader acknowledges it, so the barrier still completes.
+#[test]
+fn barrier_lost_report_test() {
+    const RESEND_TIMEOUT: u64 = 3;
+    let mut nodes = create_cluster(3, |c| c.resend_timeout = RESEND_TIMEOUT);
+    let leader = elect_leader(&mut nodes);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(0))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    let barrier = nodes[&leader].barrier(1);
+    nodes.values_mut().for_each(|n| n.set_applied_idx(1));
+    nodes.values_mut().for_each(|n| n.tick());
+    let mut lost = 0;
+    deliver_filtered(&mut nodes, |m| {
+        let report = matches!(
+            m,
+            Message::SequencePaxos(PaxosMessage {
+                msg: PaxosMsg::Applied(_),
+                ..
+            })
+        );
+        lost += report as usize;
+        report
+    });
+    assert_eq!(lost, 2);
+    assert!(matches!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Pending(_)
+    ));
+
+    for _ in 0..RESEND_TIMEOUT {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(&mut nodes, None);
+    }
+    assert_eq!(
+        nodes[&leader].poll_barrier(&barrier),
+        BarrierStatus::Complete
+    );
+    // the acknowledged reports are not sent again
+    for _ in 0..2 * RESEND_TIMEOUT {
+        nodes.values_mut().for_each(|n| n.tick());
+        let reports = nodes
+            .values_mut()
+            .flat_map(|n| n.outgoing_messages())
+            .filter(|m| {
+                matches!(
+                    m,
+                    Message::SequencePaxos(PaxosMessage {
+                        msg: PaxosMsg::Applied(_),
+                        ..
+                    })
+                )
+            })
+            .count();
+        assert_eq!(reports, 0);
+    }
+}
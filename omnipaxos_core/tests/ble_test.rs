@@ -1,9 +1,27 @@
 pub mod utils;
 
 use kompact::prelude::{promise, Ask};
-use omnipaxos_core::ballot_leader_election::Ballot;
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    messages::{
+        ballot_leader_election::{BLEMessage, HeartbeatMsg},
+        Message,
+    },
+    omni_paxos::{
+        ElectionStateErr, HandleErr, OmniPaxos, OmniPaxosConfig, PartitionStatus, RejoinPolicy,
+    },
+    util::{NodeId, ELECTION_STATE_VERSION},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
 use serial_test::serial;
-use utils::{TestConfig, TestSystem};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader, hb_round, Node},
+    TestConfig, TestSystem, Value,
+};
 
 /// Test Ballot Election Leader module.
 /// The test waits for [`num_elections`] elections.
@@ -53,3 +71,1547 @@ fn ble_test() {
         Err(e) => panic!("Error on kompact shutdown: {}", e),
     };
 }
+
+/// Verifies that a hinted leader is only adopted if its ballot is greater than the current leader.
+#[test]
+#[serial]
+fn hint_leader_test() {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    let mut omni_paxos = op_config.build(MemoryStorage::<Value, ()>::default());
+    assert_eq!(omni_paxos.get_current_leader_ballot(), None);
+
+    let hinted = Ballot::with(2, 0, 1);
+    assert!(omni_paxos.hint_leader(hinted));
+    assert_eq!(omni_paxos.get_current_leader_ballot(), Some(hinted));
+
+    // lower ballot should be ignored
+    assert!(!omni_paxos.hint_leader(Ballot::with(1, 0, 1)));
+    assert_eq!(omni_paxos.get_current_leader_ballot(), Some(hinted));
+}
+
+/// Verifies that a leader that is followed after its `Prepare` is adopted by the leader election as well.
+#[test]
+fn prepare_leader_adopted_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = Ballot::with(2, 0, 2);
+    assert!(nodes.get_mut(&2).unwrap().hint_leader(leader));
+    assert_eq!(nodes[&1].leader_quorum_connected(), None);
+
+    // only the messages of Sequence Paxos are delivered, so the others learn of the leader from its `Prepare`
+    deliver_filtered(&mut nodes, |m| matches!(m, Message::BLE(_)));
+    for node in nodes.values() {
+        assert_eq!(node.get_current_leader_ballot(), Some(leader));
+        // the leader election knows of the leader as well
+        assert!(node.leader_quorum_connected().is_some());
+    }
+}
+
+/// Verifies that with `sticky_leader`, the leader is only replaced by a candidate with a strictly greater ballot number or priority, not by one that only has a greater pid.
+#[test]
+fn sticky_leader_test() {
+    let leader = Ballot::with(1, 0, 2);
+    let equal = Ballot::with(1, 0, 3);
+    for sticky in [false, true] {
+        let mut op_config = OmniPaxosConfig::default();
+        op_config.pid = 1;
+        op_config.peers = vec![2, 3];
+        op_config.configuration_id = 1;
+        op_config.sticky_leader = sticky;
+        let mut omni_paxos = op_config.build(MemoryStorage::<Value, ()>::default());
+        assert!(omni_paxos.hint_leader(leader));
+
+        let expected = if sticky { leader } else { equal };
+        assert_eq!(
+            omni_paxos.would_elect(&[(leader, true), (equal, true)]),
+            Some(expected)
+        );
+        // a strictly greater ballot number or priority always takes over
+        for greater in [Ballot::with(2, 0, 1), Ballot::with(1, 1, 1)] {
+            assert_eq!(
+                omni_paxos.would_elect(&[(leader, true), (equal, true), (greater, true)]),
+                Some(greater)
+            );
+        }
+        // the leader is not kept once it is no longer a candidate
+        assert_eq!(
+            omni_paxos.would_elect(&[(leader, false), (equal, true)]),
+            Some(equal)
+        );
+    }
+}
+
+/// Verifies that a leader that is disconnected for fewer than `quorum_grace_rounds` rounds keeps its leadership,
+/// but is demoted if it remains disconnected.
+#[test]
+fn quorum_grace_rounds_test() {
+    const GRACE_ROUNDS: u32 = 3;
+    for (disconnected_rounds, demoted) in [(GRACE_ROUNDS - 1, false), (GRACE_ROUNDS + 1, true)] {
+        let mut nodes = create_cluster(3, |c| c.quorum_grace_rounds = GRACE_ROUNDS);
+        for _ in 0..10 {
+            hb_round(&mut nodes, None);
+        }
+        let leader = nodes[&1]
+            .get_current_leader_ballot()
+            .expect("No leader has been elected");
+
+        for _ in 0..disconnected_rounds {
+            hb_round(&mut nodes, Some(leader.pid));
+        }
+        for _ in 0..10 {
+            hb_round(&mut nodes, None);
+        }
+        for n in nodes.values() {
+            let current_leader = n.get_current_leader_ballot();
+            if demoted {
+                assert_ne!(current_leader, Some(leader), "Leader was not demoted");
+            } else {
+                assert_eq!(current_leader, Some(leader), "Leader was demoted");
+            }
+        }
+    }
+}
+
+/// Verifies that the fixed leader is elected without any heartbeats and that entries get decided.
+#[test]
+fn fixed_leader_test() {
+    const LEADER: NodeId = 2;
+    let mut nodes = create_cluster(3, |c| c.fixed_leader = Some(LEADER));
+    for n in nodes.values_mut() {
+        assert!(
+            n.outgoing_messages().is_empty(),
+            "Heartbeats were sent with a fixed leader"
+        );
+    }
+    hb_round(&mut nodes, None);
+    for n in nodes.values() {
+        assert_eq!(n.get_current_leader(), Some(LEADER));
+    }
+
+    nodes
+        .get_mut(&LEADER)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    hb_round(&mut nodes, None);
+    for n in nodes.values() {
+        assert_eq!(n.get_decided_idx(), 1);
+    }
+}
+
+/// Verifies that the leader is demoted and notified when another server takes over with a greater ballot.
+#[test]
+fn leader_demotion_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    let demotions = Arc::new(Mutex::new(vec![]));
+    let d = demotions.clone();
+    nodes
+        .get_mut(&leader.pid)
+        .unwrap()
+        .on_demotion(Box::new(move |n| d.lock().unwrap().push(n)));
+
+    let new_pid = *nodes.keys().find(|pid| **pid != leader.pid).unwrap();
+    let new_leader = Ballot::with(leader.n + 1, 0, new_pid);
+    assert!(nodes.get_mut(&new_pid).unwrap().hint_leader(new_leader));
+    hb_round(&mut nodes, None);
+
+    assert_eq!(*demotions.lock().unwrap(), vec![new_leader]);
+    assert!(!nodes[&leader.pid].health().is_leader);
+}
+
+/// Verifies that a server is only quiescent once its heartbeat round has received all replies.
+#[test]
+fn quiescent_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    elect_leader(&mut nodes);
+    deliver_all(&mut nodes, None);
+    assert!(nodes[&1].is_quiescent());
+
+    nodes.get_mut(&1).unwrap().election_timeout();
+    assert!(!nodes[&1].is_quiescent());
+    // the requests are sent, but the replies are still in flight
+    for m in nodes.get_mut(&1).unwrap().outgoing_messages() {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    assert!(!nodes[&1].is_quiescent());
+    deliver_all(&mut nodes, None);
+    assert!(nodes[&1].is_quiescent());
+}
+
+/// Verifies that a server with registered callbacks can still be shared between threads.
+#[test]
+fn callbacks_sync_test() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    let mut nodes = create_cluster(3, |_| {});
+    let node = nodes.get_mut(&1).unwrap();
+    node.on_demotion(Box::new(|_| {}));
+    node.on_snapshot_installed(Box::new(|_| {}));
+    assert_send_sync(node);
+}
+
+/// Verifies that `handle_ble_checked()` reports stale replies and messages from unknown senders as ignored.
+#[test]
+fn handle_ble_checked_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let ble_msgs = |n: &mut OmniPaxos<Value, (), MemoryStorage<Value, ()>>| -> Vec<BLEMessage> {
+        n.outgoing_messages()
+            .into_iter()
+            .filter_map(|m| match m {
+                Message::BLE(b) => Some(b),
+                _ => None,
+            })
+            .collect()
+    };
+    let request = ble_msgs(nodes.get_mut(&1).unwrap())
+        .into_iter()
+        .find(|m| m.to == 2 && matches!(m.msg, HeartbeatMsg::Request(_)))
+        .unwrap();
+    let mut unknown = request.clone();
+    unknown.from = 9;
+    assert!(!nodes.get_mut(&2).unwrap().handle_ble_checked(unknown));
+    assert!(nodes.get_mut(&2).unwrap().handle_ble_checked(request));
+
+    let reply = ble_msgs(nodes.get_mut(&2).unwrap())
+        .into_iter()
+        .find(|m| matches!(m.msg, HeartbeatMsg::Reply(_)))
+        .unwrap();
+    // the reply is stale once 1 has started two new rounds
+    nodes.get_mut(&1).unwrap().election_timeout();
+    nodes.get_mut(&1).unwrap().election_timeout();
+    assert!(!nodes.get_mut(&1).unwrap().handle_ble_checked(reply));
+}
+
+/// Verifies that `try_handle()` returns why a message of the leader election was ignored.
+#[test]
+fn try_handle_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let ble_msgs = |n: &mut OmniPaxos<Value, (), MemoryStorage<Value, ()>>| -> Vec<BLEMessage> {
+        n.outgoing_messages()
+            .into_iter()
+            .filter_map(|m| match m {
+                Message::BLE(b) => Some(b),
+                _ => None,
+            })
+            .collect()
+    };
+    let request = ble_msgs(nodes.get_mut(&1).unwrap())
+        .into_iter()
+        .find(|m| m.to == 2 && matches!(m.msg, HeartbeatMsg::Request(_)))
+        .unwrap();
+    let mut unknown = request.clone();
+    unknown.from = 9;
+    assert_eq!(
+        nodes.get_mut(&2).unwrap().try_handle(Message::BLE(unknown)),
+        Err(HandleErr::UnknownPeer(9))
+    );
+    // `handle_incoming()` does not return the reason but still counts the dropped message
+    let mut unknown = request.clone();
+    unknown.from = 8;
+    nodes
+        .get_mut(&2)
+        .unwrap()
+        .handle_incoming(Message::BLE(unknown));
+    assert_eq!(nodes[&2].unknown_peer_messages_dropped(), 2);
+    assert_eq!(
+        nodes.get_mut(&2).unwrap().try_handle(Message::BLE(request)),
+        Ok(())
+    );
+
+    let reply = ble_msgs(nodes.get_mut(&2).unwrap())
+        .into_iter()
+        .find(|m| matches!(m.msg, HeartbeatMsg::Reply(_)))
+        .unwrap();
+    let round = match &reply.msg {
+        HeartbeatMsg::Reply(rep) => rep.round,
+        _ => unreachable!(),
+    };
+    // a reply to the previous round still counts, only older replies are stale
+    nodes.get_mut(&1).unwrap().election_timeout();
+    nodes.get_mut(&1).unwrap().election_timeout();
+    // with `hardened`, the requests of older rounds are no longer outstanding
+    let expected = if cfg!(feature = "hardened") {
+        HandleErr::UnknownNonce(2)
+    } else {
+        HandleErr::StaleRound(round)
+    };
+    assert_eq!(
+        nodes.get_mut(&1).unwrap().try_handle(Message::BLE(reply)),
+        Err(expected)
+    );
+}
+
+/// Verifies that `ticks_as_leader()` counts the ticks since the current leader was elected and restarts on a new ballot.
+#[test]
+fn ticks_as_leader_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    assert_eq!(nodes[&1].ticks_as_leader(), None);
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    for n in nodes.values() {
+        assert_eq!(n.ticks_as_leader(), Some(5));
+    }
+
+    // the same server is re-elected with a greater ballot
+    let new_leader = Ballot::with(leader.n + 1, leader.priority, leader.pid);
+    assert!(nodes.get_mut(&leader.pid).unwrap().hint_leader(new_leader));
+    hb_round(&mut nodes, None);
+    nodes.values_mut().for_each(|n| n.tick());
+    for n in nodes.values() {
+        assert_eq!(n.get_current_leader_ballot(), Some(new_leader));
+        assert_eq!(n.ticks_as_leader(), Some(1));
+    }
+}
+
+/// Verifies that the leadership is rotated with `leader_rotation_rounds`, but kept if no other candidate is connected.
+#[test]
+fn leader_rotation_test() {
+    const ROTATION_ROUNDS: u32 = 3;
+    let mut nodes = create_cluster(3, |c| c.leader_rotation_rounds = Some(ROTATION_ROUNDS));
+    let mut leaders = vec![];
+    for _ in 0..30 {
+        hb_round(&mut nodes, None);
+        if let Some(l) = nodes[&1].get_current_leader() {
+            if leaders.last() != Some(&l) {
+                leaders.push(l);
+            }
+        }
+    }
+    assert!(
+        leaders.len() > 2,
+        "Leadership was not rotated: {:?}",
+        leaders
+    );
+    let rotations: u64 = nodes.values().map(|n| n.voluntary_rotations()).sum();
+    assert!(rotations >= leaders.len() as u64 - 1);
+
+    // all other servers are witnesses and can never take over
+    let mut nodes = create_cluster(3, |c| {
+        c.leader_rotation_rounds = Some(ROTATION_ROUNDS);
+        c.witnesses = vec![1, 2];
+    });
+    for _ in 0..30 {
+        hb_round(&mut nodes, None);
+    }
+    for n in nodes.values() {
+        assert_eq!(n.get_current_leader(), Some(3));
+        assert_eq!(n.voluntary_rotations(), 0);
+    }
+}
+
+/// Verifies that `verify_invariants()` holds during elections and detects a leader outside the configuration.
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "is not a member of the configuration")]
+fn verify_invariants_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+        nodes.values().for_each(|n| n.verify_invariants());
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    let unknown_leader = Ballot::with(leader.n + 1, 0, 9);
+    let node = nodes.get_mut(&1).unwrap();
+    assert!(node.hint_leader(unknown_leader));
+    node.verify_invariants();
+}
+
+/// Verifies that the connectivity of the leader is returned as claimed by the leader, and not the connectivity of this server.
+#[test]
+fn leader_quorum_connected_test() {
+    let mut nodes = create_cluster(3, |c| c.quorum_grace_rounds = 2);
+    assert_eq!(nodes[&1].leader_quorum_connected(), None);
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    for node in nodes.values() {
+        assert_eq!(node.leader_quorum_connected(), Some(true));
+    }
+    // the followers keep the leader during the grace period, but did not hear from it in the round that ended last
+    for _ in 0..2 {
+        hb_round(&mut nodes, Some(leader));
+    }
+    for (pid, node) in nodes.iter().filter(|(pid, _)| **pid != leader) {
+        assert_eq!(node.get_current_leader(), Some(leader));
+        assert!(node.health().majority_connected);
+        assert_eq!(
+            node.leader_quorum_connected(),
+            Some(false),
+            "Unexpected connectivity of the leader at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a server whose ballot is received but that is not connected to a majority is not elected,
+/// even though its ballot is greater than the ballots of the connected candidates.
+#[test]
+fn quorum_connected_candidate_test() {
+    let mut nodes = create_cluster(5, |_| {});
+    // 5 is only connected to 1
+    let lost = |m: &Message<Value, ()>| {
+        let (from, to) = (m.get_sender(), m.get_receiver());
+        (from == 5 || to == 5) && from != 1 && to != 1
+    };
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_filtered(&mut nodes, lost);
+    }
+    let ballots = nodes[&1].last_round_ballots();
+    assert!(ballots
+        .iter()
+        .any(|(b, connected)| b.pid == 5 && !connected));
+    for pid in 1..=4 {
+        assert_eq!(nodes[&pid].get_current_leader(), Some(4));
+    }
+}
+
+/// Verifies that the leader elected by a group is fanned out to the groups registered with it, which follow it without exchanging heartbeats themselves.
+#[test]
+fn shared_leader_election_test() {
+    let mut group_a = create_cluster(3, |_| {});
+    let mut groups: BTreeMap<u64, _> = (2..=3)
+        .map(|group_id| (group_id, create_cluster(3, |c| c.cluster_id = group_id)))
+        .collect();
+    for node in group_a.values_mut() {
+        groups
+            .keys()
+            .for_each(|group_id| node.register_group(*group_id));
+    }
+    // only the heartbeats sent when creating the groups are exchanged
+    groups.values_mut().for_each(|g| deliver_all(g, None));
+    for _ in 0..10 {
+        hb_round(&mut group_a, None);
+        for (pid, node) in group_a.iter_mut() {
+            for (group_id, ballot) in node.take_group_leaders() {
+                groups
+                    .get_mut(&group_id)
+                    .unwrap()
+                    .get_mut(pid)
+                    .unwrap()
+                    .hint_leader(ballot);
+            }
+        }
+        for group in groups.values_mut() {
+            group.values_mut().for_each(|n| n.tick());
+            deliver_all(group, None);
+        }
+    }
+    let leader = group_a[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    for group in groups.values_mut() {
+        group
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(1))
+            .expect("Failed to append");
+        loop {
+            let msgs: Vec<_> = group
+                .values_mut()
+                .flat_map(|n| n.outgoing_messages())
+                .collect();
+            if msgs.is_empty() {
+                break;
+            }
+            assert!(
+                msgs.iter().all(|m| matches!(m, Message::SequencePaxos(_))),
+                "A registered group sent heartbeats"
+            );
+            for m in msgs {
+                group.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+            }
+        }
+        for node in group.values() {
+            assert_eq!(node.get_current_leader(), Some(leader));
+            assert_eq!(node.get_decided_idx(), 1);
+        }
+    }
+
+    // a group registered after the election learns the current leader right away
+    let node = group_a.get_mut(&1).unwrap();
+    node.register_group(4);
+    assert_eq!(
+        node.take_group_leaders(),
+        vec![(4, node.get_current_leader_ballot().unwrap())]
+    );
+}
+
+/// Verifies that with `heartbeat_peers`, a server only sends requests to that many peers and the leader per round,
+/// that all peers are contacted over the rounds, and that a leader is still elected and kept.
+#[test]
+fn heartbeat_peers_test() {
+    const HEARTBEAT_PEERS: usize = 3;
+    let all_pids: Vec<NodeId> = (1..=7).collect();
+    let mut nodes = create_cluster(all_pids.len() as u64, |c| {
+        c.heartbeat_peers = Some(HEARTBEAT_PEERS)
+    });
+    // the requests of the initial round
+    deliver_all(&mut nodes, None);
+    let mut contacted: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        let msgs: Vec<_> = nodes
+            .values_mut()
+            .flat_map(|n| n.outgoing_messages())
+            .collect();
+        for pid in &all_pids {
+            let targets: Vec<NodeId> = msgs
+                .iter()
+                .filter_map(|m| match m {
+                    Message::BLE(BLEMessage {
+                        from,
+                        to,
+                        msg: HeartbeatMsg::Request(_),
+                        ..
+                    }) if from == pid => Some(*to),
+                    _ => None,
+                })
+                .collect();
+            // the leader is contacted in addition to the subset
+            assert!(targets.len() == HEARTBEAT_PEERS || targets.len() == HEARTBEAT_PEERS + 1);
+            contacted.entry(*pid).or_default().extend(targets);
+        }
+        for m in msgs {
+            nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+        }
+        deliver_all(&mut nodes, None);
+    }
+    for (pid, targets) in contacted.iter_mut() {
+        targets.sort_unstable();
+        targets.dedup();
+        let peers: Vec<NodeId> = all_pids.iter().filter(|p| *p != pid).cloned().collect();
+        assert_eq!(*targets, peers, "Not all peers were contacted by {}", pid);
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    for node in nodes.values() {
+        assert_eq!(node.get_current_leader(), Some(leader));
+    }
+}
+
+/// Verifies that the leader is considered stable after `stable_leader_rounds` rounds and not anymore as soon as it is missed.
+#[test]
+fn stable_leader_test() {
+    let mut nodes = create_cluster(3, |c| c.stable_leader_rounds = Some(3));
+    let mut leader = None;
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+        if leader.is_none() {
+            leader = nodes[&1].get_current_leader();
+            assert!(!nodes[&1].is_leader_stable());
+        }
+    }
+    let leader = leader.expect("No leader has been elected");
+    let follower = *nodes.keys().find(|p| **p != leader).unwrap();
+    assert!(nodes[&follower].is_leader_stable());
+
+    // the round in which the leader is partitioned ends with the next call to `election_timeout()`
+    hb_round(&mut nodes, Some(leader));
+    hb_round(&mut nodes, Some(leader));
+    assert!(!nodes[&follower].is_leader_stable());
+}
+
+/// Verifies that heartbeats sent by a server to itself, e.g. by a broadcast transport, are ignored and its ballot is only counted once.
+#[test]
+fn loopback_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        let msgs: Vec<_> = nodes
+            .values_mut()
+            .flat_map(|n| n.outgoing_messages())
+            .collect();
+        for m in msgs {
+            if let Message::BLE(b) = &m {
+                // loop the message back to its sender as well
+                let mut looped = b.clone();
+                looped.to = b.from;
+                assert_eq!(
+                    nodes
+                        .get_mut(&b.from)
+                        .unwrap()
+                        .try_handle(Message::BLE(looped)),
+                    Err(HandleErr::Loopback)
+                );
+            }
+            nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+        }
+        deliver_all(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        let ballots = node.last_round_ballots();
+        assert_eq!(
+            ballots.len(),
+            3,
+            "Unexpected ballots at {}: {:?}",
+            pid,
+            ballots
+        );
+        assert_eq!(ballots.iter().filter(|(b, _)| b.pid == *pid).count(), 1);
+    }
+}
+
+/// Verifies that the highest ballot seen by the followers is the ballot of the elected leader, while the leader has only seen lower ballots.
+#[test]
+fn highest_seen_ballot_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    assert_eq!(nodes[&1].highest_seen_ballot(), Ballot::default());
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    for (pid, node) in &nodes {
+        if *pid == leader.pid {
+            assert!(node.highest_seen_ballot() < leader);
+        } else {
+            assert_eq!(node.highest_seen_ballot(), leader);
+        }
+    }
+}
+
+/// Verifies that restoring a checkpoint of the leader election lets a branched execution reach the same outcome again.
+#[test]
+fn checkpoint_election_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    let checkpoints: HashMap<NodeId, _> = nodes
+        .iter()
+        .map(|(pid, n)| (*pid, n.checkpoint_election()))
+        .collect();
+    let ballots: HashMap<NodeId, Vec<(Ballot, bool)>> = nodes
+        .iter()
+        .map(|(pid, n)| (*pid, n.last_round_ballots().to_vec()))
+        .collect();
+
+    // branch 1: the leader is partitioned and another server takes over
+    for _ in 0..3 {
+        hb_round(&mut nodes, Some(leader));
+    }
+    let follower = *nodes.keys().find(|p| **p != leader).unwrap();
+    let new_leader = nodes[&follower].get_current_leader();
+    assert_ne!(new_leader, Some(leader));
+
+    // branch 2: restored to the checkpoint, the same partition elects the same new leader
+    for (pid, n) in nodes.iter_mut() {
+        n.restore_election(checkpoints[pid].clone());
+    }
+    for (pid, n) in &nodes {
+        assert_eq!(n.last_round_ballots(), ballots[pid].as_slice());
+    }
+    for _ in 0..3 {
+        hb_round(&mut nodes, Some(leader));
+    }
+    assert_eq!(nodes[&follower].get_current_leader(), new_leader);
+}
+
+/// Verifies that a new leader uses the ballot number of the ballot generator, and that a generated number that is not greater is ignored.
+#[test]
+fn ballot_generator_test() {
+    for (step, expected_step) in [(10, 10), (0, 1)].iter() {
+        let mut nodes = create_cluster(3, |_| {});
+        for n in nodes.values_mut() {
+            let step = *step;
+            n.set_ballot_generator(Box::new(move |n| n + step));
+        }
+        for _ in 0..10 {
+            hb_round(&mut nodes, None);
+        }
+        let leader = nodes[&1]
+            .get_current_leader_ballot()
+            .expect("No leader has been elected");
+        for _ in 0..4 {
+            hb_round(&mut nodes, Some(leader.pid));
+        }
+        let follower = *nodes.keys().find(|p| **p != leader.pid).unwrap();
+        let new_leader = nodes[&follower]
+            .get_current_leader_ballot()
+            .expect("No leader has been elected");
+        assert_ne!(new_leader.pid, leader.pid);
+        assert_eq!(new_leader.n, leader.n + expected_step);
+    }
+}
+
+/// Verifies that a partitioned server reports that it is in a minority after the round in which it was partitioned, while the others still have a majority.
+#[test]
+fn partition_status_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    assert_eq!(nodes[&1].partition_status(), PartitionStatus::Unknown);
+    for _ in 0..3 {
+        hb_round(&mut nodes, None);
+    }
+    for node in nodes.values() {
+        assert_eq!(node.partition_status(), PartitionStatus::Majority);
+    }
+
+    // the round in which the server is partitioned ends with the next call to `election_timeout()`
+    hb_round(&mut nodes, Some(1));
+    hb_round(&mut nodes, Some(1));
+    assert_eq!(
+        nodes[&1].partition_status(),
+        PartitionStatus::Minority {
+            reachable: 1,
+            needed: 2
+        }
+    );
+    assert_eq!(nodes[&2].partition_status(), PartitionStatus::Majority);
+}
+
+/// Delivers outgoing messages until there are none left. Every message is delivered to the receiver in all `clusters`, as if they shared a network.
+fn deliver_shared(clusters: &mut [BTreeMap<NodeId, Node>]) {
+    loop {
+        let msgs: Vec<Message<Value, ()>> = clusters
+            .iter_mut()
+            .flat_map(|nodes| nodes.values_mut().flat_map(|n| n.outgoing_messages()))
+            .collect();
+        if msgs.is_empty() {
+            break;
+        }
+        for m in msgs {
+            for nodes in clusters.iter_mut() {
+                nodes
+                    .get_mut(&m.get_receiver())
+                    .unwrap()
+                    .handle_incoming(m.clone());
+            }
+        }
+    }
+}
+
+/// Verifies that two clusters with the same pids on a shared network ignore each other's messages.
+#[test]
+fn cluster_id_test() {
+    let mut clusters = vec![
+        create_cluster(3, |c| c.cluster_id = 1),
+        create_cluster(3, |c| c.cluster_id = 2),
+    ];
+    for _ in 0..5 {
+        for nodes in clusters.iter_mut() {
+            nodes.values_mut().for_each(|n| n.election_timeout());
+        }
+        deliver_shared(&mut clusters);
+    }
+    let leader = clusters[0][&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    clusters[0]
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_shared(&mut clusters);
+    for node in clusters[0].values() {
+        assert_eq!(node.get_decided_idx(), 1);
+        assert!(node.foreign_messages_dropped() > 0);
+    }
+    for node in clusters[1].values() {
+        assert_eq!(node.get_decided_idx(), 0);
+    }
+
+    let foreign_node = clusters[1].get_mut(&1).unwrap();
+    foreign_node.election_timeout();
+    let foreign = foreign_node
+        .outgoing_messages()
+        .into_iter()
+        .find(|m| matches!(m, Message::BLE(_)))
+        .expect("No heartbeat request");
+    let node = clusters[0].get_mut(&foreign.get_receiver()).unwrap();
+    assert_eq!(node.try_handle(foreign), Err(HandleErr::ForeignCluster(2)));
+    let foreign_leader = clusters[1][&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    let foreign_node = clusters[1].get_mut(&foreign_leader).unwrap();
+    foreign_node.append(Value(2)).expect("Failed to append");
+    let foreign = foreign_node
+        .outgoing_messages()
+        .into_iter()
+        .find(|m| matches!(m, Message::SequencePaxos(_)))
+        .expect("No accept");
+    let node = clusters[0].get_mut(&foreign.get_receiver()).unwrap();
+    assert_eq!(node.try_handle(foreign), Err(HandleErr::ForeignCluster(2)));
+    node.reset_metrics();
+    assert_eq!(node.foreign_messages_dropped(), 0);
+}
+
+/// Verifies that the candidates are ordered by their ballots, followed by a witness that never advertises its candidacy.
+#[test]
+fn candidates_test() {
+    let mut nodes = create_cluster(3, |c| c.witnesses = vec![3]);
+    assert!(nodes[&1].candidates().is_empty());
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    for (pid, node) in &nodes {
+        let candidates = node.candidates();
+        assert_eq!(
+            candidates.len(),
+            3,
+            "Unexpected candidates at {}: {:?}",
+            pid,
+            candidates
+        );
+        assert_eq!(candidates[0], (leader, true));
+        assert!(candidates[1].1 && candidates[1].0 < leader);
+        assert_eq!(candidates[2].0.pid, 3);
+        assert!(!candidates[2].1);
+    }
+}
+
+/// Verifies that a preferred leader is elected over a candidate with a greater pid while it is available, that another candidate takes over
+/// while it is partitioned, and that it takes back the leadership once it is reachable again.
+#[test]
+fn preferred_leaders_test() {
+    let mut nodes = create_cluster(3, |c| c.preferred_leaders = vec![1]);
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader(),
+            Some(1),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+
+    // the primary is down, so the secondary with the greatest ballot is elected
+    for _ in 0..5 {
+        hb_round(&mut nodes, Some(1));
+    }
+    assert_eq!(nodes[&2].get_current_leader(), Some(3));
+    assert_eq!(nodes[&3].get_current_leader(), Some(3));
+
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader(),
+            Some(1),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a restarted follower adopts its persisted leader right away and keeps it, and that a restarted leader is re-elected with the next greater ballot.
+#[test]
+fn persisted_leader_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    let restart = |pid: NodeId, persisted_leader: Option<Ballot>| {
+        let mut op_config = OmniPaxosConfig::default();
+        op_config.pid = pid;
+        op_config.peers = vec![1, 2, 3].into_iter().filter(|p| *p != pid).collect();
+        op_config.configuration_id = 1;
+        op_config
+            .with_persisted_leader(persisted_leader)
+            .build(MemoryStorage::default())
+    };
+
+    let follower = (1..=3).find(|pid| *pid != leader.pid).unwrap();
+    nodes.insert(follower, restart(follower, Some(leader)));
+    assert_eq!(nodes[&follower].get_current_leader_ballot(), Some(leader));
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader_ballot(),
+            Some(leader),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+
+    nodes.insert(leader.pid, restart(leader.pid, Some(leader)));
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let resumed = Ballot::with(leader.n + 1, leader.priority, leader.pid);
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader_ballot(),
+            Some(resumed),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a former leader that rejoins after a partition adopts the leader elected in the meantime with `RejoinPolicy::FastYield`,
+/// and takes the leadership back with a greater ballot with `RejoinPolicy::Contest`.
+#[test]
+fn rejoin_policy_test() {
+    for policy in vec![RejoinPolicy::FastYield, RejoinPolicy::Contest] {
+        let mut nodes = create_cluster(3, |c| c.rejoin_policy = policy);
+        for _ in 0..5 {
+            hb_round(&mut nodes, None);
+        }
+        let former = nodes[&1]
+            .get_current_leader_ballot()
+            .expect("No leader has been elected");
+        for _ in 0..5 {
+            hb_round(&mut nodes, Some(former.pid));
+        }
+        let current = nodes
+            .iter()
+            .find(|(pid, _)| **pid != former.pid)
+            .and_then(|(_, n)| n.get_current_leader_ballot())
+            .expect("No leader has been elected during the partition");
+        assert_ne!(current.pid, former.pid);
+        assert!(current > former);
+
+        for _ in 0..5 {
+            hb_round(&mut nodes, None);
+        }
+        for (pid, node) in &nodes {
+            let leader = node.get_current_leader_ballot();
+            match policy {
+                RejoinPolicy::FastYield if *pid != former.pid => {
+                    assert_eq!(leader, Some(current), "Unexpected leader at {}", pid)
+                }
+                RejoinPolicy::FastYield => assert!(!node.health().is_leader),
+                RejoinPolicy::Contest => {
+                    let leader = leader.expect("No leader after rejoining");
+                    assert_eq!(leader.pid, former.pid, "Unexpected leader at {}", pid);
+                    assert!(leader > current);
+                }
+            }
+        }
+    }
+}
+
+/// Runs a heartbeat round where the heartbeat replies to `pid` are lost, such that it loses its quorum while the others still hear from it.
+fn flaky_hb_round(nodes: &mut BTreeMap<NodeId, Node>, pid: NodeId) {
+    nodes.values_mut().for_each(|n| n.election_timeout());
+    deliver_filtered(nodes, |m| {
+        matches!(
+            m,
+            Message::BLE(BLEMessage {
+                to,
+                msg: HeartbeatMsg::Reply(_),
+                ..
+            }) if *to == pid
+        )
+    });
+}
+
+/// Verifies that the followers do not contest a leader that temporarily loses its quorum during `candidate_cooldown_rounds`,
+/// but still elect a new leader right away if the leader is gone.
+#[test]
+fn candidate_cooldown_test() {
+    for cooldown in vec![0, 20] {
+        let mut nodes = create_cluster(3, |c| c.candidate_cooldown_rounds = cooldown);
+        for _ in 0..5 {
+            hb_round(&mut nodes, None);
+        }
+        let leader = nodes[&1]
+            .get_current_leader_ballot()
+            .expect("No leader has been elected");
+        let follower = (1..=3).find(|pid| *pid != leader.pid).unwrap();
+        let mut current = leader;
+        let mut leader_changes = 0;
+        for round in 0..10 {
+            if round < 3 {
+                flaky_hb_round(&mut nodes, leader.pid);
+            } else {
+                hb_round(&mut nodes, None);
+            }
+            let l = nodes[&follower].get_current_leader_ballot().unwrap();
+            if l != current {
+                current = l;
+                leader_changes += 1;
+            }
+        }
+        if cooldown == 0 {
+            assert!(leader_changes > 0, "The flaky leader was not contested");
+        } else {
+            assert_eq!(leader_changes, 0, "The flaky leader was contested");
+
+            // a leader that is gone is contested despite the cooldown
+            for _ in 0..5 {
+                hb_round(&mut nodes, Some(leader.pid));
+            }
+            let new_leader = nodes[&follower].get_current_leader_ballot().unwrap();
+            assert_ne!(new_leader.pid, leader.pid);
+        }
+    }
+}
+
+/// Verifies that a round is in progress until all peers replied to its heartbeat requests, and that an isolated server receives no replies.
+#[test]
+fn round_in_progress_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    assert!(nodes[&1].round_in_progress());
+    assert_eq!(nodes[&1].replies_this_round(), 0);
+    deliver_all(&mut nodes, None);
+    for (pid, node) in &nodes {
+        assert!(!node.round_in_progress(), "Round in progress at {}", pid);
+        assert_eq!(
+            node.replies_this_round(),
+            2,
+            "Unexpected replies at {}",
+            pid
+        );
+    }
+
+    for _ in 0..3 {
+        hb_round(&mut nodes, Some(1));
+        assert!(nodes[&1].round_in_progress());
+        assert_eq!(nodes[&1].replies_this_round(), 0);
+        assert_eq!(nodes[&2].replies_this_round(), 1);
+    }
+
+    let nodes = create_cluster(3, |c| c.fixed_leader = Some(1));
+    assert!(!nodes[&1].round_in_progress());
+}
+
+/// Verifies that `ensure_ballot_at_least()` raises the ballot number of a server in a single call, keeping its pid and priority, and never lowers it.
+#[test]
+fn ensure_ballot_at_least_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let joining = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let ballot_of = |nodes: &BTreeMap<NodeId, Node>, pid: NodeId| -> Ballot {
+        nodes[&leader]
+            .candidates()
+            .into_iter()
+            .map(|(b, _)| b)
+            .find(|b| b.pid == pid)
+            .expect("No ballot of the server in the latest round")
+    };
+    let cluster_ballot = Ballot::with(7, 3, leader);
+    nodes
+        .get_mut(&joining)
+        .unwrap()
+        .ensure_ballot_at_least(cluster_ballot);
+    // the candidates are those of the latest completed round
+    hb_round(&mut nodes, None);
+    hb_round(&mut nodes, None);
+    assert_eq!(ballot_of(&nodes, joining), Ballot::with(7, 0, joining));
+
+    // a smaller ballot does not lower it
+    nodes
+        .get_mut(&joining)
+        .unwrap()
+        .ensure_ballot_at_least(Ballot::with(2, 0, leader));
+    hb_round(&mut nodes, None);
+    hb_round(&mut nodes, None);
+    assert_eq!(ballot_of(&nodes, joining).n, 7);
+}
+
+/// Verifies that `would_elect()` applies the priorities and preferred leaders of the election without changing the state, and matches the leader of a real round.
+#[test]
+fn would_elect_test() {
+    let mut nodes = create_cluster(3, |c| c.preferred_leaders = vec![1]);
+    let node = &nodes[&2];
+    let low = Ballot::with(1, 0, 2);
+    let high = Ballot::with(1, 0, 3);
+    let preferred = Ballot::with(0, 0, 1);
+    assert_eq!(
+        node.would_elect(&[(low, true), (high, true), (preferred, false)]),
+        Some(high)
+    );
+    assert_eq!(
+        node.would_elect(&[(low, true), (high, true), (preferred, true)]),
+        Some(preferred)
+    );
+    assert_eq!(
+        node.would_elect(&[(Ballot::with(1, 5, 2), true), (high, true)]),
+        Some(Ballot::with(1, 5, 2))
+    );
+    assert_eq!(node.would_elect(&[(low, false), (high, false)]), None);
+    assert_eq!(node.get_current_leader_ballot(), None);
+
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.would_elect(&node.candidates()),
+            node.get_current_leader_ballot(),
+            "Unexpected prediction at {}",
+            pid
+        );
+    }
+    // without the candidacy of the preferred leader, the next candidate takes over
+    let node = &nodes[&2];
+    let candidates = node.candidates();
+    let ballots: Vec<(Ballot, bool)> = candidates
+        .iter()
+        .map(|(b, candidate)| (*b, *candidate && b.pid != 1))
+        .collect();
+    let next = ballots
+        .iter()
+        .filter(|(_, candidate)| *candidate)
+        .map(|(b, _)| *b)
+        .max();
+    assert_eq!(node.would_elect(&ballots), next);
+    assert_ne!(next.map(|b| b.pid), Some(1));
+
+    // a round in which only candidates with smaller ballots than the leader reply gives up the leader without electing another
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let node = &nodes[&1];
+    let leader = node.get_current_leader_ballot().expect("No leader");
+    let smaller = node
+        .candidates()
+        .iter()
+        .map(|(b, _)| *b)
+        .find(|b| *b < leader)
+        .expect("No ballot smaller than the leader");
+    assert_eq!(node.would_elect(&[(leader, false), (smaller, true)]), None);
+    assert_eq!(
+        node.would_elect(&[(leader, true), (smaller, true)]),
+        Some(leader)
+    );
+}
+
+/// Verifies that a standby built with the same config resumes the election where the serialized server left off, and that invalid states are rejected without changes.
+#[test]
+fn election_state_transfer_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader_ballot()
+        .expect("No leader has been elected");
+    let follower = if leader.pid == 1 { 2 } else { 1 };
+    let state = nodes[&follower].serialize_election_state();
+
+    let mut standby = create_cluster(3, |_| {}).remove(&follower).unwrap();
+    assert_eq!(
+        standby.deserialize_election_state(&state[..state.len() - 1]),
+        Err(ElectionStateErr::Malformed)
+    );
+    let mut unknown_version = state.clone();
+    unknown_version[0] = ELECTION_STATE_VERSION + 1;
+    assert_eq!(
+        standby.deserialize_election_state(&unknown_version),
+        Err(ElectionStateErr::UnsupportedVersion(
+            ELECTION_STATE_VERSION + 1
+        ))
+    );
+    assert_eq!(
+        nodes
+            .get_mut(&leader.pid)
+            .unwrap()
+            .deserialize_election_state(&state),
+        Err(ElectionStateErr::OtherServer(follower))
+    );
+    assert_eq!(standby.get_current_leader_ballot(), None);
+    standby
+        .deserialize_election_state(&state)
+        .expect("Failed to deserialize the election state");
+    assert_eq!(standby.serialize_election_state(), state);
+
+    // the standby recovers its log from the leader and keeps the elected leader
+    standby.fail_recovery();
+    nodes.insert(follower, standby);
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader_ballot(),
+            Some(leader),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a reply with a ballot that was already received in the round, e.g. from a server with a colliding pid, is rejected and not counted.
+/// With `hardened`, the duplicated replies are rejected by their nonce instead, see `unknown_nonce_test`.
+#[cfg(not(feature = "hardened"))]
+#[test]
+fn duplicate_ballot_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    nodes.get_mut(&1).unwrap().election_timeout();
+    let requests: Vec<Message<Value, ()>> = nodes.get_mut(&1).unwrap().outgoing_messages();
+    for m in requests {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    let reply = |n: &mut OmniPaxos<Value, (), MemoryStorage<Value, ()>>| -> BLEMessage {
+        n.outgoing_messages()
+            .into_iter()
+            .find_map(|m| match m {
+                Message::BLE(b) if matches!(b.msg, HeartbeatMsg::Reply(_)) => Some(b),
+                _ => None,
+            })
+            .expect("No heartbeat reply")
+    };
+    let reply_2 = reply(nodes.get_mut(&2).unwrap());
+    let reply_3 = reply(nodes.get_mut(&3).unwrap());
+    let with_ballot = |m: &BLEMessage, ballot: Ballot| -> BLEMessage {
+        let mut m = m.clone();
+        if let HeartbeatMsg::Reply(rep) = &mut m.msg {
+            rep.ballot = ballot;
+        }
+        m
+    };
+    let ballot_of = |m: &BLEMessage| match &m.msg {
+        HeartbeatMsg::Reply(rep) => rep.ballot,
+        _ => unreachable!(),
+    };
+    let ballot_2 = ballot_of(&reply_2);
+    let node = nodes.get_mut(&1).unwrap();
+    assert_eq!(node.try_handle(Message::BLE(reply_2.clone())), Ok(()));
+    assert_eq!(
+        node.try_handle(Message::BLE(reply_2)),
+        Err(HandleErr::DuplicateBallot(ballot_2))
+    );
+    // server 3 replies with the ballot of server 2, e.g. because both are configured with the same pid
+    let colliding = with_ballot(&reply_3, Ballot::with(ballot_2.n, 7, ballot_2.pid));
+    assert_eq!(
+        node.try_handle(Message::BLE(colliding)),
+        Err(HandleErr::DuplicateBallot(Ballot::with(
+            ballot_2.n,
+            7,
+            ballot_2.pid
+        )))
+    );
+    // server 3 replies with the ballot of this server
+    let own_ballot = node
+        .candidates()
+        .iter()
+        .map(|(b, _)| *b)
+        .find(|b| b.pid == 1)
+        .unwrap();
+    assert_eq!(
+        node.try_handle(Message::BLE(with_ballot(&reply_3, own_ballot))),
+        Err(HandleErr::DuplicateBallot(own_ballot))
+    );
+    assert_eq!(node.replies_this_round(), 1);
+    assert_eq!(node.try_handle(Message::BLE(reply_3)), Ok(()));
+    assert_eq!(node.replies_this_round(), 2);
+}
+
+/// Verifies that with `rejected_ballots`, the candidates that are lower than a leader that is no longer heard from are recorded when the leader is given up.
+#[cfg(feature = "rejected_ballots")]
+#[test]
+fn rejected_ballots_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let leader_ballot = nodes[&1].get_current_leader_ballot().unwrap();
+    assert!(nodes[&1].rejected_ballots().is_empty());
+
+    for _ in 0..5 {
+        hb_round(&mut nodes, Some(leader));
+    }
+    let rejected = nodes[&1].rejected_ballots();
+    assert!(!rejected.is_empty(), "No rejected ballots were recorded");
+    for r in &rejected {
+        assert_eq!(r.leader, leader_ballot);
+        assert!(r.ballot < leader_ballot);
+        assert_ne!(r.ballot.pid, leader);
+    }
+    // electing a new leader keeps the records
+    assert_ne!(nodes[&1].get_current_leader(), Some(leader));
+    assert_eq!(nodes[&1].rejected_ballots(), rejected);
+}
+
+/// Verifies that with `hardened`, a duplicated reply and a reply of another server to the same request are rejected by their nonce and not counted.
+#[cfg(feature = "hardened")]
+#[test]
+fn unknown_nonce_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    nodes.get_mut(&1).unwrap().election_timeout();
+    let requests: Vec<Message<Value, ()>> = nodes.get_mut(&1).unwrap().outgoing_messages();
+    for m in requests {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    let reply_2 = nodes
+        .get_mut(&2)
+        .unwrap()
+        .outgoing_messages()
+        .into_iter()
+        .find_map(|m| match m {
+            Message::BLE(b) if matches!(b.msg, HeartbeatMsg::Reply(_)) => Some(b),
+            _ => None,
+        })
+        .expect("No heartbeat reply");
+    let mut forged = reply_2.clone();
+    forged.from = 3;
+    let node = nodes.get_mut(&1).unwrap();
+    assert_eq!(
+        node.try_handle(Message::BLE(forged)),
+        Err(HandleErr::UnknownNonce(3))
+    );
+    assert_eq!(node.try_handle(Message::BLE(reply_2.clone())), Ok(()));
+    assert_eq!(
+        node.try_handle(Message::BLE(reply_2)),
+        Err(HandleErr::UnknownNonce(2))
+    );
+    assert_eq!(node.replies_this_round(), 1);
+}
+
+/// Verifies that with `hardened`, a reply is only counted if it carries the nonce of the request, such that a replayed reply of an earlier round is rejected.
+#[cfg(feature = "hardened")]
+#[test]
+fn replayed_reply_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    elect_leader(&mut nodes);
+    let reply_of = |nodes: &mut BTreeMap<NodeId, Node>| -> BLEMessage {
+        nodes.get_mut(&1).unwrap().election_timeout();
+        for m in nodes.get_mut(&1).unwrap().outgoing_messages() {
+            nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+        }
+        nodes
+            .get_mut(&2)
+            .unwrap()
+            .outgoing_messages()
+            .into_iter()
+            .find_map(|m| match m {
+                Message::BLE(b) if matches!(b.msg, HeartbeatMsg::Reply(_)) => Some(b),
+                _ => None,
+            })
+            .expect("No heartbeat reply")
+    };
+    let old_reply = reply_of(&mut nodes);
+    assert_eq!(
+        nodes
+            .get_mut(&1)
+            .unwrap()
+            .try_handle(Message::BLE(old_reply.clone())),
+        Ok(())
+    );
+    deliver_all(&mut nodes, None);
+    reply_of(&mut nodes);
+    reply_of(&mut nodes);
+
+    // the reply of the current round with a wrong nonce
+    let mut reply = reply_of(&mut nodes);
+    if let HeartbeatMsg::Reply(rep) = &mut reply.msg {
+        rep.nonce = rep.nonce.wrapping_add(1);
+    }
+    let node = nodes.get_mut(&1).unwrap();
+    assert_eq!(
+        node.try_handle(Message::BLE(reply)),
+        Err(HandleErr::UnknownNonce(2))
+    );
+    assert_eq!(
+        node.try_handle(Message::BLE(old_reply)),
+        Err(HandleErr::UnknownNonce(2))
+    );
+    assert_eq!(node.replies_this_round(), 0);
+}
+
+/// Verifies that a reply that arrives one round late counts for the current round until the sender replies to the current round.
+#[test]
+fn late_reply_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let node_1 = nodes.get_mut(&1).unwrap();
+    node_1.election_timeout();
+    let requests: Vec<Message<Value, ()>> = node_1
+        .outgoing_messages()
+        .into_iter()
+        .filter(|m| m.get_receiver() == 2)
+        .collect();
+    nodes.get_mut(&2).unwrap().handle_all(requests);
+    let late_reply = nodes.get_mut(&2).unwrap().outgoing_messages();
+
+    // the reply arrives after the next round has started
+    let node_1 = nodes.get_mut(&1).unwrap();
+    node_1.election_timeout();
+    let requests: Vec<Message<Value, ()>> = node_1
+        .outgoing_messages()
+        .into_iter()
+        .filter(|m| m.get_receiver() == 2)
+        .collect();
+    for m in late_reply {
+        assert_eq!(nodes.get_mut(&1).unwrap().try_handle(m), Ok(()));
+    }
+    assert_eq!(nodes[&1].replies_this_round(), 1);
+    assert_eq!(
+        nodes[&1].partition_status(),
+        PartitionStatus::Minority {
+            reachable: 1,
+            needed: 2
+        }
+    );
+    nodes.get_mut(&2).unwrap().handle_all(requests);
+    let reply = nodes.get_mut(&2).unwrap().outgoing_messages();
+    nodes.get_mut(&1).unwrap().handle_all(reply);
+    assert_eq!(nodes[&1].replies_this_round(), 1);
+
+    // the late reply is enough for a majority, so the leader is kept
+    let leader = nodes[&1].get_current_leader_ballot();
+    nodes.get_mut(&1).unwrap().election_timeout();
+    assert_eq!(nodes[&1].partition_status(), PartitionStatus::Majority);
+    assert_eq!(nodes[&1].get_current_leader_ballot(), leader);
+}
+
+/// Verifies that `announce_leadership()` starts a heartbeat round at the leader right away, keeping the replies of the interrupted round, and does nothing at a follower.
+#[test]
+fn announce_leadership_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    let follower = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    nodes.get_mut(&follower).unwrap().announce_leadership();
+    assert!(nodes
+        .get_mut(&follower)
+        .unwrap()
+        .outgoing_messages()
+        .is_empty());
+
+    assert_eq!(nodes[&leader].replies_this_round(), 2);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.announce_leadership();
+    let requests = leader_node.outgoing_messages();
+    // a heartbeat request and an announcement per peer
+    assert_eq!(requests.len(), 4);
+    assert_eq!(nodes[&leader].replies_this_round(), 2);
+    for m in requests {
+        let receiver = nodes.get_mut(&m.get_receiver()).unwrap();
+        receiver.handle_incoming(m);
+        for reply in receiver.outgoing_messages() {
+            assert_eq!(nodes.get_mut(&leader).unwrap().try_handle(reply), Ok(()));
+        }
+    }
+    assert_eq!(nodes[&leader].replies_this_round(), 2);
+    assert!(!nodes[&leader].round_in_progress());
+    let ballot = nodes[&leader].get_current_leader_ballot();
+    hb_round(&mut nodes, None);
+    assert_eq!(nodes[&leader].get_current_leader_ballot(), ballot);
+}
+
+/// Verifies that a follower that missed a leader change adopts the new leader from its announcement, without a heartbeat round of its own.
+#[test]
+fn announce_leadership_converge_test() {
+    let mut nodes = create_cluster(5, |_| {});
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let old_leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    let stale = *nodes.keys().find(|pid| **pid != old_leader).unwrap();
+    // the remaining majority elects a new leader while the old leader and `stale` are cut off
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_filtered(&mut nodes, |m| {
+            [old_leader, stale].contains(&m.get_sender())
+                || [old_leader, stale].contains(&m.get_receiver())
+        });
+    }
+    let new_leader = *nodes
+        .keys()
+        .find(|pid| ![old_leader, stale].contains(pid))
+        .unwrap();
+    let new_leader = nodes[&new_leader]
+        .get_current_leader_ballot()
+        .expect("No new leader");
+    assert_ne!(new_leader.pid, old_leader);
+    assert_eq!(nodes[&stale].get_current_leader(), Some(old_leader));
+
+    // the announcement makes `stale` request a `Prepare` from the new leader
+
+    nodes
+        .get_mut(&new_leader.pid)
+        .unwrap()
+        .announce_leadership();
+    deliver_filtered(&mut nodes, |m| {
+        m.get_sender() == old_leader || m.get_receiver() == old_leader
+    });
+    assert_eq!(nodes[&stale].get_current_leader_ballot(), Some(new_leader));
+}
+
+/// Verifies that the ticks without a leader are accumulated until a leader is elected and after the leader is lost,
+/// and that a server that is paused, i.e., not ticked, does not accumulate any.
+#[test]
+fn leaderless_ticks_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    for _ in 0..3 {
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    assert_eq!(nodes[&1].total_leaderless_ticks(), 3);
+    assert_eq!(nodes[&1].leaderless_streak(), 3);
+    for _ in 0..5 {
+        hb_round(&mut nodes, None);
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    nodes.values_mut().for_each(|n| n.tick());
+    assert_eq!(nodes[&1].total_leaderless_ticks(), 3);
+    assert_eq!(nodes[&1].leaderless_streak(), 0);
+
+    // the followers lose the leader until they elect a new one, but only the ticked follower counts it
+    let mut followers: Vec<NodeId> = nodes
+        .keys()
+        .filter(|pid| **pid != leader)
+        .cloned()
+        .collect();
+    followers.sort_unstable();
+    let (follower, paused) = (followers[0], followers[1]);
+    let mut leaderless = 0;
+    for _ in 0..5 {
+        hb_round(&mut nodes, Some(leader));
+        nodes.get_mut(&follower).unwrap().tick();
+        if nodes[&follower].leaderless_streak() > 0 {
+            leaderless += 1;
+        }
+    }
+    assert!(leaderless > 0);
+    assert_eq!(nodes[&follower].total_leaderless_ticks(), 3 + leaderless);
+    assert_eq!(nodes[&follower].leaderless_streak(), 0);
+    assert_ne!(nodes[&follower].get_current_leader(), Some(leader));
+    assert_eq!(nodes[&paused].total_leaderless_ticks(), 3);
+    nodes.get_mut(&paused).unwrap().tick();
+    assert_eq!(nodes[&paused].total_leaderless_ticks(), 3);
+    assert_eq!(nodes[&paused].leaderless_streak(), 0);
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "checksum")]
+pub mod utils;
+
+use omnipaxos_core::{
+    omni_paxos::{OmniPaxos, OmniPaxosConfig},
+    util::crc32,
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use utils::Value;
+
+fn create_node() -> OmniPaxos<Value, (), MemoryStorage<Value, ()>> {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    op_config.build(MemoryStorage::default())
+}
+
+/// Verifies that `crc32()` computes the standard CRC32 (IEEE) checksum.
+#[test]
+fn crc32_test() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(
+        crc32(b"The quick brown fox jumps over the lazy dog"),
+        0x414F_A339
+    );
+}
+
+/// Verifies that `verify_checksum()` accepts intact messages and rejects and counts corrupted ones.
+#[test]
+fn verify_checksum_test() {
+    let mut node = create_node();
+    let bytes = b"serialized message".to_vec();
+    let checksum = crc32(&bytes);
+    assert!(node.verify_checksum(&bytes, checksum));
+    assert_eq!(node.corrupt_messages_dropped(), 0);
+
+    // a single flipped bit is detected
+    let mut corrupted = bytes.clone();
+    corrupted[3] ^= 1;
+    assert!(!node.verify_checksum(&corrupted, checksum));
+    // as is a truncated message
+    assert!(!node.verify_checksum(&bytes[..bytes.len() - 1], checksum));
+    assert_eq!(node.corrupt_messages_dropped(), 2);
+
+    node.reset_metrics();
+    assert_eq!(node.corrupt_messages_dropped(), 0);
+}
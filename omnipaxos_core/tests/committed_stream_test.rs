@@ -0,0 +1,318 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::Message,
+    omni_paxos::ReadResult,
+    util::{LogEntry, NodeId},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+use utils::{
+    cluster::{create_cluster, deliver_all, elect_leader, Node},
+    Value,
+};
+
+fn append_all(nodes: &mut BTreeMap<NodeId, Node>, leader: NodeId, values: std::ops::Range<u64>) {
+    for i in values {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    deliver_all(nodes, None);
+}
+
+fn decided_values(entries: Vec<(u64, LogEntry<Value, ()>)>) -> Vec<(u64, Value)> {
+    entries
+        .into_iter()
+        .map(|(idx, e)| match e {
+            LogEntry::Decided(v) => (idx, v),
+            e => panic!("Expected decided entry at {}, got: {:?}", idx, e),
+        })
+        .collect()
+}
+
+/// Verifies that a subscriber receives every decided entry once, from its starting offset.
+#[test]
+fn poll_committed_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..5);
+
+    let mut stream = nodes[&leader].subscribe_committed(2);
+    let polled = decided_values(nodes[&leader].poll_committed(&mut stream));
+    assert_eq!(polled, vec![(2, Value(2)), (3, Value(3)), (4, Value(4))]);
+    assert!(nodes[&leader].poll_committed(&mut stream).is_empty());
+
+    append_all(&mut nodes, leader, 5..7);
+    let polled = decided_values(nodes[&leader].poll_committed(&mut stream));
+    assert_eq!(polled, vec![(5, Value(5)), (6, Value(6))]);
+    assert_eq!(stream.next_idx(), 7);
+}
+
+/// Verifies that a subscriber that fell behind the compacted prefix first gets the compacted entry and then the rest of the log.
+#[test]
+fn poll_committed_compacted_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..6);
+    let mut stream = nodes[&leader].subscribe_committed(0);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(4))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+
+    let mut polled = nodes[&leader].poll_committed(&mut stream).into_iter();
+    match polled.next() {
+        Some((0, LogEntry::Trimmed(4))) => {}
+        e => panic!("Expected trimmed entry, got: {:?}", e),
+    }
+    assert_eq!(
+        decided_values(polled.collect()),
+        vec![(4, Value(4)), (5, Value(5))]
+    );
+    assert_eq!(stream.next_idx(), 6);
+}
+
+/// Verifies that `decided_since()` returns the decided entries together with the watermark after them, also if `from` has been compacted.
+#[test]
+fn decided_since_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..4);
+    let (entries, watermark) = nodes[&leader].decided_since(1);
+    assert_eq!(entries, vec![Value(1), Value(2), Value(3)]);
+    assert_eq!(watermark, 4);
+    assert_eq!(nodes[&leader].decided_since(watermark), (vec![], 4));
+    assert_eq!(nodes[&leader].decided_since(10), (vec![], 10));
+
+    append_all(&mut nodes, leader, 4..6);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(5))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+    // the entries before the compacted index are skipped
+    assert_eq!(nodes[&leader].decided_since(watermark), (vec![Value(5)], 6));
+}
+
+/// Verifies that `decided_iter()` yields every decided entry after the compacted prefix in order, across its read batches, and that `decided_len()` matches it.
+#[test]
+fn decided_iter_test() {
+    const NUM_ENTRIES: u64 = 2500;
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..NUM_ENTRIES);
+    // an entry that is not decided yet is not yielded
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(NUM_ENTRIES))
+        .expect("Failed to append");
+    let decided: Vec<Value> = nodes[&leader].decided_iter().collect();
+    assert_eq!(decided, (0..NUM_ENTRIES).map(Value).collect::<Vec<_>>());
+    assert_eq!(nodes[&leader].decided_len(), NUM_ENTRIES);
+
+    deliver_all(&mut nodes, None);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(1500))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+    let decided: Vec<Value> = nodes[&leader].decided_iter().collect();
+    assert_eq!(decided, (1500..=NUM_ENTRIES).map(Value).collect::<Vec<_>>());
+    assert_eq!(nodes[&leader].decided_len(), decided.len() as u64);
+}
+
+/// Verifies that the decide callback is called once per entry in index order, also for an entry of a failed leader that is decided by the next leader.
+#[test]
+fn on_decide_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..2);
+    let decided: HashMap<NodeId, Arc<Mutex<Vec<(u64, Value)>>>> = nodes
+        .iter_mut()
+        .map(|(pid, node)| {
+            let d = Arc::new(Mutex::new(vec![]));
+            let d_cb = d.clone();
+            node.set_on_decide(Box::new(move |idx, v| d_cb.lock().unwrap().push((idx, *v))));
+            (*pid, d)
+        })
+        .collect();
+    append_all(&mut nodes, leader, 2..3);
+
+    // the followers accept the next entry, but the leader fails before deciding it
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(3)).expect("Failed to append");
+    let msgs = leader_node.outgoing_messages();
+    nodes.remove(&leader);
+    for m in msgs {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    for node in nodes.values_mut() {
+        node.outgoing_messages();
+    }
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_all(&mut nodes, None);
+    }
+    let new_leader = nodes
+        .values()
+        .find_map(|n| n.get_current_leader())
+        .expect("No leader has been elected");
+    assert_ne!(new_leader, leader);
+    append_all(&mut nodes, new_leader, 4..5);
+
+    for pid in nodes.keys() {
+        let expected: Vec<(u64, Value)> = (2..5).map(|i| (i, Value(i))).collect();
+        assert_eq!(
+            *decided[pid].lock().unwrap(),
+            expected,
+            "Unexpected decided entries at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that the entries that a lagging follower decides at once are passed to the batch decide callback in batches of up to `coalesce_decides` entries, in index order.
+#[test]
+fn on_decide_batch_test() {
+    let mut nodes = create_cluster(3, |c| c.coalesce_decides = Some(2));
+    let leader = elect_leader(&mut nodes);
+    let batches: HashMap<NodeId, Arc<Mutex<Vec<(u64, Vec<Value>)>>>> = nodes
+        .iter_mut()
+        .map(|(pid, node)| {
+            let b = Arc::new(Mutex::new(vec![]));
+            let b_cb = b.clone();
+            node.set_on_decide_batch(Box::new(move |idx, entries| {
+                b_cb.lock().unwrap().push((idx, entries.to_vec()))
+            }));
+            (*pid, b)
+        })
+        .collect();
+    let lagging = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let lagging_node = nodes.remove(&lagging).unwrap();
+    append_all(&mut nodes, leader, 0..5);
+
+    nodes.insert(lagging, lagging_node);
+    nodes.get_mut(&lagging).unwrap().fail_recovery();
+    deliver_all(&mut nodes, None);
+    assert_eq!(
+        *batches[&lagging].lock().unwrap(),
+        vec![
+            (0, vec![Value(0), Value(1)]),
+            (2, vec![Value(2), Value(3)]),
+            (4, vec![Value(4)]),
+        ]
+    );
+    for (pid, b) in &batches {
+        let entries: Vec<(u64, Value)> = b
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(idx, entries)| (*idx..).zip(entries.iter().cloned()))
+            .collect();
+        let expected: Vec<(u64, Value)> = (0..5).map(|i| (i, Value(i))).collect();
+        assert_eq!(entries, expected, "Unexpected decided entries at {}", pid);
+        assert!(b
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, entries)| entries.len() <= 2));
+    }
+}
+
+/// Verifies that a follower only serves a read with a session token once it has decided up to the token.
+#[test]
+fn read_at_least_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    append_all(&mut nodes, leader, 0..2);
+    match nodes[&follower].read_at_least(1, 2) {
+        ReadResult::Ready(Some(LogEntry::Decided(v))) => assert_eq!(v, Value(1)),
+        r => panic!("Expected decided entry, got: {:?}", r),
+    }
+
+    // the write is decided at the leader before the follower learns about it
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(2)).expect("Failed to append");
+    let accepts = leader_node.outgoing_messages();
+    for m in accepts {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    let accepted: Vec<Message<Value, ()>> = nodes
+        .values_mut()
+        .flat_map(|n| n.outgoing_messages())
+        .collect();
+    for m in accepted {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    let token = nodes[&leader].get_decided_idx();
+    assert_eq!(token, 3);
+    match nodes[&follower].read_at_least(2, token) {
+        ReadResult::NotReady(2) => {}
+        r => panic!("Expected NotReady, got: {:?}", r),
+    }
+    deliver_all(&mut nodes, None);
+    match nodes[&follower].read_at_least(2, token) {
+        ReadResult::Ready(Some(LogEntry::Decided(v))) => assert_eq!(v, Value(2)),
+        r => panic!("Expected decided entry, got: {:?}", r),
+    }
+}
+
+/// Verifies that `read_page()` paginates through the decided log, and that a cursor into a prefix that was compacted between pages
+/// degrades to a trimmed entry followed by the remaining entries.
+#[test]
+fn read_page_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    append_all(&mut nodes, leader, 0..10);
+    let values = |entries: Vec<LogEntry<Value, ()>>| -> Vec<Value> {
+        entries
+            .into_iter()
+            .map(|e| match e {
+                LogEntry::Decided(v) => v,
+                e => panic!("Expected decided entry, got: {:?}", e),
+            })
+            .collect()
+    };
+
+    let (page, cursor) = nodes[&leader].read_page(None, 3);
+    assert_eq!(values(page), vec![Value(0), Value(1), Value(2)]);
+    let cursor = cursor.expect("No cursor for the next page");
+    assert_eq!(cursor.next_idx(), 3);
+
+    // the entries of the next page are trimmed in between
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(5))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+    let (page, cursor) = nodes[&leader].read_page(Some(cursor), 3);
+    let mut page = page.into_iter();
+    match page.next() {
+        Some(LogEntry::Trimmed(5)) => {}
+        e => panic!("Expected trimmed entry, got: {:?}", e),
+    }
+    assert_eq!(values(page.collect()), vec![Value(5)]);
+    let cursor = cursor.expect("No cursor for the next page");
+    assert_eq!(cursor.next_idx(), 6);
+
+    let (page, cursor) = nodes[&leader].read_page(Some(cursor), 100);
+    assert_eq!(values(page), vec![Value(6), Value(7), Value(8), Value(9)]);
+    // caught up with the decided log until more entries are decided
+    let (page, cursor) = nodes[&leader].read_page(cursor, 100);
+    assert!(page.is_empty());
+    append_all(&mut nodes, leader, 10..11);
+    let (page, _) = nodes[&leader].read_page(cursor, 100);
+    assert_eq!(values(page), vec![Value(10)]);
+}
@@ -0,0 +1,104 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    omni_paxos::{OmniPaxos, OmniPaxosConfig},
+    storage::EncodedEntry,
+    util::{EntryCodec, LogEntry, NodeId},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use std::collections::BTreeMap;
+use utils::cluster::{create_cluster_with_storage, deliver_all, elect_leader};
+
+type Node = OmniPaxos<EncodedEntry, (), MemoryStorage<EncodedEntry, ()>>;
+
+/// Compresses runs of the same byte into (count, byte) pairs.
+struct RunLengthCodec;
+
+impl EntryCodec for RunLengthCodec {
+    fn encode(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![];
+        for b in bytes {
+            match encoded.len() {
+                n if n >= 2 && encoded[n - 1] == *b && encoded[n - 2] < u8::MAX => {
+                    encoded[n - 2] += 1
+                }
+                _ => encoded.extend_from_slice(&[1, *b]),
+            }
+        }
+        encoded
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .chunks(2)
+            .flat_map(|run| std::iter::repeat(run[1]).take(run[0] as usize))
+            .collect()
+    }
+}
+
+/// Verifies that entries above the threshold are replicated encoded, entries below it as they are, and that reads decode both to the appended bytes.
+#[test]
+fn entry_codec_test() {
+    let mut nodes: BTreeMap<NodeId, Node> =
+        create_cluster_with_storage(3, |_| {}, MemoryStorage::default);
+    for node in nodes.values_mut() {
+        node.set_entry_codec(Box::new(RunLengthCodec), 16);
+    }
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|pid| **pid != leader).unwrap();
+
+    let large: Vec<u8> = [vec![b'{'; 1], vec![b' '; 300], vec![b'}'; 1]].concat();
+    let small = b"{}".to_vec();
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node
+        .append_encoded(large.clone())
+        .expect("Failed to append");
+    leader_node
+        .append_encoded(small.clone())
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+
+    let follower_node = &nodes[&follower];
+    assert_eq!(follower_node.get_decided_idx(), 2);
+    match follower_node.read(0) {
+        Some(LogEntry::Decided(e)) => {
+            assert!(e.encoded);
+            assert!(e.bytes.len() < large.len());
+        }
+        e => panic!("Unexpected entry: {:?}", e),
+    }
+    match follower_node.read(1) {
+        Some(LogEntry::Decided(e)) => assert_eq!(
+            e,
+            EncodedEntry {
+                bytes: small.clone(),
+                encoded: false
+            }
+        ),
+        e => panic!("Unexpected entry: {:?}", e),
+    }
+    for (idx, bytes) in vec![large, small].into_iter().enumerate() {
+        match follower_node.read_decoded(idx as u64) {
+            Some(LogEntry::Decided(e)) => {
+                assert_eq!(e.bytes, bytes);
+                assert!(!e.encoded);
+            }
+            e => panic!("Unexpected entry: {:?}", e),
+        }
+    }
+    let decoded: Vec<Vec<u8>> = follower_node
+        .decided_iter()
+        .map(|e| follower_node.decode_entry(&e).expect("No codec"))
+        .collect();
+    assert_eq!(decoded[1], b"{}".to_vec());
+
+    // without a codec, only entries that are not encoded can be decoded
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    let plain: Node = op_config.build(MemoryStorage::default());
+    let entries: Vec<EncodedEntry> = follower_node.decided_iter().collect();
+    assert_eq!(plain.decode_entry(&entries[0]), None);
+    assert_eq!(plain.decode_entry(&entries[1]), Some(b"{}".to_vec()));
+}
@@ -0,0 +1,56 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    omni_paxos::OmniPaxos,
+    storage::{EntryMeta, WithMeta},
+    util::{LogEntry, NodeId},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster_with_storage, deliver_all, elect_leader},
+    Value,
+};
+
+type Node = OmniPaxos<WithMeta<Value>, (), MemoryStorage<WithMeta<Value>, ()>>;
+
+/// Verifies that the metadata of an entry is decided together with it and returned by reads at all nodes.
+#[test]
+fn entry_meta_test() {
+    let mut nodes: BTreeMap<NodeId, Node> =
+        create_cluster_with_storage(3, |_| {}, MemoryStorage::default);
+    let leader = elect_leader(&mut nodes);
+    let meta = EntryMeta {
+        trace_id: u128::MAX - 1,
+        priority: 3,
+    };
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node
+        .append_with_meta(Value(1), meta)
+        .expect("Failed to append");
+    leader_node
+        .append(WithMeta::with(Value(2), EntryMeta::default()))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+
+    for (pid, node) in &nodes {
+        let decided: Vec<WithMeta<Value>> = node
+            .read_decided_suffix(0)
+            .expect("No decided entries")
+            .into_iter()
+            .map(|e| match e {
+                LogEntry::Decided(e) => e,
+                e => panic!("Expected decided entry at {}, got: {:?}", pid, e),
+            })
+            .collect();
+        assert_eq!(
+            decided,
+            vec![
+                WithMeta::with(Value(1), meta),
+                WithMeta::with(Value(2), EntryMeta::default())
+            ],
+            "Unexpected decided entries at {}",
+            pid
+        );
+    }
+}
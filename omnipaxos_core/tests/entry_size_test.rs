@@ -0,0 +1,58 @@
+pub mod utils;
+
+use omnipaxos_core::{omni_paxos::ProposeErr, util::LogEntry};
+use utils::{
+    cluster::{create_cluster, deliver_all, elect_leader},
+    Value,
+};
+
+/// Verifies that an entry larger than `max_entry_bytes` is rejected at the leader and at a follower before it enters the log,
+/// while smaller entries are still decided.
+#[test]
+fn max_entry_bytes_test() {
+    let mut nodes = create_cluster(3, |c| c.max_entry_bytes = Some(8));
+    for node in nodes.values_mut() {
+        // the value of an entry is used as its size
+        node.set_entry_sizer(Box::new(|v: &Value| v.0 as usize));
+    }
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|pid| **pid != leader).unwrap();
+
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    match leader_node.append(Value(10)) {
+        Err(ProposeErr::TooLarge { entry, size, limit }) => {
+            assert_eq!(entry, Value(10));
+            assert_eq!((size, limit), (10, 8));
+        }
+        res => panic!("Expected TooLarge error, got: {:?}", res),
+    }
+    assert!(matches!(
+        leader_node.append_tracked(Value(9)),
+        Err(ProposeErr::TooLarge { size: 9, .. })
+    ));
+    assert!(matches!(
+        nodes.get_mut(&follower).unwrap().append(Value(9)),
+        Err(ProposeErr::TooLarge { size: 9, .. })
+    ));
+    assert_eq!(nodes[&leader].outgoing_len(), 0);
+    assert_eq!(nodes[&follower].outgoing_len(), 0);
+
+    nodes
+        .get_mut(&follower)
+        .unwrap()
+        .append(Value(8))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_decided_idx(),
+            1,
+            "Unexpected decided index at {}",
+            pid
+        );
+        match node.read(0) {
+            Some(LogEntry::Decided(v)) => assert_eq!(v, Value(8)),
+            e => panic!("Expected decided entry at {}, got: {:?}", pid, e),
+        }
+    }
+}
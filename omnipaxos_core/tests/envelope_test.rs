@@ -0,0 +1,52 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::Envelope,
+    omni_paxos::{OmniPaxos, OmniPaxosConfig, VersionMismatch},
+    util::{MESSAGE_VERSION, MIN_MESSAGE_VERSION},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use utils::Value;
+
+fn create_node() -> OmniPaxos<Value, (), MemoryStorage<Value, ()>> {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    op_config.build(MemoryStorage::default())
+}
+
+/// Verifies that an envelope of the current version is decoded and opened to its payload.
+#[test]
+fn envelope_roundtrip_test() {
+    let node = create_node();
+    assert_eq!(
+        node.supported_message_versions(),
+        MIN_MESSAGE_VERSION..=MESSAGE_VERSION
+    );
+    let bytes = Envelope::new(vec![1, 2, 3]).to_bytes();
+    let envelope = Envelope::from_bytes(&bytes).expect("Failed to decode envelope");
+    assert_eq!(envelope.version, MESSAGE_VERSION);
+    assert_eq!(node.open_envelope(envelope), Ok(vec![1, 2, 3]));
+    assert_eq!(Envelope::from_bytes(&bytes[..1]), None);
+}
+
+/// Verifies that a message from a newer version is rejected with a `VersionMismatch` instead of being passed on.
+#[test]
+fn envelope_version_mismatch_test() {
+    let node = create_node();
+    let newer = Envelope {
+        version: MESSAGE_VERSION + 1,
+        payload: vec![1, 2, 3],
+    };
+    let bytes = newer.to_bytes();
+    let envelope = Envelope::from_bytes(&bytes).expect("Failed to decode envelope");
+    assert_eq!(
+        node.open_envelope(envelope),
+        Err(VersionMismatch {
+            version: MESSAGE_VERSION + 1,
+            min_version: MIN_MESSAGE_VERSION,
+            max_version: MESSAGE_VERSION,
+        })
+    );
+}
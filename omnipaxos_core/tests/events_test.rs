@@ -0,0 +1,407 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    messages::{
+        sequence_paxos::{AcceptSync, Decide, PaxosMessage, PaxosMsg, Prepare},
+        Message,
+    },
+    omni_paxos::{ConsensusEvent, OmniPaxosConfig, ReconfigurationRequest},
+    util::{LogEntry, NodeId},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader, hb_round, Node},
+    Value,
+};
+
+/// Creates a cluster of directly connected nodes that record `event_buffer_size` events and elects a leader.
+/// Returns the nodes and the pid of the leader.
+fn create_recording_cluster(event_buffer_size: usize) -> (BTreeMap<NodeId, Node>, NodeId) {
+    let mut nodes = create_cluster(3, |c| c.event_buffer_size = event_buffer_size);
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    (nodes, leader)
+}
+
+/// Verifies that the election, decisions and compaction are recorded in order with the tick at which they occurred.
+#[test]
+fn drain_events_test() {
+    let (mut nodes, leader) = create_recording_cluster(100);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    nodes.values_mut().for_each(|n| n.tick());
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(1))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+
+    for (pid, node) in nodes.iter_mut() {
+        let events = node.drain_events();
+        match events.as_slice() {
+            [ConsensusEvent::LeaderElected { ballot, .. }, ConsensusEvent::Decided { decided_idx: 1, .. }, ConsensusEvent::Compacted {
+                compacted_idx: 1, ..
+            }] => assert_eq!(ballot.pid, leader),
+            e => panic!("Unexpected events at {}: {:?}", pid, e),
+        }
+        assert!(events[0].tick() < events[2].tick());
+        assert!(node.drain_events().is_empty());
+    }
+}
+
+/// Verifies that an election is recorded at a follower that already promised the elected ballot to a `Prepare` of the new leader.
+#[test]
+fn leader_elected_after_prepare_test() {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 2;
+    op_config.peers = vec![1, 3];
+    op_config.configuration_id = 1;
+    op_config.event_buffer_size = 10;
+    let mut follower: Node = op_config.build(MemoryStorage::default());
+    let n = Ballot::with(1, 0, 1);
+    follower.handle_incoming(Message::SequencePaxos(PaxosMessage {
+        from: 1,
+        cluster_id: 0,
+        to: 2,
+        msg: PaxosMsg::Prepare(Prepare {
+            n,
+            decided_idx: 0,
+            n_accepted: Ballot::default(),
+            accepted_idx: 0,
+        }),
+    }));
+    // the leader election adopts the leader of the `Prepare`, so hinting it again is not recorded twice
+    assert!(!follower.hint_leader(n));
+    let elected: Vec<Ballot> = follower
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            ConsensusEvent::LeaderElected { ballot, .. } => Some(ballot),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(elected, vec![n]);
+}
+
+/// Verifies that only the latest `event_buffer_size` events are kept, and that no events are recorded by default.
+#[test]
+fn event_buffer_size_test() {
+    let (mut nodes, leader) = create_recording_cluster(2);
+    for i in 1..=3 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        deliver_all(&mut nodes, None);
+    }
+    let decided: Vec<u64> = nodes
+        .get_mut(&leader)
+        .unwrap()
+        .drain_events()
+        .into_iter()
+        .map(|e| match e {
+            ConsensusEvent::Decided { decided_idx, .. } => decided_idx,
+            e => panic!("Unexpected event: {:?}", e),
+        })
+        .collect();
+    assert_eq!(decided, vec![2, 3]);
+
+    let (mut nodes, _) = create_recording_cluster(0);
+    for node in nodes.values_mut() {
+        assert!(node.drain_events().is_empty());
+    }
+}
+
+/// Verifies that a follower that accepted entries from a leader whose entries were not chosen discards them for the log of the next leader,
+/// and records the truncation.
+#[test]
+fn log_truncated_test() {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 2;
+    op_config.peers = vec![1, 3];
+    op_config.configuration_id = 1;
+    op_config.event_buffer_size = 100;
+    let mut follower: Node = op_config.build(MemoryStorage::default());
+    let deliver = |follower: &mut Node, from: NodeId, msg: PaxosMsg<Value, ()>| {
+        follower.handle_incoming(Message::SequencePaxos(PaxosMessage {
+            from,
+            cluster_id: 0,
+            to: 2,
+            msg,
+        }));
+        follower.outgoing_messages()
+    };
+
+    // the first leader only gets its entries accepted by this follower
+    let first = Ballot::with(1, 0, 1);
+    deliver(
+        &mut follower,
+        1,
+        PaxosMsg::Prepare(Prepare {
+            n: first,
+            decided_idx: 0,
+            n_accepted: Ballot::default(),
+            accepted_idx: 0,
+        }),
+    );
+    deliver(
+        &mut follower,
+        1,
+        PaxosMsg::AcceptSync(AcceptSync {
+            n: first,
+            decided_snapshot: None,
+            suffix: vec![Value(1), Value(2)],
+            sync_idx: 0,
+            decided_idx: 0,
+            stopsign: None,
+        }),
+    );
+    assert!(follower
+        .drain_events()
+        .iter()
+        .all(|e| !matches!(e, ConsensusEvent::LogTruncated { .. })));
+
+    // the next leader has accepted other entries in a greater round
+    let second = Ballot::with(2, 0, 3);
+    deliver(
+        &mut follower,
+        3,
+        PaxosMsg::Prepare(Prepare {
+            n: second,
+            decided_idx: 0,
+            n_accepted: Ballot::with(1, 0, 3),
+            accepted_idx: 3,
+        }),
+    );
+    let replies = deliver(
+        &mut follower,
+        3,
+        PaxosMsg::AcceptSync(AcceptSync {
+            n: second,
+            decided_snapshot: None,
+            suffix: vec![Value(10), Value(11), Value(12)],
+            sync_idx: 0,
+            decided_idx: 0,
+            stopsign: None,
+        }),
+    );
+    match replies.as_slice() {
+        [Message::SequencePaxos(PaxosMessage {
+            msg: PaxosMsg::Accepted(accepted),
+            ..
+        })] => assert_eq!(accepted.accepted_idx, 3),
+        m => panic!("Expected Accepted, got: {:?}", m),
+    }
+    let truncated: Vec<u64> = follower
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            ConsensusEvent::LogTruncated { from_idx, .. } => Some(from_idx),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(truncated, vec![0]);
+    // the entries of the next leader are decided in place of the discarded ones
+    deliver(
+        &mut follower,
+        3,
+        PaxosMsg::Decide(Decide {
+            n: second,
+            decided_idx: 3,
+        }),
+    );
+    let decided: Vec<Value> = follower
+        .read_decided_suffix(0)
+        .expect("No decided entries")
+        .into_iter()
+        .map(|e| match e {
+            LogEntry::Decided(v) => v,
+            e => panic!("Unexpected entry: {:?}", e),
+        })
+        .collect();
+    assert_eq!(decided, vec![Value(10), Value(11), Value(12)]);
+}
+
+/// Verifies that a follower that accepted the same entries as the next leader in the same round is not resynced over them,
+/// and that no truncation is recorded.
+#[test]
+fn log_not_truncated_test() {
+    let (mut nodes, leader) = create_recording_cluster(100);
+    for i in 1..=2 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    // the followers accept the entries but the leader never learns it
+    deliver_filtered(&mut nodes, |m| {
+        matches!(
+            m,
+            Message::SequencePaxos(PaxosMessage {
+                msg: PaxosMsg::Accepted(_),
+                ..
+            })
+        )
+    });
+    for _ in 0..10 {
+        hb_round(&mut nodes, Some(leader));
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    let follower = nodes.keys().find(|pid| **pid != leader).unwrap();
+    let next_leader = nodes[follower]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    assert_ne!(next_leader, leader);
+    nodes
+        .get_mut(&next_leader)
+        .unwrap()
+        .append(Value(3))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, Some(leader));
+    for (pid, node) in nodes.iter_mut().filter(|(pid, _)| **pid != leader) {
+        assert!(
+            node.drain_events()
+                .iter()
+                .all(|e| !matches!(e, ConsensusEvent::LogTruncated { .. })),
+            "Truncation recorded at {}",
+            pid
+        );
+        let decided: Vec<Value> = node
+            .read_decided_suffix(0)
+            .expect("No decided entries")
+            .into_iter()
+            .map(|e| match e {
+                LogEntry::Decided(v) => v,
+                e => panic!("Unexpected entry: {:?}", e),
+            })
+            .collect();
+        assert_eq!(decided, vec![Value(1), Value(2), Value(3)]);
+    }
+}
+
+/// Verifies that a node of a new configuration whose other nodes never respond reports the stalled reconfiguration once, and that a configuration that elects a leader does not.
+#[test]
+fn reconfiguration_stalled_test() {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![4, 5];
+    op_config.configuration_id = 2;
+    op_config.event_buffer_size = 10;
+    op_config.reconfiguration_timeout = Some(5);
+    let mut node: Node = op_config.build(MemoryStorage::default());
+    for _ in 0..10 {
+        node.election_timeout();
+        node.outgoing_messages();
+        node.tick();
+    }
+    let stalled: Vec<(u64, u32)> = node
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            ConsensusEvent::ReconfigurationStalled {
+                tick,
+                configuration_id,
+            } => Some((tick, configuration_id)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stalled, vec![(5, 2)]);
+
+    let mut nodes = create_cluster(3, |c| {
+        c.configuration_id = 2;
+        c.event_buffer_size = 10;
+        c.reconfiguration_timeout = Some(5);
+    });
+    for _ in 0..10 {
+        hb_round(&mut nodes, None);
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    for node in nodes.values_mut() {
+        assert!(node.get_current_leader().is_some());
+        assert!(!node
+            .drain_events()
+            .iter()
+            .any(|e| matches!(e, ConsensusEvent::ReconfigurationStalled { .. })));
+    }
+}
+
+/// Verifies that a slow election of the initial configuration is not reported as a stalled reconfiguration, as no `StopSign` was decided before it.
+#[test]
+fn initial_configuration_not_stalled_test() {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    op_config.event_buffer_size = 10;
+    op_config.reconfiguration_timeout = Some(5);
+    let mut node: Node = op_config.build(MemoryStorage::default());
+    for _ in 0..10 {
+        node.election_timeout();
+        node.outgoing_messages();
+        node.tick();
+    }
+    assert_eq!(node.get_current_leader(), None);
+    assert!(stalled_configurations(&mut node).is_empty());
+}
+
+/// Returns the stalled configurations that `node` recorded.
+fn stalled_configurations(node: &mut Node) -> Vec<u32> {
+    node.drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            ConsensusEvent::ReconfigurationStalled {
+                configuration_id, ..
+            } => Some(configuration_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Verifies that the nodes of the old configuration report a stalled reconfiguration `reconfiguration_timeout` ticks after the `StopSign` was decided, unless it is confirmed.
+#[test]
+fn stopsign_stalled_test() {
+    for confirmed in [false, true] {
+        let mut nodes = create_cluster(3, |c| {
+            c.event_buffer_size = 10;
+            c.reconfiguration_timeout = Some(5);
+        });
+        let leader = elect_leader(&mut nodes);
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .reconfigure(ReconfigurationRequest::with(vec![1, 2, 4], None))
+            .expect("Failed to reconfigure");
+        deliver_all(&mut nodes, None);
+        for node in nodes.values_mut() {
+            assert!(node.is_reconfigured().is_some());
+            if confirmed {
+                node.confirm_reconfiguration();
+            }
+            for _ in 0..4 {
+                node.tick();
+            }
+            assert!(stalled_configurations(node).is_empty());
+        }
+        for node in nodes.values_mut() {
+            for _ in 0..5 {
+                node.tick();
+            }
+            let expected = if confirmed { vec![] } else { vec![2] };
+            assert_eq!(stalled_configurations(node), expected);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::{
+        sequence_paxos::{PaxosMessage, PaxosMsg},
+        Message,
+    },
+    omni_paxos::ProposeErr,
+    util::NodeId,
+};
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader, Node},
+    Value,
+};
+
+/// Delivers outgoing messages between the nodes until there are none left. Messages from and to `dark` are lost.
+/// Returns the number of entries that were sent to `dark` in `AcceptDecide` messages.
+fn deliver_counting_lost(nodes: &mut BTreeMap<NodeId, Node>, dark: NodeId) -> usize {
+    let mut lost_entries = 0;
+    deliver_filtered(nodes, |m| {
+        if let Message::SequencePaxos(PaxosMessage {
+            msg: PaxosMsg::AcceptDecide(acc),
+            to,
+            ..
+        }) = m
+        {
+            if *to == dark {
+                lost_entries += acc.entries.len();
+            }
+        }
+        m.get_sender() == dark || m.get_receiver() == dark
+    });
+    lost_entries
+}
+
+/// Verifies that the leader stops sending entries to a follower that went dark once its backlog exceeds `max_follower_backlog`,
+/// and synchronizes it with all entries once it is reachable again.
+#[test]
+fn max_follower_backlog_test() {
+    let mut nodes = create_cluster(3, |c| {
+        c.resend_timeout = 5;
+        c.max_follower_backlog = Some(5);
+    });
+    let leader = elect_leader(&mut nodes);
+    let dark = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let follower = *nodes
+        .keys()
+        .find(|pid| **pid != leader && **pid != dark)
+        .unwrap();
+
+    let mut sent_to_dark = 0;
+    for i in 0..50 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        sent_to_dark += deliver_counting_lost(&mut nodes, dark);
+        assert!(nodes[&leader].follower_backlog(dark) <= 5);
+    }
+    assert_eq!(sent_to_dark, 5);
+    assert_eq!(nodes[&leader].follower_backlog(dark), 0);
+    assert_eq!(nodes[&leader].follower_backlog(follower), 0);
+    assert_eq!(nodes[&follower].get_decided_idx(), 50);
+
+    // the leader resends its Prepare and synchronizes the follower once it is reachable again
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&dark].get_decided_idx(), 50);
+}
+
+/// Verifies that `append()` returns `ProposeErr::WouldBlock` once `max_inflight_entries` entries are not decided yet,
+/// and accepts entries again once they are decided.
+#[test]
+fn max_inflight_entries_test() {
+    let mut nodes = create_cluster(3, |c| c.max_inflight_entries = Some(3));
+    let leader = elect_leader(&mut nodes);
+    assert_eq!(nodes[&leader].inflight_available(), Some(3));
+
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    for i in 0..3 {
+        leader_node.append(Value(i)).expect("Failed to append");
+    }
+    assert_eq!(leader_node.inflight_count(), 3);
+    assert_eq!(leader_node.inflight_available(), Some(0));
+    match leader_node.append(Value(3)) {
+        Err(ProposeErr::WouldBlock(entry)) => assert_eq!(entry, Value(3)),
+        res => panic!("Expected WouldBlock error, got: {:?}", res),
+    }
+    assert!(matches!(
+        leader_node.append_tracked(Value(3)),
+        Err(ProposeErr::WouldBlock(Value(3)))
+    ));
+
+    // the window is freed once the entries are decided
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].get_decided_idx(), 3);
+    assert_eq!(nodes[&leader].inflight_available(), Some(3));
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(3))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].get_decided_idx(), 4);
+}
+
+/// Verifies that `inflight_count()` and `inflight_bytes()` only cover the entries that are accepted but not decided yet, at the leader and at the followers.
+#[test]
+fn inflight_bytes_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let size_of = |v: &Value| v.0 as usize;
+    for i in 1..=3 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    assert_eq!(nodes[&leader].inflight_count(), 3);
+    assert_eq!(nodes[&leader].inflight_bytes(size_of), 6);
+
+    // the followers accept the entries, but the leader never learns of it and cannot decide them
+    deliver_filtered(&mut nodes, |m| m.get_receiver() == leader);
+    assert_eq!(nodes[&follower].inflight_count(), 3);
+    assert_eq!(nodes[&follower].inflight_bytes(size_of), 6);
+
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(4))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    for node in nodes.values() {
+        assert_eq!(node.get_decided_idx(), 4);
+        assert_eq!(node.inflight_count(), 0);
+        assert_eq!(node.inflight_bytes(size_of), 0);
+    }
+}
+
+/// Verifies that the proposals appended while the leader is in the prepare phase occupy the window of `max_inflight_entries`.
+#[test]
+fn inflight_prepare_test() {
+    let mut nodes = create_cluster(3, |c| c.max_inflight_entries = Some(2));
+    // the promises are lost, such that the elected leader stays in the prepare phase
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_filtered(&mut nodes, |m| matches!(m, Message::SequencePaxos(_)));
+    }
+    // only the leader itself knows of its leadership before the prepare phase completes
+    let leader = *nodes
+        .keys()
+        .find(|pid| nodes[*pid].get_current_leader() == Some(**pid))
+        .expect("No leader");
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(0)).expect("Failed to append");
+    leader_node
+        .append_tracked(Value(1))
+        .expect("Failed to append");
+    assert_eq!(leader_node.inflight_count(), 0);
+    assert_eq!(leader_node.inflight_available(), Some(0));
+    assert!(matches!(
+        leader_node.append(Value(2)),
+        Err(ProposeErr::WouldBlock(Value(2)))
+    ));
+}
+
+/// Verifies that the proposals forwarded to a leader with a full window of `max_inflight_entries` are returned to the follower that forwarded them.
+#[test]
+fn inflight_forwarded_test() {
+    let mut nodes = create_cluster(3, |c| c.max_inflight_entries = Some(2));
+    let leader = elect_leader(&mut nodes);
+    let follower = if leader == 1 { 2 } else { 1 };
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(0)).expect("Failed to append");
+    leader_node.append(Value(1)).expect("Failed to append");
+    // the follower does not know that the window of the leader is full
+    nodes
+        .get_mut(&follower)
+        .unwrap()
+        .append(Value(2))
+        .expect("Failed to append");
+    // the forward reaches the leader before its entries are decided
+    let msgs: Vec<Message<Value, ()>> = nodes.get_mut(&follower).unwrap().outgoing_messages();
+    for m in msgs {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].get_decided_idx(), 2);
+    assert_eq!(
+        nodes.get_mut(&follower).unwrap().take_rejected(),
+        vec![Value(2)]
+    );
+}
@@ -0,0 +1,72 @@
+#[cfg(feature = "framing")]
+use bytes::BytesMut;
+#[cfg(feature = "framing")]
+use omnipaxos_core::messages::{Envelope, FrameTooLarge, FramedCodec, FramingError};
+#[cfg(feature = "framing")]
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Verifies that frames that arrive split across reads, or several in one read, are decoded whole and in order.
+#[cfg(feature = "framing")]
+#[test]
+fn partial_reads_test() {
+    let mut codec = FramedCodec::with(1024);
+    let payloads: Vec<Vec<u8>> = vec![
+        Envelope::new(vec![1, 2, 3]).to_bytes(),
+        vec![],
+        Envelope::new(vec![7; 100]).to_bytes(),
+    ];
+    let mut stream = BytesMut::new();
+    for p in &payloads {
+        codec.encode(&p[..], &mut stream).expect("Failed to encode");
+    }
+
+    // deliver the stream in reads of 3 bytes
+    let mut buf = BytesMut::new();
+    let mut decoded = vec![];
+    for read in stream.chunks(3) {
+        buf.extend_from_slice(read);
+        while let Some(frame) = codec.decode(&mut buf).expect("Failed to decode") {
+            decoded.push(frame.to_vec());
+        }
+    }
+    assert_eq!(decoded, payloads);
+    assert!(buf.is_empty());
+
+    // two frames in a single read
+    let mut buf = BytesMut::new();
+    codec.encode(&[1][..], &mut buf).unwrap();
+    codec.encode(&[2, 2][..], &mut buf).unwrap();
+    assert_eq!(codec.decode(&mut buf).unwrap().unwrap()[..], [1]);
+    assert_eq!(codec.decode(&mut buf).unwrap().unwrap()[..], [2, 2]);
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+}
+
+/// Verifies that frames longer than the maximum are rejected when encoding, and when decoding as soon as the length prefix has arrived.
+#[cfg(feature = "framing")]
+#[test]
+fn oversized_frame_test() {
+    let mut codec = FramedCodec::with(8);
+    let too_large = FrameTooLarge {
+        len: 9,
+        max_frame_len: 8,
+    };
+    let mut buf = BytesMut::new();
+    assert!(matches!(
+        codec.encode(&[0; 9][..], &mut buf),
+        Err(FramingError::TooLarge(e)) if e == too_large
+    ));
+    assert!(buf.is_empty());
+
+    let mut buf = BytesMut::new();
+    FramedCodec::with(1024)
+        .encode(&[0; 100][..], &mut buf)
+        .unwrap();
+    buf.truncate(5);
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(FramingError::TooLarge(FrameTooLarge {
+            len: 100,
+            max_frame_len: 8
+        }))
+    ));
+}
@@ -0,0 +1,79 @@
+pub mod utils;
+
+use omnipaxos_core::util::{NodeId, COMMIT_LATENCY_BUCKETS};
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, elect_leader, Node},
+    Value,
+};
+
+/// Delivers the outgoing messages of `pid` once, without the responses.
+fn deliver_from(nodes: &mut BTreeMap<NodeId, Node>, pid: NodeId) {
+    let msgs = nodes.get_mut(&pid).unwrap().outgoing_messages();
+    for m in msgs {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+}
+
+/// Verifies that the commit latency of entries is counted in the bucket of the number of ticks between accepting and deciding them.
+#[test]
+fn commit_latency_histogram_test() {
+    let mut nodes = create_cluster(3, |c| c.resend_timeout = 100);
+    let leader = elect_leader(&mut nodes);
+
+    // decided within the same tick
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    // the Accepted replies are delayed by three ticks
+    for i in 2..=3 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    deliver_from(&mut nodes, leader);
+    for _ in 0..3 {
+        nodes.values_mut().for_each(|n| n.tick());
+    }
+    deliver_all(&mut nodes, None);
+
+    let mut expected = [0; COMMIT_LATENCY_BUCKETS];
+    expected[0] = 1;
+    expected[2] = 2;
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.commit_latency_histogram(),
+            expected,
+            "Unexpected histogram at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that `reset_metrics()` clears the histogram without affecting the log.
+#[test]
+fn reset_metrics_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(1)).expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].commit_latency_histogram()[0], 1);
+
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.reset_metrics();
+    assert_eq!(
+        leader_node.commit_latency_histogram(),
+        [0; COMMIT_LATENCY_BUCKETS]
+    );
+    assert_eq!(leader_node.get_decided_idx(), 1);
+    assert_eq!(leader_node.get_current_leader(), Some(leader));
+    leader_node.append(Value(2)).expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].commit_latency_histogram()[0], 1);
+}
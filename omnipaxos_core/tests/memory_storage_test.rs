@@ -0,0 +1,157 @@
+pub mod utils;
+
+use omnipaxos_core::{messages::Message, omni_paxos::ProposeErr, storage::StorageErr};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use std::sync::{Arc, Mutex};
+use utils::{
+    cluster::{create_cluster_with_storage, deliver_all, deliver_filtered, elect_leader},
+    Value,
+};
+
+/// Verifies that proposals are rejected once the storage is full, and accepted again after trimming, which calls the eviction hook.
+#[test]
+fn max_entries_test() {
+    const MAX_ENTRIES: u64 = 5;
+    let evicted = Arc::new(Mutex::new(vec![]));
+    let e = evicted.clone();
+    let mut nodes = create_cluster_with_storage(
+        3,
+        |_| {},
+        || {
+            let mut storage: MemoryStorage<Value, ()> = MemoryStorage::default();
+            storage.set_max_entries(Some(MAX_ENTRIES));
+            let e = e.clone();
+            storage.set_eviction_hook(Arc::new(move |entries: &[Value]| {
+                e.lock().unwrap().extend_from_slice(entries)
+            }));
+            storage
+        },
+    );
+    let leader = elect_leader(&mut nodes);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    for i in 0..MAX_ENTRIES {
+        leader_node.append(Value(i)).expect("Failed to append");
+    }
+    match leader_node.append(Value(MAX_ENTRIES)) {
+        Err(ProposeErr::StorageFull(v)) => assert_eq!(v, Value(MAX_ENTRIES)),
+        r => panic!("Expected StorageFull, got: {:?}", r),
+    }
+    assert_eq!(leader_node.health().storage_error, Some(StorageErr::Full));
+    deliver_all(&mut nodes, None);
+    for node in nodes.values() {
+        assert_eq!(node.get_decided_idx(), MAX_ENTRIES);
+    }
+
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .trim(Some(2))
+        .expect("Failed to trim");
+    deliver_all(&mut nodes, None);
+    // every server evicts the trimmed entries
+    let expected: Vec<Value> = (0..3).flat_map(|_| vec![Value(0), Value(1)]).collect();
+    assert_eq!(*evicted.lock().unwrap(), expected);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(5)).expect("Failed to append");
+    assert_eq!(leader_node.health().storage_error, None);
+    leader_node.append(Value(6)).expect("Failed to append");
+    assert!(matches!(
+        leader_node.append(Value(7)),
+        Err(ProposeErr::StorageFull(_))
+    ));
+}
+
+/// Verifies that proposals are rejected once the total size of the entries exceeds the limit of the storage.
+#[test]
+fn max_bytes_test() {
+    let mut nodes = create_cluster_with_storage(
+        3,
+        |_| {},
+        || {
+            let mut storage: MemoryStorage<Value, ()> = MemoryStorage::default();
+            storage.set_max_bytes(10, |v: &Value| v.0 as usize);
+            storage
+        },
+    );
+    let leader = elect_leader(&mut nodes);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(4)).expect("Failed to append");
+    leader_node.append(Value(6)).expect("Failed to append");
+    assert!(matches!(
+        leader_node.append(Value(1)),
+        Err(ProposeErr::StorageFull(_))
+    ));
+    // entries without a size still fit
+    leader_node.append(Value(0)).expect("Failed to append");
+}
+
+/// Verifies that the proposals forwarded to a leader whose storage is full are returned to the follower that forwarded them.
+#[test]
+fn forwarded_proposals_rejected_test() {
+    let mut nodes = create_cluster_with_storage(
+        3,
+        |_| {},
+        || {
+            let mut storage: MemoryStorage<Value, ()> = MemoryStorage::default();
+            storage.set_max_entries(Some(3));
+            storage
+        },
+    );
+    let leader = elect_leader(&mut nodes);
+    let follower = if leader == 1 { 2 } else { 1 };
+    nodes
+        .get_mut(&follower)
+        .unwrap()
+        .append(Value(0))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(1)).expect("Failed to append");
+    leader_node.append(Value(2)).expect("Failed to append");
+    nodes
+        .get_mut(&follower)
+        .unwrap()
+        .append(Value(3))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    let follower_node = nodes.get_mut(&follower).unwrap();
+    assert_eq!(follower_node.take_rejected(), vec![Value(3)]);
+    assert!(follower_node.take_rejected().is_empty());
+    for node in nodes.values() {
+        assert_eq!(node.get_decided_idx(), 3);
+    }
+}
+
+/// Verifies that the proposals that are pending while the leader is in the prepare phase count towards the limit of the storage.
+#[test]
+fn pending_proposals_count_test() {
+    const MAX_ENTRIES: u64 = 3;
+    let mut nodes = create_cluster_with_storage(
+        3,
+        |_| {},
+        || {
+            let mut storage: MemoryStorage<Value, ()> = MemoryStorage::default();
+            storage.set_max_entries(Some(MAX_ENTRIES));
+            storage
+        },
+    );
+    // the promises are lost, such that the elected leader stays in the prepare phase
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_filtered(&mut nodes, |m| matches!(m, Message::SequencePaxos(_)));
+    }
+    // only the leader itself knows of its leadership before the prepare phase completes
+    let leader = *nodes
+        .keys()
+        .find(|pid| nodes[*pid].get_current_leader() == Some(**pid))
+        .expect("No leader");
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    for i in 0..MAX_ENTRIES {
+        leader_node.append(Value(i)).expect("Failed to append");
+    }
+    assert!(leader_node.read(0).is_none());
+    assert!(matches!(
+        leader_node.append(Value(MAX_ENTRIES)),
+        Err(ProposeErr::StorageFull(_))
+    ));
+}
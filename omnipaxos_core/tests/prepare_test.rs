@@ -0,0 +1,231 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    messages::{
+        sequence_paxos::{PaxosMessage, PaxosMsg, Prepare},
+        Message,
+    },
+    util::{LogEntry, NodeId},
+};
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, Node},
+    Value,
+};
+
+/// Delivers outgoing messages between the nodes until there are none left, except for the messages of Sequence Paxos, which are returned.
+fn deliver_holding_paxos(nodes: &mut BTreeMap<NodeId, Node>) -> Vec<Message<Value, ()>> {
+    let mut held = vec![];
+    deliver_filtered(nodes, |m| match m {
+        Message::SequencePaxos(_) => {
+            held.push(m.clone());
+            true
+        }
+        _ => false,
+    });
+    held
+}
+
+/// Verifies that a follower that has elected a leader rejects a simultaneous `Prepare` with a smaller ballot and replies with the ballot of
+/// its leader, instead of promising both candidates in turn.
+#[test]
+fn dueling_prepare_test() {
+    let all_pids: Vec<NodeId> = vec![1, 2, 3];
+    let mut nodes = create_cluster(3, |_| {});
+    // elect a leader, but hold back its Prepare
+    let mut held = vec![];
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        held.append(&mut deliver_holding_paxos(&mut nodes));
+    }
+    // only the leader itself knows about its election without the Prepare
+    let leader = nodes
+        .values()
+        .find_map(|n| n.get_current_leader_ballot())
+        .expect("No leader has been elected");
+    let followers: Vec<NodeId> = all_pids
+        .iter()
+        .filter(|p| **p != leader.pid)
+        .cloned()
+        .collect();
+    let (stale_pid, follower) = (followers[0], followers[1]);
+    let stale = Ballot::with(leader.n, leader.priority, stale_pid);
+    assert!(stale < leader);
+
+    // the stale candidate's Prepare arrives first
+    let stale_prepare = PaxosMessage {
+        from: stale.pid,
+        cluster_id: 0,
+        to: follower,
+        msg: PaxosMsg::Prepare(Prepare {
+            n: stale,
+            decided_idx: 0,
+            n_accepted: Ballot::default(),
+            accepted_idx: 0,
+        }),
+    };
+    let follower_node = nodes.get_mut(&follower).unwrap();
+    follower_node.handle_incoming(Message::SequencePaxos(stale_prepare));
+    let replies = follower_node.outgoing_messages();
+    assert_eq!(replies.len(), 1);
+    match &replies[0] {
+        Message::SequencePaxos(PaxosMessage {
+            to,
+            msg: PaxosMsg::PrepareNack(n),
+            ..
+        }) => {
+            assert_eq!(*to, stale.pid);
+            assert_eq!(*n, leader);
+        }
+        m => panic!("Expected PrepareNack, got: {:?}", m),
+    }
+
+    // the Prepare of the elected leader is still promised
+    for m in held {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    deliver_all(&mut nodes, None);
+    nodes
+        .get_mut(&leader.pid)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    match nodes[&follower].read(0) {
+        Some(LogEntry::Decided(v)) => assert_eq!(v, Value(1)),
+        e => panic!("Expected decided entry at the follower, got: {:?}", e),
+    }
+}
+
+/// Verifies that `outgoing_iter()` takes all outgoing messages, both when electing and synchronizing a leader and when replicating entries.
+#[test]
+fn outgoing_iter_test() {
+    let all_pids: Vec<NodeId> = vec![1, 2, 3];
+    let mut nodes = create_cluster(3, |_| {});
+    // take the messages of each server with the iterator and deliver them one at a time
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        loop {
+            let mut delivered = false;
+            for pid in all_pids.iter() {
+                let msgs: Vec<Message<Value, ()>> =
+                    nodes.get_mut(pid).unwrap().outgoing_iter().collect();
+                for m in msgs {
+                    delivered = true;
+                    nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+                }
+            }
+            if !delivered {
+                break;
+            }
+        }
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    for i in 0..3 {
+        leader_node.append(Value(i)).expect("Failed to append");
+    }
+    let expected = leader_node.outgoing_len();
+    let msgs: Vec<Message<Value, ()>> = leader_node.outgoing_iter().collect();
+    assert_eq!(msgs.len(), expected);
+    assert_eq!(leader_node.outgoing_len(), 0);
+    assert!(leader_node.outgoing_messages().is_empty());
+    for m in msgs {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    deliver_all(&mut nodes, None);
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_decided_idx(),
+            3,
+            "Unexpected decided index at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a new leader in a 5-node cluster starts the Accept phase as soon as it has a majority of promises (including its own),
+/// and synchronizes the delayed followers once their promises arrive.
+#[test]
+fn majority_promises_test() {
+    let mut nodes = create_cluster(5, |_| {});
+    let mut held = vec![];
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        held.append(&mut deliver_holding_paxos(&mut nodes));
+    }
+    let leader = nodes
+        .values()
+        .find_map(|n| n.get_current_leader_ballot())
+        .expect("No leader has been elected")
+        .pid;
+    // deliver the Prepares and hold back the Promises
+    for m in held {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    let mut promises: Vec<Message<Value, ()>> = nodes
+        .values_mut()
+        .flat_map(|n| n.outgoing_messages())
+        .collect();
+    assert_eq!(promises.len(), 4);
+    let delayed = promises.split_off(2);
+
+    let mut syncs = vec![];
+    for (i, m) in promises.into_iter().enumerate() {
+        let fast = m.get_sender();
+        let leader_node = nodes.get_mut(&leader).unwrap();
+        leader_node.handle_incoming(m);
+        syncs = leader_node.outgoing_messages();
+        let sync_pids: Vec<NodeId> = syncs
+            .iter()
+            .filter_map(|m| match m {
+                Message::SequencePaxos(PaxosMessage {
+                    to,
+                    msg: PaxosMsg::AcceptSync(_),
+                    ..
+                }) => Some(*to),
+                _ => None,
+            })
+            .collect();
+        if i == 0 {
+            assert!(
+                sync_pids.is_empty(),
+                "AcceptSync before a majority of promises"
+            );
+        } else {
+            assert_eq!(sync_pids.len(), 2);
+            assert!(sync_pids.contains(&fast));
+        }
+    }
+    for m in syncs {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+
+    // the entry is decided by the leader and the two fast followers
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    leader_node.append(Value(1)).expect("Failed to append");
+    let accepts = leader_node.outgoing_messages();
+    assert_eq!(accepts.len(), 2);
+    for m in accepts {
+        nodes.get_mut(&m.get_receiver()).unwrap().handle_incoming(m);
+    }
+    deliver_all(&mut nodes, None);
+    assert_eq!(nodes[&leader].get_decided_idx(), 1);
+
+    for m in delayed {
+        let straggler = m.get_receiver();
+        nodes.get_mut(&straggler).unwrap().handle_incoming(m);
+    }
+    deliver_all(&mut nodes, None);
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_decided_idx(),
+            1,
+            "Unexpected decided index at {}",
+            pid
+        );
+    }
+}
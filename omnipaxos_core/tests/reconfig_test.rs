@@ -2,11 +2,18 @@ pub mod utils;
 
 #[cfg(feature = "hocon_config")]
 use kompact::prelude::{promise, Ask, FutureCollection};
-use omnipaxos_core::omni_paxos::ReconfigurationRequest;
+use omnipaxos_core::{
+    omni_paxos::ReconfigurationRequest,
+    util::{LogEntry, NodeId},
+};
 #[cfg(feature = "hocon_config")]
 use serial_test::serial;
+use utils::{
+    cluster::{create_cluster, deliver_all, elect_leader},
+    Value, SS_METADATA,
+};
 #[cfg(feature = "hocon_config")]
-use utils::{TestConfig, TestSystem, Value, SS_METADATA};
+use utils::{TestConfig, TestSystem};
 
 #[cfg(feature = "hocon_config")]
 /// Verifies that the decided StopSign is correct and error is returned when trying to append after decided StopSign.
@@ -84,3 +91,57 @@ fn reconfig_test() {
         Err(e) => panic!("Error on kompact shutdown: {}", e),
     };
 }
+
+/// Verifies that the metadata of a reconfiguration is decided together with the StopSign at every node.
+#[test]
+fn reconfig_metadata_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+
+    let new_config: Vec<NodeId> = vec![2, 3, 4, 5];
+    let metadata = vec![SS_METADATA, 1, 2, 3];
+    let rc = ReconfigurationRequest::with(new_config.clone(), Some(metadata.clone()));
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .reconfigure(rc)
+        .expect("Failed to reconfigure");
+    deliver_all(&mut nodes, None);
+
+    for (pid, node) in &nodes {
+        match node.read(0) {
+            Some(LogEntry::StopSign(ss)) => {
+                assert_eq!(ss.nodes, new_config);
+                assert_eq!(ss.config_id, 2);
+                assert_eq!(ss.metadata, Some(metadata.clone()));
+            }
+            e => panic!("Expected decided StopSign at {}, got: {:?}", pid, e),
+        }
+        assert_eq!(
+            node.is_reconfigured().and_then(|ss| ss.metadata),
+            Some(metadata.clone())
+        );
+    }
+}
+
+/// Verifies that the membership diff of a decided StopSign contains the added servers and the removed ones, including the local server.
+#[test]
+fn membership_diff_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let rc = ReconfigurationRequest::with(vec![5, 2, 4, 3], None);
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .reconfigure(rc)
+        .expect("Failed to reconfigure");
+    deliver_all(&mut nodes, None);
+
+    for (pid, node) in &nodes {
+        let ss = node
+            .is_reconfigured()
+            .unwrap_or_else(|| panic!("No decided StopSign at {}", pid));
+        assert_eq!(node.membership_diff(&ss.nodes), (vec![4, 5], vec![1]));
+    }
+    assert_eq!(nodes[&2].membership_diff(&[1, 2, 3]), (vec![], vec![]));
+}
@@ -0,0 +1,31 @@
+pub mod utils;
+
+use omnipaxos_core::omni_paxos::ProposeErr;
+use utils::{
+    cluster::{create_cluster, elect_leader},
+    Value,
+};
+
+/// Verifies that a follower that rejects a tracked proposal records its leadership state at the time of the rejection.
+#[test]
+fn last_rejection_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    assert!(nodes[&1].last_rejection().is_none());
+    let leader = elect_leader(&mut nodes);
+    let leader_ballot = nodes[&leader]
+        .get_current_leader_ballot()
+        .expect("No leader");
+    let follower = if leader == 1 { 2 } else { 1 };
+    let follower_node = nodes.get_mut(&follower).unwrap();
+    match follower_node.append_tracked(Value(0)) {
+        Err(ProposeErr::NotLeader(v, Some(hint))) => {
+            assert_eq!(v, Value(0));
+            assert_eq!(hint.ballot, leader_ballot);
+        }
+        res => panic!("Expected NotLeader error, got: {:?}", res),
+    }
+    let context = follower_node.last_rejection().expect("No rejection");
+    assert_eq!(context.leader, Some(leader));
+    assert_eq!(context.ballot, leader_ballot);
+    assert!(nodes[&leader].last_rejection().is_none());
+}
@@ -0,0 +1,149 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    messages::{sequence_paxos::PaxosMsg, Message},
+    omni_paxos::ConsensusEvent,
+    util::{LogEntry, NodeId},
+};
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader, Node},
+    Value,
+};
+
+const RESEND_TIMEOUT: u64 = 5;
+
+/// Appends `v` at the leader and delivers all messages, except the first `AcceptDecide` to `follower`.
+fn append_and_drop_accept(
+    nodes: &mut BTreeMap<NodeId, Node>,
+    leader: NodeId,
+    follower: NodeId,
+    v: Value,
+) {
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(v)
+        .expect("Failed to append");
+    let mut dropped = false;
+    deliver_filtered(nodes, |m| match m {
+        Message::SequencePaxos(p) if p.to == follower && !dropped => {
+            if let PaxosMsg::AcceptDecide(_) = p.msg {
+                dropped = true;
+            }
+            dropped
+        }
+        _ => false,
+    });
+    assert!(dropped, "No AcceptDecide was sent to the follower");
+}
+
+/// Verifies that an entry is eventually decided at a follower even if the `AcceptDecide` carrying it was lost.
+#[test]
+fn resend_accept_test() {
+    let mut nodes = create_cluster(3, |c| c.resend_timeout = RESEND_TIMEOUT);
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|p| **p != leader).unwrap();
+    append_and_drop_accept(&mut nodes, leader, follower, Value(1));
+
+    for _ in 0..(3 * RESEND_TIMEOUT) {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(&mut nodes, None);
+    }
+    match nodes[&follower].read(0) {
+        Some(LogEntry::Decided(v)) => assert_eq!(v, Value(1)),
+        e => panic!("Expected decided entry at the follower, got: {:?}", e),
+    }
+}
+
+/// Verifies that a lagging follower catches up by requesting it from the leader, without the leader resending anything.
+#[test]
+fn catchup_request_test() {
+    let mut nodes = create_cluster(3, |c| c.resend_timeout = RESEND_TIMEOUT);
+    let leader = elect_leader(&mut nodes);
+    let follower = *nodes.keys().find(|p| **p != leader).unwrap();
+    append_and_drop_accept(&mut nodes, leader, follower, Value(1));
+    assert!(nodes[&follower].read(0).is_none());
+
+    for _ in 0..(2 * RESEND_TIMEOUT) {
+        nodes.get_mut(&follower).unwrap().tick();
+        deliver_all(&mut nodes, None);
+    }
+    match nodes[&follower].read(0) {
+        Some(LogEntry::Decided(v)) => assert_eq!(v, Value(1)),
+        e => panic!("Expected decided entry at the follower, got: {:?}", e),
+    }
+}
+
+/// Verifies that `tick()` closes the batched `AcceptDecide` of a follower, so that entries appended after it are sent in a new message.
+#[cfg(feature = "batch_accept")]
+#[test]
+fn flush_batch_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    let mut accepts_per_follower = |tick: bool| {
+        leader_node.append(Value(1)).expect("Failed to append");
+        if tick {
+            leader_node.tick();
+        }
+        leader_node.append(Value(2)).expect("Failed to append");
+        let accepts = leader_node
+            .outgoing_messages()
+            .into_iter()
+            .filter(|m| {
+                matches!(
+                    m,
+                    Message::SequencePaxos(p) if matches!(p.msg, PaxosMsg::AcceptDecide(_))
+                )
+            })
+            .count();
+        accepts / 2
+    };
+    assert_eq!(accepts_per_follower(false), 1);
+    assert_eq!(accepts_per_follower(true), 2);
+}
+
+/// Verifies that a leader that does not get promises from a majority reports the timed out `Prepare` once, and that it still resends the `Prepare`.
+#[test]
+fn prepare_timeout_test() {
+    let mut nodes = create_cluster(3, |c| {
+        c.resend_timeout = RESEND_TIMEOUT;
+        c.prepare_timeout = Some(2 * RESEND_TIMEOUT);
+        c.event_buffer_size = 10;
+    });
+    // the leader is elected, but no Sequence Paxos message is delivered
+    for _ in 0..10 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_filtered(&mut nodes, |m| matches!(m, Message::SequencePaxos(_)));
+    }
+    let leader = *nodes
+        .keys()
+        .find(|pid| nodes[*pid].get_current_leader() == Some(**pid))
+        .expect("No leader has been elected");
+    let leader_node = nodes.get_mut(&leader).unwrap();
+    let ballot = leader_node.get_current_leader_ballot().unwrap();
+    leader_node.drain_events();
+    let mut resent = 0;
+    for _ in 0..(3 * RESEND_TIMEOUT) {
+        leader_node.tick();
+        resent += leader_node
+            .outgoing_messages()
+            .iter()
+            .filter(
+                |m| matches!(m, Message::SequencePaxos(p) if matches!(p.msg, PaxosMsg::Prepare(_))),
+            )
+            .count();
+    }
+    assert!(resent > 0);
+    let timed_out: Vec<Ballot> = leader_node
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            ConsensusEvent::PrepareTimedOut { ballot, .. } => Some(ballot),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(timed_out, vec![ballot]);
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "signing")]
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::Message,
+    omni_paxos::{OmniPaxos, OmniPaxosConfig},
+    util::{MessageSignature, MessageSigner, NodeId},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use utils::Value;
+
+/// A signer whose signature is the pid of the signing server followed by the signed bytes. Only for testing.
+struct PidSigner(NodeId);
+
+impl MessageSigner for PidSigner {
+    fn sign(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut signature = vec![self.0 as u8];
+        signature.extend_from_slice(bytes);
+        signature
+    }
+
+    fn verify(&self, pid: NodeId, bytes: &[u8], signature: &[u8]) -> bool {
+        signature.split_first() == Some((&(pid as u8), bytes))
+    }
+}
+
+/// Creates the node `pid` whose signing session is `session`.
+fn create_node(pid: NodeId, session: u64) -> OmniPaxos<Value, (), MemoryStorage<Value, ()>> {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = pid;
+    op_config.peers = [1, 2, 3].iter().filter(|p| **p != pid).cloned().collect();
+    op_config.configuration_id = 1;
+    let mut node = op_config.build(MemoryStorage::default());
+    node.set_message_signer(Box::new(PidSigner(pid)), session);
+    node
+}
+
+/// Returns the heartbeat request of `sender` to 2 and its stand-in serialization, which deserializes only to that request.
+fn heartbeat_request(
+    sender: &mut OmniPaxos<Value, (), MemoryStorage<Value, ()>>,
+) -> (Message<Value, ()>, Vec<u8>) {
+    let request = sender
+        .outgoing_messages()
+        .into_iter()
+        .find(|m| m.get_receiver() == 2)
+        .expect("No heartbeat request to 2");
+    (request, b"serialized heartbeat".to_vec())
+}
+
+/// Verifies that a message signed by its sender is handled, while a message with a spoofed sender is dropped and counted.
+#[test]
+fn handle_incoming_signed_test() {
+    let mut sender = create_node(1, 1);
+    let mut receiver = create_node(2, 1);
+    // the heartbeat requests of the initial round of the receiver
+    receiver.outgoing_messages();
+    let (request, bytes) = heartbeat_request(&mut sender);
+    let signature = sender.sign_message(&bytes).expect("No signer");
+    assert!(receiver.handle_incoming_signed(&bytes, &signature, |_| Some(request.clone())));
+    assert!(matches!(
+        receiver.outgoing_messages().as_slice(),
+        [Message::BLE(_)]
+    ));
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 0);
+
+    // server 3 forges a heartbeat of server 1
+    let mut forger = create_node(3, 1);
+    let forged_signature = forger.sign_message(&bytes).expect("No signer");
+    assert!(!receiver.handle_incoming_signed(&bytes, &forged_signature, |_| Some(request.clone())));
+    assert!(receiver.outgoing_messages().is_empty());
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 1);
+}
+
+/// Verifies that a message whose bytes were tampered with after signing is dropped and counted, even if they deserialize to a valid message.
+#[test]
+fn tampered_message_test() {
+    let mut sender = create_node(1, 1);
+    let mut receiver = create_node(2, 1);
+    receiver.outgoing_messages();
+    let (request, bytes) = heartbeat_request(&mut sender);
+    let signature = sender.sign_message(&bytes).expect("No signer");
+    let mut tampered = bytes.clone();
+    tampered[0] ^= 1;
+    assert!(!receiver.handle_incoming_signed(&tampered, &signature, |_| Some(request.clone())));
+    assert!(receiver.outgoing_messages().is_empty());
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 1);
+
+    // bytes that do not deserialize are dropped as well
+    assert!(!receiver.handle_incoming_signed(&bytes, &signature, |_| None));
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 2);
+
+    // the signature survives its encoding for the network
+    let decoded = MessageSignature::from_bytes(&signature.to_bytes()).expect("Too short");
+    assert_eq!(decoded, signature);
+    assert!(receiver.handle_incoming_signed(&bytes, &decoded, |_| Some(request.clone())));
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 2);
+}
+
+/// Verifies that a replayed signed message is dropped and counted, while signed messages that arrive out of order are handled.
+#[test]
+fn replayed_message_test() {
+    let mut sender = create_node(1, 1);
+    let mut receiver = create_node(2, 1);
+    receiver.outgoing_messages();
+    let (request, bytes) = heartbeat_request(&mut sender);
+    let first = sender.sign_message(&bytes).expect("No signer");
+    let second = sender.sign_message(&bytes).expect("No signer");
+    assert!(receiver.handle_incoming_signed(&bytes, &second, |_| Some(request.clone())));
+    assert!(receiver.handle_incoming_signed(&bytes, &first, |_| Some(request.clone())));
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 0);
+    receiver.outgoing_messages();
+
+    assert!(!receiver.handle_incoming_signed(&bytes, &first, |_| Some(request.clone())));
+    assert!(!receiver.handle_incoming_signed(&bytes, &second, |_| Some(request.clone())));
+    assert!(receiver.outgoing_messages().is_empty());
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 2);
+
+    // the messages of a restarted sender start a new session
+    let mut restarted = create_node(1, 2);
+    let (request, bytes) = heartbeat_request(&mut restarted);
+    let signature = restarted.sign_message(&bytes).expect("No signer");
+    assert!(signature.session > first.session);
+    assert!(receiver.handle_incoming_signed(&bytes, &signature, |_| Some(request.clone())));
+    // and the messages of the previous session are dropped
+    assert!(!receiver.handle_incoming_signed(&bytes, &first, |_| Some(request.clone())));
+    assert_eq!(receiver.unauthenticated_messages_dropped(), 3);
+}
+
+/// Verifies that a signed message that is redirected to another receiver than the one it was sent to is dropped and counted.
+#[test]
+fn redirected_message_test() {
+    let mut sender = create_node(1, 1);
+    let mut third = create_node(3, 1);
+    third.outgoing_messages();
+    let (request, bytes) = heartbeat_request(&mut sender);
+    let signature = sender.sign_message(&bytes).expect("No signer");
+    assert!(!third.handle_incoming_signed(&bytes, &signature, |_| Some(request.clone())));
+    assert!(third.outgoing_messages().is_empty());
+    assert_eq!(third.unauthenticated_messages_dropped(), 1);
+}
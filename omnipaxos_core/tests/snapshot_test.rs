@@ -4,12 +4,25 @@ use crate::utils::LatestValue;
 use kompact::prelude::{promise, Ask, FutureCollection};
 use omnipaxos_core::{
     ballot_leader_election::Ballot,
+    messages::{
+        sequence_paxos::{PaxosMessage, PaxosMsg},
+        Message,
+    },
+    omni_paxos::{BootstrapErr, OmniPaxos, OmniPaxosConfig, SnapshotTrigger},
     storage::Snapshot,
     util::{LogEntry, NodeId},
 };
+use omnipaxos_storage::memory_storage::MemoryStorage;
 use serial_test::serial;
-use std::thread;
-use utils::{TestConfig, TestSystem, Value};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+use utils::{
+    cluster::{create_cluster_with_storage, deliver_all, deliver_filtered, elect_leader, hb_round},
+    TestConfig, TestSystem, Value,
+};
 
 const TRIM_INDEX_INCREMENT: u64 = 10;
 
@@ -164,6 +177,164 @@ fn double_snapshot_test() {
     };
 }
 
+type Node = OmniPaxos<Value, LatestValue, MemoryStorage<Value, LatestValue>>;
+
+/// Verifies that a follower that missed a snapshot installs it when it reconnects, and that `on_snapshot_installed` is then called with the compacted index.
+#[test]
+fn on_snapshot_installed_test() {
+    let mut nodes: BTreeMap<NodeId, Node> =
+        create_cluster_with_storage(3, |c| c.resend_timeout = 1, MemoryStorage::default);
+    let installed = Arc::new(Mutex::new(vec![]));
+    for node in nodes.values_mut() {
+        let installed = installed.clone();
+        node.on_snapshot_installed(Box::new(move |idx| installed.lock().unwrap().push(idx)));
+    }
+    let leader = elect_leader(&mut nodes);
+    let lagging = *nodes.keys().find(|p| **p != leader).unwrap();
+
+    {
+        let leader = nodes.get_mut(&leader).unwrap();
+        for i in 1..=5 {
+            leader.append(Value(i)).expect("Failed to append");
+        }
+    }
+    deliver_all(&mut nodes, Some(lagging));
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .snapshot(None, false)
+        .expect("Failed to snapshot");
+    deliver_all(&mut nodes, Some(lagging));
+    assert!(installed.lock().unwrap().is_empty());
+
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(&mut nodes, None);
+    }
+    assert_eq!(*installed.lock().unwrap(), vec![5]);
+    assert_eq!(nodes[&lagging].get_compacted_idx(), 5);
+    match nodes[&lagging].read(0) {
+        Some(LogEntry::Snapshotted(s)) => assert_eq!(s.trimmed_idx, 5),
+        e => panic!("Expected a snapshot at the follower, got: {:?}", e),
+    }
+}
+
+/// Verifies that with `SnapshotTrigger::LogLength`, the leader snapshots the log on `tick()` once enough entries are decided, but not beyond the index accepted by a lagging follower.
+#[test]
+fn snapshot_trigger_test() {
+    let mut nodes: BTreeMap<NodeId, Node> = create_cluster_with_storage(
+        3,
+        |c| c.snapshot_trigger = SnapshotTrigger::LogLength(5),
+        MemoryStorage::default,
+    );
+    let leader = elect_leader(&mut nodes);
+    let lagging = *nodes.keys().find(|p| **p != leader).unwrap();
+    let follower = *nodes
+        .keys()
+        .find(|p| **p != leader && **p != lagging)
+        .unwrap();
+    let append = |nodes: &mut BTreeMap<NodeId, Node>, v: u64, partitioned: Option<NodeId>| {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(v))
+            .expect("Failed to append");
+        deliver_all(nodes, partitioned);
+        nodes.get_mut(&leader).unwrap().tick();
+        deliver_all(nodes, partitioned);
+    };
+    for i in 1..=4 {
+        append(&mut nodes, i, None);
+    }
+    assert_eq!(nodes[&leader].get_compacted_idx(), 0);
+
+    // the lagging follower has only accepted the first four entries when the fifth is decided
+    append(&mut nodes, 5, Some(lagging));
+    assert_eq!(nodes[&leader].get_decided_idx(), 5);
+    for pid in [leader, follower] {
+        assert_eq!(nodes[&pid].get_compacted_idx(), 4);
+        match nodes[&pid].read(0) {
+            Some(LogEntry::Snapshotted(s)) => {
+                assert_eq!(s.trimmed_idx, 4);
+                assert_eq!(s.snapshot, LatestValue::create(&[Value(4)]));
+            }
+            e => panic!("Expected a snapshot at {}, got: {:?}", pid, e),
+        }
+    }
+}
+
+/// Verifies that a new server bootstrapped from a snapshot is only synchronized with the entries after the snapshot, and that bootstrapping is rejected once a server has state.
+#[test]
+fn bootstrap_from_snapshot_test() {
+    let mut nodes: BTreeMap<NodeId, Node> =
+        create_cluster_with_storage(3, |c| c.resend_timeout = 1, MemoryStorage::default);
+    // server 3 is not started yet
+    let new_pid = 3;
+    for _ in 0..10 {
+        hb_round(&mut nodes, Some(new_pid));
+    }
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    assert_ne!(leader, new_pid);
+    let values: Vec<Value> = (1..=6).map(Value).collect();
+    for v in &values {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(*v)
+            .expect("Failed to append");
+    }
+    deliver_all(&mut nodes, Some(new_pid));
+    assert_eq!(
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .bootstrap_from_snapshot(LatestValue::default(), 4, 4),
+        Err(BootstrapErr::NotEmpty)
+    );
+
+    // the new server is started with the current leader as its initial leader
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = new_pid;
+    op_config.peers = vec![1, 2];
+    op_config.configuration_id = 1;
+    op_config.resend_timeout = 1;
+    op_config.initial_leader = nodes[&leader].get_current_leader_ballot();
+    let mut new_node: Node = op_config.build(MemoryStorage::default());
+    new_node.election_timeout();
+    let backup = LatestValue::create(&values[..4]);
+    assert_eq!(
+        new_node.bootstrap_from_snapshot(backup, 4, 6),
+        Err(BootstrapErr::InvalidDecidedIdx(6))
+    );
+    new_node
+        .bootstrap_from_snapshot(backup, 4, 4)
+        .expect("Failed to bootstrap");
+    nodes.insert(new_pid, new_node);
+    let mut sync_idx = None;
+    for _ in 0..5 {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_filtered(&mut nodes, |m| {
+            if let Message::SequencePaxos(PaxosMessage {
+                msg: PaxosMsg::AcceptSync(acc_sync),
+                ..
+            }) = m
+            {
+                sync_idx = Some(acc_sync.sync_idx);
+            }
+            false
+        });
+    }
+    assert_eq!(sync_idx, Some(4));
+    let new_node = &nodes[&new_pid];
+    assert_eq!(new_node.get_decided_idx(), 6);
+    match new_node.read(0) {
+        Some(LogEntry::Snapshotted(s)) => assert_eq!(s.snapshot, LatestValue::create(&values)),
+        e => panic!("Expected snapshot, got: {:?}", e),
+    }
+}
+
 fn check_snapshot(
     vec_proposals: Vec<Value>,
     seq_after: Vec<(u64, Vec<LogEntry<Value, LatestValue>>)>,
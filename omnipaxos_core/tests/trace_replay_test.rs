@@ -0,0 +1,61 @@
+#![cfg(feature = "testing")]
+pub mod utils;
+
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    messages::Message,
+    omni_paxos::OmniPaxosConfig,
+    testing::{TraceMismatch, TraceReplayer},
+    util::NodeId,
+};
+use utils::cluster::{create_cluster, deliver_filtered};
+
+fn config(pid: NodeId, all_pids: &[NodeId]) -> OmniPaxosConfig {
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = pid;
+    op_config.peers = all_pids.iter().filter(|p| **p != pid).cloned().collect();
+    op_config.configuration_id = 1;
+    op_config
+}
+
+/// Verifies that replaying the recorded heartbeats of a server elects the same leader at the same tick, and that a wrong expectation is reported.
+#[test]
+fn trace_replay_test() {
+    let all_pids: Vec<NodeId> = vec![1, 2, 3];
+    let mut nodes = create_cluster(3, |_| {});
+    let mut trace = vec![];
+    let mut elected_at = None;
+    for tick in 1..=5 {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        // record the heartbeat messages that server 1 receives in this round
+        deliver_filtered(&mut nodes, |m| {
+            match m {
+                Message::BLE(b) if b.to == 1 => trace.push((tick, b.clone())),
+                _ => {}
+            }
+            false
+        });
+        if elected_at.is_none() {
+            elected_at = nodes[&1].get_current_leader_ballot().map(|b| (tick, b));
+        }
+    }
+    let (tick, leader) = elected_at.expect("No leader has been elected");
+
+    TraceReplayer::with(config(1, &all_pids), trace.clone())
+        .expect_leader(tick, leader)
+        .expect_leader(5, leader)
+        .replay()
+        .expect("Replay elected another leader");
+
+    let wrong = Ballot::with(leader.n, leader.priority, leader.pid % 3 + 1);
+    assert_eq!(
+        TraceReplayer::with(config(1, &all_pids), trace)
+            .expect_leader(5, wrong)
+            .replay(),
+        Err(TraceMismatch {
+            tick: 5,
+            expected: wrong,
+            elected: Some(leader),
+        })
+    );
+}
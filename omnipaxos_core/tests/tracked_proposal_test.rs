@@ -0,0 +1,109 @@
+pub mod utils;
+
+use kompact::prelude::{promise, Ask};
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    omni_paxos::{OmniPaxosConfig, ProposalFailure, ProposeErr},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use serial_test::serial;
+use std::thread;
+use utils::{TestConfig, TestSystem, Value};
+
+/// Verifies that a tracked proposal at the leader is reported as completed once decided,
+/// and that tracked proposals are rejected at followers.
+#[test]
+#[serial]
+fn tracked_proposal_test() {
+    let cfg = TestConfig::load("proposal_test").expect("Test config loaded");
+
+    let mut sys = TestSystem::with(
+        cfg.num_nodes,
+        cfg.election_timeout,
+        cfg.num_threads,
+        cfg.storage_type,
+    );
+
+    let first_node = sys.nodes.get(&1).unwrap();
+    let (kprom_ble, kfuture_ble) = promise::<Ballot>();
+    first_node.on_definition(|x| x.election_futures.push(Ask::new(kprom_ble, ())));
+
+    sys.start_all_nodes();
+
+    let elected_leader = kfuture_ble
+        .wait_timeout(cfg.wait_timeout)
+        .expect("No leader has been elected in the allocated time!");
+
+    let follower_pid = (1..=cfg.num_nodes as u64)
+        .find(|pid| *pid != elected_leader.pid)
+        .unwrap();
+    let follower = sys.nodes.get(&follower_pid).unwrap();
+    // the follower might not have learned of the leader yet
+    let mut hint = None;
+    for _ in 0..10 {
+        hint = follower.on_definition(|x| match x.paxos.append_tracked(Value(0)) {
+            Err(ProposeErr::NotLeader(_, hint)) => hint,
+            res => panic!("Expected NotLeader error, got: {:?}", res),
+        });
+        if hint.is_some() {
+            break;
+        }
+        thread::sleep(cfg.wait_timeout / 10);
+    }
+    let hint = hint.expect("Follower returned no leader hint");
+    assert_eq!(hint.pid, elected_leader.pid, "Follower returned a stale leader hint");
+
+    let leader = sys.nodes.get(&elected_leader.pid).unwrap();
+    let v = Value(1);
+    let (kprom_px, kfuture_px) = promise::<Value>();
+    let proposal_id = leader.on_definition(|x| {
+        x.decided_futures.push(Ask::new(kprom_px, ()));
+        x.paxos.append_tracked(v).expect("Failed to call append_tracked")
+    });
+
+    let decided = kfuture_px
+        .wait_timeout(cfg.wait_timeout)
+        .expect("The message was not decided in the allocated time!");
+    assert_eq!(v, decided);
+
+    let (completed, failed) =
+        leader.on_definition(|x| (x.paxos.take_completed(), x.paxos.take_failed()));
+    assert_eq!(completed, vec![(proposal_id, 0)]);
+    assert!(failed.is_empty(), "Unexpected failed proposals: {:?}", failed);
+
+    let kompact_system =
+        std::mem::take(&mut sys.kompact_system).expect("No KompactSystem in memory");
+    match kompact_system.shutdown() {
+        Ok(_) => {}
+        Err(e) => panic!("Error on kompact shutdown: {}", e),
+    };
+}
+
+/// Verifies that a tracked proposal that cannot be decided is failed with `TimedOut` after `proposal_ttl` ticks.
+#[test]
+fn tracked_proposal_ttl_test() {
+    const TTL: u64 = 5;
+    let mut op_config = OmniPaxosConfig::default();
+    op_config.pid = 1;
+    op_config.peers = vec![2, 3];
+    op_config.configuration_id = 1;
+    op_config.fixed_leader = Some(1);
+    op_config.proposal_ttl = Some(TTL);
+    let mut omni_paxos = op_config.build(MemoryStorage::<Value, ()>::default());
+    omni_paxos.election_timeout();
+    // the peers never reply, so the proposal can never be decided
+    let proposal_id = omni_paxos
+        .append_tracked(Value(1))
+        .expect("Failed to call append_tracked");
+
+    for _ in 0..TTL - 1 {
+        omni_paxos.tick();
+    }
+    assert!(omni_paxos.take_failed().is_empty());
+    omni_paxos.tick();
+    assert_eq!(
+        omni_paxos.take_failed(),
+        vec![(proposal_id, ProposalFailure::TimedOut)]
+    );
+    assert!(omni_paxos.take_completed().is_empty());
+}
@@ -0,0 +1,154 @@
+pub mod utils;
+
+use omnipaxos_core::{
+    messages::{
+        ballot_leader_election::{BLEMessage, HeartbeatMsg},
+        Message,
+    },
+    omni_paxos::TransferErr,
+    util::{LogEntry, NodeId},
+};
+use utils::{
+    cluster::{create_cluster, deliver_all, deliver_filtered, elect_leader, hb_round, Node},
+    Value,
+};
+
+fn decided_values(node: &Node) -> Vec<Value> {
+    node.read_decided_suffix(0)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| match e {
+            LogEntry::Decided(v) => v,
+            e => panic!("Expected decided entry, got: {:?}", e),
+        })
+        .collect()
+}
+
+/// Verifies that the leadership is only transferred once the target has caught up, that the old leader holds back the entries appended
+/// after the `TimeoutNow` and forwards them to the target once it is elected, and that no entry is lost.
+#[test]
+fn transfer_leadership_test() {
+    let all_pids: Vec<NodeId> = vec![1, 2, 3];
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let target = *all_pids.iter().find(|pid| **pid != leader).unwrap();
+    let other = *all_pids
+        .iter()
+        .find(|pid| **pid != leader && **pid != target)
+        .unwrap();
+    assert!(matches!(
+        nodes
+            .get_mut(&target)
+            .unwrap()
+            .transfer_leadership_to(other),
+        Err(TransferErr::NotLeader(Some(_)))
+    ));
+    assert_eq!(
+        nodes.get_mut(&leader).unwrap().transfer_leadership_to(9),
+        Err(TransferErr::InvalidTarget(9))
+    );
+
+    // the target lags behind and is not sent a `TimeoutNow` until it caught up
+    for i in 0..3 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+    }
+    deliver_all(&mut nodes, Some(target));
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .transfer_leadership_to(target)
+        .expect("Failed to transfer the leadership");
+    assert_eq!(nodes[&leader].pending_transfer(), Some(target));
+    hb_round(&mut nodes, Some(target));
+    assert_eq!(nodes[&leader].get_current_leader(), Some(leader));
+
+    // the target catches up at the next resend and is sent a `TimeoutNow`
+    while nodes[&leader].pending_transfer().is_some() {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(&mut nodes, None);
+    }
+    // the old leader still takes appends, but does not replicate them to the log the target caught up with
+    let old_leader = nodes.get_mut(&leader).unwrap();
+    for i in 3..5 {
+        old_leader.append(Value(i)).expect("Failed to append");
+    }
+    assert!(old_leader.outgoing_messages().is_empty());
+
+    for _ in 0..3 {
+        hb_round(&mut nodes, None);
+    }
+    for (pid, node) in &nodes {
+        assert_eq!(
+            node.get_current_leader(),
+            Some(target),
+            "Unexpected leader at {}",
+            pid
+        );
+    }
+    nodes
+        .get_mut(&target)
+        .unwrap()
+        .append(Value(5))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, None);
+    let expected: Vec<Value> = (0..6).map(Value).collect();
+    for (pid, node) in &nodes {
+        assert_eq!(
+            decided_values(node),
+            expected,
+            "Unexpected decided entries at {}",
+            pid
+        );
+    }
+}
+
+/// Verifies that a lost `TimeoutNow` is resent after `resend_timeout` ticks.
+#[test]
+fn lost_timeout_now_test() {
+    const RESEND_TIMEOUT: u64 = 3;
+    let mut nodes = create_cluster(3, |c| c.resend_timeout = RESEND_TIMEOUT);
+    let leader = elect_leader(&mut nodes);
+    let target = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .transfer_leadership_to(target)
+        .expect("Failed to transfer the leadership");
+    let is_timeout_now = |m: &Message<Value, ()>| {
+        matches!(
+            m,
+            Message::BLE(BLEMessage {
+                msg: HeartbeatMsg::TimeoutNow(_),
+                ..
+            })
+        )
+    };
+    let mut lost = 0;
+    deliver_filtered(&mut nodes, |m| {
+        lost += is_timeout_now(m) as usize;
+        is_timeout_now(m)
+    });
+    assert_eq!(lost, 1);
+    hb_round(&mut nodes, None);
+    assert_eq!(nodes[&target].get_current_leader(), Some(leader));
+
+    let mut resent = 0;
+    for _ in 0..RESEND_TIMEOUT {
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_filtered(&mut nodes, |m| {
+            resent += is_timeout_now(m) as usize;
+            false
+        });
+    }
+    assert_eq!(resent, 1);
+    for _ in 0..3 {
+        hb_round(&mut nodes, None);
+    }
+    for node in nodes.values() {
+        assert_eq!(node.get_current_leader(), Some(target));
+    }
+}
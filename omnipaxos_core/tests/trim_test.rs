@@ -1,10 +1,19 @@
 pub mod utils;
 
 use kompact::prelude::{promise, Ask, FutureCollection};
-use omnipaxos_core::{ballot_leader_election::Ballot, util::NodeId};
+use omnipaxos_core::{
+    ballot_leader_election::Ballot,
+    omni_paxos::CompactionErr,
+    storage::Snapshot,
+    util::{LogEntry, NodeId},
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
 use serial_test::serial;
 use std::thread;
-use utils::{TestConfig, TestSystem, Value};
+use utils::{
+    cluster::{create_cluster, create_cluster_with_storage, deliver_all, elect_leader},
+    LatestValue, TestConfig, TestSystem, Value,
+};
 
 const TRIM_INDEX_INCREMENT: u64 = 10;
 
@@ -179,3 +188,139 @@ fn check_trim(
         }
     }
 }
+
+/// Verifies that an emergency trim keeps the last entries, but does not trim the entries a lagging follower still needs.
+#[test]
+fn emergency_trim_test() {
+    let mut nodes = create_cluster(3, |_| {});
+    let leader = elect_leader(&mut nodes);
+    let lagging = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let follower = *nodes
+        .keys()
+        .find(|pid| **pid != leader && **pid != lagging)
+        .unwrap();
+    for i in 0..10 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        // the lagging follower misses the last four entries
+        let partitioned = if i < 6 { None } else { Some(lagging) };
+        deliver_all(&mut nodes, partitioned);
+    }
+    match nodes.get_mut(&follower).unwrap().emergency_trim(2) {
+        Err(CompactionErr::NotCurrentLeader(Some(hint))) => assert_eq!(hint.pid, leader),
+        res => panic!("Expected NotCurrentLeader with a hint, got: {:?}", res),
+    }
+
+    let reclaimed = nodes
+        .get_mut(&leader)
+        .unwrap()
+        .emergency_trim(2)
+        .expect("Failed to trim");
+    assert_eq!(reclaimed, 6);
+    deliver_all(&mut nodes, Some(lagging));
+    for pid in [leader, follower].iter() {
+        assert_eq!(nodes[pid].get_compacted_idx(), 6);
+        match nodes[pid].read(6) {
+            Some(LogEntry::Decided(v)) => assert_eq!(v, Value(6)),
+            e => panic!("Expected decided entry at {}, got: {:?}", pid, e),
+        }
+    }
+}
+
+/// Verifies that with snapshots, an emergency trim keeps only the last entries even if a follower lags behind, and that the follower is synchronized with a snapshot.
+#[test]
+fn emergency_trim_snapshot_test() {
+    let mut nodes =
+        create_cluster_with_storage(3, |_| {}, MemoryStorage::<Value, LatestValue>::default);
+    let leader = elect_leader(&mut nodes);
+    let lagging = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    for i in 0..10 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        // the lagging follower misses the last four entries
+        let partitioned = if i < 6 { None } else { Some(lagging) };
+        deliver_all(&mut nodes, partitioned);
+    }
+
+    let reclaimed = nodes
+        .get_mut(&leader)
+        .unwrap()
+        .emergency_trim(2)
+        .expect("Failed to trim");
+    assert_eq!(reclaimed, 8);
+    deliver_all(&mut nodes, Some(lagging));
+    for (pid, node) in nodes.iter().filter(|(pid, _)| **pid != lagging) {
+        assert_eq!(
+            node.get_compacted_idx(),
+            8,
+            "Unexpected compacted index at {}",
+            pid
+        );
+        match node.read_decided_suffix(0).as_deref() {
+            Some(
+                [LogEntry::Snapshotted(s), LogEntry::Decided(Value(8)), LogEntry::Decided(Value(9))],
+            ) => {
+                assert_eq!(s.trimmed_idx, 8);
+                assert_eq!(s.snapshot, LatestValue::create(&[Value(7)]));
+            }
+            e => panic!("Unexpected log at {}: {:?}", pid, e),
+        }
+    }
+    // the lagging follower installs a snapshot of the entries it missed
+    nodes.get_mut(&lagging).unwrap().reconnected(leader);
+    deliver_all(&mut nodes, None);
+    match nodes[&lagging].read_decided_suffix(0).as_deref() {
+        Some([LogEntry::Snapshotted(s)]) => {
+            assert_eq!(s.trimmed_idx, 10);
+            assert_eq!(s.snapshot, LatestValue::create(&[Value(9)]));
+        }
+        e => panic!("Unexpected log at the lagging follower: {:?}", e),
+    }
+}
+
+/// Verifies that a lagging follower drags the minimum replicated index down until it has been silent for `follower_timeout` ticks,
+/// while trimming is still limited by the index accepted by all servers.
+#[test]
+fn min_replicated_idx_test() {
+    let mut nodes = create_cluster(3, |c| c.follower_timeout = Some(5));
+    let leader = elect_leader(&mut nodes);
+    let lagging = *nodes.keys().find(|pid| **pid != leader).unwrap();
+    let follower = *nodes
+        .keys()
+        .find(|pid| **pid != leader && **pid != lagging)
+        .unwrap();
+    for i in 0..10 {
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        // the lagging follower misses the last four entries
+        let partitioned = if i < 6 { None } else { Some(lagging) };
+        deliver_all(&mut nodes, partitioned);
+    }
+    assert_eq!(nodes[&leader].min_replicated_idx(), 6);
+    assert_eq!(nodes[&follower].min_replicated_idx(), 0);
+
+    // the follower keeps accepting new entries while the lagging follower stays silent
+    for i in 10..16 {
+        nodes.values_mut().for_each(|n| n.tick());
+        nodes
+            .get_mut(&leader)
+            .unwrap()
+            .append(Value(i))
+            .expect("Failed to append");
+        deliver_all(&mut nodes, Some(lagging));
+    }
+    assert_eq!(nodes[&leader].min_replicated_idx(), 16);
+    assert!(matches!(
+        nodes.get_mut(&leader).unwrap().trim(Some(8)),
+        Err(CompactionErr::NotAllDecided(6))
+    ));
+}
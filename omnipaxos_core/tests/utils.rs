@@ -1,3 +1,5 @@
+pub mod cluster;
+
 use self::omnireplica::OmniPaxosComponent;
 use commitlog::LogOptions;
 use kompact::{config_keys::system, executors::crossbeam_workstealing_pool, prelude::*};
@@ -445,6 +447,7 @@ pub mod omnireplica {
                 CHECK_DECIDED_TIMEOUT,
                 CHECK_DECIDED_TIMEOUT,
                 move |c, _| {
+                    c.paxos.tick();
                     c.send_outgoing_msgs();
                     c.answer_decided_future();
                     Handled::Ok
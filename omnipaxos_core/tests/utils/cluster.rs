@@ -0,0 +1,116 @@
+//! Helpers for tests that connect `OmniPaxos` servers directly, without the kompact components of `TestSystem`.
+use super::Value;
+use omnipaxos_core::{
+    messages::Message,
+    omni_paxos::{OmniPaxos, OmniPaxosConfig},
+    storage::{Entry, Snapshot, Storage},
+    util::NodeId,
+};
+use omnipaxos_storage::memory_storage::MemoryStorage;
+use std::collections::BTreeMap;
+
+/// A server of `Value`s with in-memory storage.
+pub type Node = OmniPaxos<Value, (), MemoryStorage<Value, ()>>;
+
+/// The servers of a cluster by pid. The map is ordered, such that the messages are delivered in the same order in every run.
+pub type Cluster<T, S, B> = BTreeMap<NodeId, OmniPaxos<T, S, B>>;
+
+/// Creates the servers `1..=num_nodes` of configuration `1` with in-memory storage, where `f` customizes the config of every server after its pid and peers are set.
+pub fn create_cluster<F>(num_nodes: u64, f: F) -> BTreeMap<NodeId, Node>
+where
+    F: Fn(&mut OmniPaxosConfig),
+{
+    create_cluster_with_storage(num_nodes, f, MemoryStorage::default)
+}
+
+/// Creates a cluster like `create_cluster()` with the storage returned by `storage` at every server.
+pub fn create_cluster_with_storage<T, S, B, F, G>(
+    num_nodes: u64,
+    f: F,
+    storage: G,
+) -> Cluster<T, S, B>
+where
+    T: Entry,
+    S: Snapshot<T>,
+    B: Storage<T, S>,
+    F: Fn(&mut OmniPaxosConfig),
+    G: Fn() -> B,
+{
+    let all_pids: Vec<NodeId> = (1..=num_nodes).collect();
+    all_pids
+        .iter()
+        .map(|pid| {
+            let mut op_config = OmniPaxosConfig::default();
+            op_config.pid = *pid;
+            op_config.peers = all_pids.iter().filter(|p| *p != pid).cloned().collect();
+            op_config.configuration_id = 1;
+            f(&mut op_config);
+            (*pid, op_config.build(storage()))
+        })
+        .collect()
+}
+
+/// Delivers outgoing messages between the servers until there are none left. Messages from and to `partitioned`, and to servers that are not in `nodes`, are lost.
+pub fn deliver_all<T, S, B>(nodes: &mut Cluster<T, S, B>, partitioned: Option<NodeId>)
+where
+    T: Entry,
+    S: Snapshot<T>,
+    B: Storage<T, S>,
+{
+    deliver_filtered(nodes, |m| {
+        partitioned.map_or(false, |p| m.get_sender() == p || m.get_receiver() == p)
+    })
+}
+
+/// Delivers outgoing messages between the servers until there are none left. Messages for which `drop` returns `true`, and messages to servers that are not in `nodes`, are lost.
+pub fn deliver_filtered<T, S, B, F>(nodes: &mut Cluster<T, S, B>, mut drop: F)
+where
+    T: Entry,
+    S: Snapshot<T>,
+    B: Storage<T, S>,
+    F: FnMut(&Message<T, S>) -> bool,
+{
+    loop {
+        let msgs: Vec<Message<T, S>> = nodes
+            .values_mut()
+            .flat_map(|n| n.outgoing_messages())
+            .collect();
+        if msgs.is_empty() {
+            break;
+        }
+        for m in msgs {
+            if drop(&m) {
+                continue;
+            }
+            if let Some(node) = nodes.get_mut(&m.get_receiver()) {
+                node.handle_incoming(m);
+            }
+        }
+    }
+}
+
+/// Runs a heartbeat round in which all messages from and to `partitioned` are lost.
+pub fn hb_round<T, S, B>(nodes: &mut Cluster<T, S, B>, partitioned: Option<NodeId>)
+where
+    T: Entry,
+    S: Snapshot<T>,
+    B: Storage<T, S>,
+{
+    nodes.values_mut().for_each(|n| n.election_timeout());
+    deliver_all(nodes, partitioned);
+}
+
+/// Runs heartbeat rounds until a leader is elected and known by server `1`. Returns the pid of the leader.
+pub fn elect_leader<T, S, B>(nodes: &mut Cluster<T, S, B>) -> NodeId
+where
+    T: Entry,
+    S: Snapshot<T>,
+    B: Storage<T, S>,
+{
+    for _ in 0..10 {
+        hb_round(nodes, None);
+    }
+    nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected")
+}
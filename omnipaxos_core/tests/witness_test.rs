@@ -0,0 +1,129 @@
+pub mod utils;
+
+use omnipaxos_core::util::{LogEntry, NodeId};
+use std::collections::BTreeMap;
+use utils::{
+    cluster::{create_cluster, deliver_all, Node},
+    Value,
+};
+
+const WITNESS: NodeId = 3;
+
+/// Runs a number of heartbeat rounds and ticks in which the `partitioned` node is disconnected.
+fn run_rounds(nodes: &mut BTreeMap<NodeId, Node>, partitioned: Option<NodeId>, rounds: usize) {
+    for _ in 0..rounds {
+        nodes.values_mut().for_each(|n| n.election_timeout());
+        deliver_all(nodes, partitioned);
+        nodes.values_mut().for_each(|n| n.tick());
+        deliver_all(nodes, partitioned);
+    }
+}
+
+/// Creates a cluster of two full nodes and the witness `WITNESS` with the highest leader priority, and elects a leader.
+/// Returns the nodes and the pid of the leader.
+fn create_witness_cluster() -> (BTreeMap<NodeId, Node>, NodeId) {
+    let mut nodes = create_cluster(3, |c| {
+        c.resend_timeout = 1;
+        c.witnesses = vec![WITNESS];
+        if c.pid == WITNESS {
+            c.leader_priority = 10;
+        }
+    });
+    run_rounds(&mut nodes, None, 10);
+    let leader = nodes[&1]
+        .get_current_leader()
+        .expect("No leader has been elected");
+    (nodes, leader)
+}
+
+fn other_full_node(leader: NodeId) -> NodeId {
+    if leader == 1 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Verifies that a witness is never elected and that it acknowledges entries without storing them.
+#[test]
+fn witness_never_leader_test() {
+    let (mut nodes, leader) = create_witness_cluster();
+    assert_ne!(leader, WITNESS, "The witness was elected as leader");
+    for node in nodes.values() {
+        assert_eq!(node.get_current_leader(), Some(leader));
+    }
+
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    run_rounds(&mut nodes, None, 2);
+    for pid in [1, 2] {
+        match nodes[&pid].read(0) {
+            Some(LogEntry::Decided(v)) => assert_eq!(v, Value(1)),
+            e => panic!("Expected decided entry at {}, got: {:?}", pid, e),
+        }
+    }
+    assert_eq!(nodes[&WITNESS].get_decided_idx(), 1);
+    match nodes[&WITNESS].read(0) {
+        Some(LogEntry::Trimmed(_)) => {}
+        e => panic!("Expected trimmed entry at the witness, got: {:?}", e),
+    }
+}
+
+/// Verifies that an entry is decided with the acknowledgement of the witness while the other full node is partitioned.
+#[test]
+fn witness_quorum_test() {
+    let (mut nodes, leader) = create_witness_cluster();
+    let follower = other_full_node(leader);
+
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, Some(follower));
+    match nodes[&leader].read(0) {
+        Some(LogEntry::Decided(v)) => assert_eq!(v, Value(1)),
+        e => panic!("Expected decided entry at the leader, got: {:?}", e),
+    }
+    assert!(nodes[&follower].read(0).is_none());
+}
+
+/// Verifies that a full node elected with only the witness does not make progress until a full node that has the entries
+/// accepted by the witness is reachable, and that it then recovers these entries.
+#[test]
+fn witness_recovery_test() {
+    let (mut nodes, leader) = create_witness_cluster();
+    let follower = other_full_node(leader);
+
+    // decide an entry that only the leader stores
+    nodes
+        .get_mut(&leader)
+        .unwrap()
+        .append(Value(1))
+        .expect("Failed to append");
+    deliver_all(&mut nodes, Some(follower));
+    assert_eq!(nodes[&leader].get_decided_idx(), 1);
+
+    // the follower takes over with only the witness
+    run_rounds(&mut nodes, Some(leader), 10);
+    assert_eq!(nodes[&follower].get_current_leader(), Some(follower));
+    nodes
+        .get_mut(&follower)
+        .unwrap()
+        .append(Value(2))
+        .expect("Failed to append");
+    run_rounds(&mut nodes, Some(leader), 2);
+    assert_eq!(nodes[&follower].get_decided_idx(), 0);
+    assert!(nodes[&follower].read(0).is_none());
+
+    // the former leader reconnects
+    run_rounds(&mut nodes, None, 2);
+    assert_eq!(nodes[&follower].get_current_leader(), Some(follower));
+    for pid in [1, 2] {
+        let entries: Vec<Value> = nodes[&pid].decided_iter().collect();
+        assert_eq!(entries, vec![Value(1), Value(2)]);
+    }
+}
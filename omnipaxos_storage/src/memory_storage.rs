@@ -1,7 +1,12 @@
 use omnipaxos_core::{
     ballot_leader_election::Ballot,
-    storage::{Entry, Snapshot, StopSignEntry, Storage},
+    storage::{Entry, Snapshot, StopSignEntry, Storage, StorageErr},
 };
+use std::sync::Arc;
+
+/// A callback that is called with the entries that are removed from the log when it is trimmed.
+pub type EvictionHook<T> = Arc<dyn Fn(&[T]) + Send + Sync>;
+
 /// An in-memory storage implementation for SequencePaxos.
 #[derive(Clone)]
 pub struct MemoryStorage<T, S>
@@ -23,6 +28,46 @@ where
     snapshot: Option<S>,
     /// Stored StopSign
     stopsign: Option<StopSignEntry>,
+    /// Maximum number of entries in the log.
+    max_entries: Option<u64>,
+    /// Maximum total size of the entries in the log.
+    max_bytes: Option<usize>,
+    /// Returns the size of an entry. Only set together with `max_bytes`.
+    size_of: Option<fn(&T) -> usize>,
+    /// Total size of the entries in the log. Only maintained if `max_bytes` is set.
+    log_bytes: usize,
+    /// Called with the entries that are removed from the log when it is trimmed.
+    eviction_hook: Option<EvictionHook<T>>,
+}
+
+impl<T, S> MemoryStorage<T, S>
+where
+    T: Entry,
+    S: Snapshot<T>,
+{
+    /// Limits the log to `max_entries` entries that have not been trimmed. If `None`, the number of entries is unlimited.
+    pub fn set_max_entries(&mut self, max_entries: Option<u64>) {
+        self.max_entries = max_entries;
+    }
+
+    /// Limits the total size of the entries in the log that have not been trimmed to `max_bytes`, where `size_of` returns the size of an entry.
+    pub fn set_max_bytes(&mut self, max_bytes: usize, size_of: fn(&T) -> usize) {
+        self.log_bytes = self.log.iter().map(size_of).sum();
+        self.max_bytes = Some(max_bytes);
+        self.size_of = Some(size_of);
+    }
+
+    /// Sets a callback that is called with the entries that are removed from the log when it is trimmed.
+    pub fn set_eviction_hook(&mut self, hook: EvictionHook<T>) {
+        self.eviction_hook = Some(hook);
+    }
+
+    fn size_of(&self, entries: &[T]) -> usize {
+        match self.size_of {
+            Some(size_of) => entries.iter().map(size_of).sum(),
+            None => 0,
+        }
+    }
 }
 
 impl<T, S> Storage<T, S> for MemoryStorage<T, S>
@@ -31,18 +76,22 @@ where
     S: Snapshot<T>,
 {
     fn append_entry(&mut self, entry: T) -> u64 {
+        self.log_bytes += self.size_of(std::slice::from_ref(&entry));
         self.log.push(entry);
         self.get_log_len()
     }
 
     fn append_entries(&mut self, entries: Vec<T>) -> u64 {
         let mut e = entries;
+        self.log_bytes += self.size_of(&e);
         self.log.append(&mut e);
         self.get_log_len()
     }
 
     fn append_on_prefix(&mut self, from_idx: u64, entries: Vec<T>) -> u64 {
-        self.log.truncate(from_idx as usize);
+        let from_idx = (from_idx as usize).min(self.log.len());
+        self.log_bytes -= self.size_of(&self.log[from_idx..]);
+        self.log.truncate(from_idx);
         self.append_entries(entries)
     }
 
@@ -97,8 +146,12 @@ where
     }
 
     fn trim(&mut self, trimmed_idx: u64) {
-        self.log
-            .drain(0..(trimmed_idx as usize).min(self.log.len()));
+        let trimmed_idx = (trimmed_idx as usize).min(self.log.len());
+        self.log_bytes -= self.size_of(&self.log[..trimmed_idx]);
+        if let Some(hook) = &self.eviction_hook {
+            hook(&self.log[..trimmed_idx]);
+        }
+        self.log.drain(0..trimmed_idx);
     }
 
     fn set_compacted_idx(&mut self, trimmed_idx: u64) {
@@ -116,6 +169,26 @@ where
     fn get_snapshot(&self) -> Option<S> {
         self.snapshot.clone()
     }
+
+    fn check_capacity(&self, num_entries: u64, size: usize) -> Result<(), StorageErr> {
+        let max_entries_exceeded = match self.max_entries {
+            Some(max) => self.log.len() as u64 + num_entries > max,
+            None => false,
+        };
+        let max_bytes_exceeded = match self.max_bytes {
+            Some(max) => self.log_bytes + size > max,
+            None => false,
+        };
+        if max_entries_exceeded || max_bytes_exceeded {
+            Err(StorageErr::Full)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn entries_size(&self, entries: &[T]) -> usize {
+        self.size_of(entries)
+    }
 }
 
 impl<T: Entry, S: Snapshot<T>> Default for MemoryStorage<T, S> {
@@ -128,6 +201,11 @@ impl<T: Entry, S: Snapshot<T>> Default for MemoryStorage<T, S> {
             trimmed_idx: 0,
             snapshot: None,
             stopsign: None,
+            max_entries: None,
+            max_bytes: None,
+            size_of: None,
+            log_bytes: 0,
+            eviction_hook: None,
         }
     }
 }